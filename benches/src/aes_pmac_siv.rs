@@ -6,7 +6,7 @@
 extern crate criterion;
 
 use criterion::{Benchmark, Criterion, Throughput};
-use miscreant::Aes128PmacSiv;
+use miscreant::{Aead, Aes128PmacSivAead};
 
 // WARNING: Do not ever actually use a key of all zeroes
 // NOTE: AES-(PMAC-)SIV keys are 2 * the security level, since they include
@@ -16,14 +16,14 @@ const NONCE: [u8; 12] = [0u8; 12];
 
 fn aes_pmac_siv_128_encrypt_benchmark(msg_size: usize) -> Benchmark {
     Benchmark::new(format!("encrypt ({} bytes)", msg_size), move |b| {
-        let mut siv = Aes128PmacSiv::new(&KEY_256_BIT);
+        let mut siv = Aes128PmacSivAead::new(&KEY_256_BIT);
 
         // Plaintext length + 16-byte tag
         let mut buffer = vec![0u8; msg_size + 16];
 
-        b.iter(|| siv.seal_in_place(&[NONCE], &mut buffer));
+        b.iter(|| siv.encrypt_in_place(&NONCE, b"", &mut buffer));
     })
-    .throughput(Throughput::Bytes(msg_size as u32))
+    .throughput(Throughput::Bytes(msg_size as u64))
 }
 
 fn aes_pmac_siv_128_encrypt_128_bytes(c: &mut Criterion) {
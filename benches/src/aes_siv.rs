@@ -6,7 +6,7 @@
 extern crate criterion;
 
 use criterion::{Benchmark, Criterion, Throughput};
-use miscreant::Aes128Siv;
+use miscreant::{Aead, Aes128SivAead};
 
 // WARNING: Do not ever actually use a key of all zeroes
 // NOTE: AES-SIV keys are 2 * the security level, since they include
@@ -16,14 +16,14 @@ const NONCE: [u8; 12] = [0u8; 12];
 
 fn aes_siv_128_encrypt_benchmark(msg_size: usize) -> Benchmark {
     Benchmark::new(format!("encrypt ({} bytes)", msg_size), move |b| {
-        let mut siv = Aes128Siv::new(&KEY_256_BIT);
+        let mut siv = Aes128SivAead::new(&KEY_256_BIT);
 
         // Plaintext length + 16-byte tag
         let mut buffer = vec![0u8; msg_size + 16];
 
-        b.iter(|| siv.seal_in_place(&[NONCE], &mut buffer));
+        b.iter(|| siv.encrypt_in_place(&NONCE, b"", &mut buffer));
     })
-    .throughput(Throughput::Bytes(msg_size as u32))
+    .throughput(Throughput::Bytes(msg_size as u64))
 }
 
 fn aes_siv_128_encrypt_128_bytes(c: &mut Criterion) {
@@ -38,10 +38,44 @@ fn aes_siv_128_encrypt_16384_bytes(c: &mut Criterion) {
     c.bench("AES-128-CMAC-SIV", aes_siv_128_encrypt_benchmark(16384));
 }
 
+fn aes_siv_128_encrypt_vec_benchmark(msg_size: usize) -> Benchmark {
+    Benchmark::new(format!("encrypt to Vec ({} bytes)", msg_size), move |b| {
+        let mut siv = Aes128SivAead::new(&KEY_256_BIT);
+        let plaintext = vec![0u8; msg_size];
+
+        b.iter(|| siv.encrypt(&NONCE, b"", &plaintext));
+    })
+    .throughput(Throughput::Bytes(msg_size as u64))
+}
+
+fn aes_siv_128_decrypt_vec_benchmark(msg_size: usize) -> Benchmark {
+    Benchmark::new(format!("decrypt to Vec ({} bytes)", msg_size), move |b| {
+        let mut siv = Aes128SivAead::new(&KEY_256_BIT);
+        let ciphertext = siv.encrypt(&NONCE, b"", &vec![0u8; msg_size]);
+
+        b.iter(|| siv.decrypt(&NONCE, b"", &ciphertext).unwrap());
+    })
+    .throughput(Throughput::Bytes(msg_size as u64))
+}
+
+fn aes_siv_128_encrypt_vec_16384_bytes(c: &mut Criterion) {
+    c.bench("AES-128-CMAC-SIV", aes_siv_128_encrypt_vec_benchmark(16384));
+}
+
+fn aes_siv_128_decrypt_vec_16384_bytes(c: &mut Criterion) {
+    c.bench("AES-128-CMAC-SIV", aes_siv_128_decrypt_vec_benchmark(16384));
+}
+
 criterion_group! {
     name = aes_siv_128_encrypt;
     config = Criterion::default();
     targets = aes_siv_128_encrypt_128_bytes, aes_siv_128_encrypt_1024_bytes, aes_siv_128_encrypt_16384_bytes
 }
 
-criterion_main!(aes_siv_128_encrypt);
+criterion_group! {
+    name = aes_siv_128_alloc;
+    config = Criterion::default();
+    targets = aes_siv_128_encrypt_vec_16384_bytes, aes_siv_128_decrypt_vec_16384_bytes
+}
+
+criterion_main!(aes_siv_128_encrypt, aes_siv_128_alloc);
@@ -0,0 +1,61 @@
+//! Decrypt arbitrary ciphertexts with `Aead::decrypt`
+//!
+//! Input: a 1-byte algorithm selector, a key of that algorithm's size, then
+//! a 1-byte associated data length and the associated data, a 1-byte nonce
+//! length and the nonce, and the rest of the input as the ciphertext.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use miscreant::{new_dyn, Algorithm};
+
+const ALGORITHMS: [Algorithm; 6] = [
+    Algorithm::Aes128Siv,
+    Algorithm::Aes192Siv,
+    Algorithm::Aes256Siv,
+    Algorithm::Aes128PmacSiv,
+    Algorithm::Aes192PmacSiv,
+    Algorithm::Aes256PmacSiv,
+];
+
+fuzz_target!(|data: &[u8]| {
+    let (&selector, data) = match data.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+
+    let alg = ALGORITHMS[selector as usize % ALGORITHMS.len()];
+    let (key, data) = match take(data, alg.key_size()) {
+        Some(split) => split,
+        None => return,
+    };
+    let (ad, data) = match take_prefixed(data) {
+        Some(split) => split,
+        None => return,
+    };
+    let (nonce, ciphertext) = match take_prefixed(data) {
+        Some(split) => split,
+        None => return,
+    };
+
+    let mut aead = new_dyn(alg, key).unwrap();
+
+    if let Ok(plaintext) = aead.decrypt(nonce, ad, ciphertext) {
+        // SIV is deterministic, so anything which decrypts is exactly what
+        // encrypting the plaintext produces
+        assert_eq!(aead.encrypt(nonce, ad, &plaintext), ciphertext);
+    }
+});
+
+fn take(data: &[u8], len: usize) -> Option<(&[u8], &[u8])> {
+    if data.len() < len {
+        None
+    } else {
+        Some(data.split_at(len))
+    }
+}
+
+fn take_prefixed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&len, data) = data.split_first()?;
+    take(data, len as usize)
+}
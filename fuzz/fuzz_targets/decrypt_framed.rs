@@ -0,0 +1,34 @@
+//! Decrypt arbitrary framed messages with `decrypt_framed`
+//!
+//! Input: a 1-byte key length and the key, a 1-byte associated data length
+//! and the associated data, and the rest of the input as the framed message.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use miscreant::{decrypt_framed, FRAME_VERSION};
+
+fuzz_target!(|data: &[u8]| {
+    let (key, data) = match take_prefixed(data) {
+        Some(split) => split,
+        None => return,
+    };
+    let (ad, framed) = match take_prefixed(data) {
+        Some(split) => split,
+        None => return,
+    };
+
+    if decrypt_framed(key, ad, framed).is_ok() {
+        assert_eq!(framed[0], FRAME_VERSION);
+    }
+});
+
+fn take_prefixed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&len, data) = data.split_first()?;
+
+    if data.len() < len as usize {
+        None
+    } else {
+        Some(data.split_at(len as usize))
+    }
+}
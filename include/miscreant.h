@@ -6,6 +6,7 @@
  * C99 support is assumed.
  */
 
+#include <stddef.h>
 #include <stdint.h>
 
 /********************
@@ -127,3 +128,209 @@ int crypto_aead_aes256pmacsiv_decrypt(
     const uint8_t *ad, uint64_t adlen,
     const uint8_t *key
 );
+
+/******************************************************************
+ * Reusable AEAD and STREAM instances (requires the `ffi` feature) *
+ ******************************************************************/
+
+// Status codes returned by the miscreant_* functions. Every length is
+// validated, and input and output buffers must not overlap. If an output
+// buffer is too small, its size (e.g. *ctlen_p) is set to the required
+// size and MISCREANT_ERR_BUFFER_TOO_SMALL is returned.
+#define MISCREANT_OK                          0
+#define MISCREANT_ERR_AUTHENTICATION_FAILED  -1
+#define MISCREANT_ERR_BUFFER_TOO_SMALL       -2
+#define MISCREANT_ERR_COUNTER_OVERFLOW       -3
+#define MISCREANT_ERR_ENCODING               -4
+#define MISCREANT_ERR_INVALID_KEY_SIZE       -5
+#define MISCREANT_ERR_INVALID_NONCE_SIZE     -6
+#define MISCREANT_ERR_INVALID_TAG_SIZE       -7
+#define MISCREANT_ERR_UNEXPECTED_LAST_BLOCK  -8
+#define MISCREANT_ERR_NULL_POINTER           -9
+#define MISCREANT_ERR_FINISHED              -10
+#define MISCREANT_ERR_PANIC                 -11
+#define MISCREANT_ERR_UNSUPPORTED_ALGORITHM -12
+#define MISCREANT_ERR_UNSUPPORTED_VERSION   -13
+#define MISCREANT_ERR_NONCE_REUSED          -14
+#define MISCREANT_ERR_MESSAGE_TOO_LONG      -15
+
+typedef struct miscreant_aes128siv miscreant_aes128siv;
+typedef struct miscreant_aes256siv miscreant_aes256siv;
+typedef struct miscreant_aes128siv_stream_encryptor miscreant_aes128siv_stream_encryptor;
+typedef struct miscreant_aes128siv_stream_decryptor miscreant_aes128siv_stream_decryptor;
+typedef struct miscreant_aes256siv_stream_encryptor miscreant_aes256siv_stream_encryptor;
+typedef struct miscreant_aes256siv_stream_decryptor miscreant_aes256siv_stream_decryptor;
+
+// Allocate a new AES-128-SIV instance with the given 32-byte key (which is
+// validated), storing it in *out. Free it with miscreant_aes128siv_free.
+int miscreant_aes128siv_new(miscreant_aes128siv **out, const uint8_t *key, size_t keylen);
+
+// AES-128-SIV authenticated encryption. *ctlen_p is the size of the ct buffer,
+// and is set to the length of the ciphertext (msglen + 16).
+int miscreant_aes128siv_encrypt(
+    miscreant_aes128siv *aead,
+    uint8_t *ct, size_t *ctlen_p,
+    const uint8_t *msg, size_t msglen,
+    const uint8_t *nonce, size_t noncelen,
+    const uint8_t *ad, size_t adlen
+);
+
+// AES-128-SIV authenticated decryption. *msglen_p is the size of the msg buffer,
+// and is set to the length of the plaintext (ctlen - 16).
+int miscreant_aes128siv_decrypt(
+    miscreant_aes128siv *aead,
+    uint8_t *msg, size_t *msglen_p,
+    const uint8_t *ct, size_t ctlen,
+    const uint8_t *nonce, size_t noncelen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Free an AES-128-SIV instance, zeroizing its key (does nothing if aead is NULL)
+void miscreant_aes128siv_free(miscreant_aes128siv *aead);
+
+// Allocate a new AES-256-SIV instance with the given 64-byte key (which is
+// validated), storing it in *out. Free it with miscreant_aes256siv_free.
+int miscreant_aes256siv_new(miscreant_aes256siv **out, const uint8_t *key, size_t keylen);
+
+// AES-256-SIV authenticated encryption. *ctlen_p is the size of the ct buffer,
+// and is set to the length of the ciphertext (msglen + 16).
+int miscreant_aes256siv_encrypt(
+    miscreant_aes256siv *aead,
+    uint8_t *ct, size_t *ctlen_p,
+    const uint8_t *msg, size_t msglen,
+    const uint8_t *nonce, size_t noncelen,
+    const uint8_t *ad, size_t adlen
+);
+
+// AES-256-SIV authenticated decryption. *msglen_p is the size of the msg buffer,
+// and is set to the length of the plaintext (ctlen - 16).
+int miscreant_aes256siv_decrypt(
+    miscreant_aes256siv *aead,
+    uint8_t *msg, size_t *msglen_p,
+    const uint8_t *ct, size_t ctlen,
+    const uint8_t *nonce, size_t noncelen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Free an AES-256-SIV instance, zeroizing its key (does nothing if aead is NULL)
+void miscreant_aes256siv_free(miscreant_aes256siv *aead);
+
+// Allocate a new AES-128-SIV STREAM encryptor with the given key and 8-byte nonce,
+// storing it in *out. Free it with miscreant_aes128siv_stream_encryptor_free.
+int miscreant_aes128siv_stream_encryptor_new(
+    miscreant_aes128siv_stream_encryptor **out,
+    const uint8_t *key, size_t keylen,
+    const uint8_t *nonce, size_t noncelen
+);
+
+// Encrypt the next message in the STREAM. *ctlen_p is the size of the ct
+// buffer, and is set to the length of the ciphertext (msglen + 16).
+int miscreant_aes128siv_stream_encrypt_next(
+    miscreant_aes128siv_stream_encryptor *encryptor,
+    uint8_t *ct, size_t *ctlen_p,
+    const uint8_t *msg, size_t msglen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Encrypt the last message in the STREAM. The encryptor can't be used to
+// encrypt any further messages afterward (but must still be freed).
+int miscreant_aes128siv_stream_encrypt_last(
+    miscreant_aes128siv_stream_encryptor *encryptor,
+    uint8_t *ct, size_t *ctlen_p,
+    const uint8_t *msg, size_t msglen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Free an AES-128-SIV STREAM encryptor (does nothing if encryptor is NULL)
+void miscreant_aes128siv_stream_encryptor_free(miscreant_aes128siv_stream_encryptor *encryptor);
+
+// Allocate a new AES-128-SIV STREAM decryptor with the given key and 8-byte nonce,
+// storing it in *out. Free it with miscreant_aes128siv_stream_decryptor_free.
+int miscreant_aes128siv_stream_decryptor_new(
+    miscreant_aes128siv_stream_decryptor **out,
+    const uint8_t *key, size_t keylen,
+    const uint8_t *nonce, size_t noncelen
+);
+
+// Decrypt the next message in the STREAM. *msglen_p is the size of the msg
+// buffer, and is set to the length of the plaintext (ctlen - 16). Returns
+// MISCREANT_ERR_UNEXPECTED_LAST_BLOCK if this is the last message, which
+// must be decrypted with miscreant_aes128siv_stream_decrypt_last instead.
+int miscreant_aes128siv_stream_decrypt_next(
+    miscreant_aes128siv_stream_decryptor *decryptor,
+    uint8_t *msg, size_t *msglen_p,
+    const uint8_t *ct, size_t ctlen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Decrypt the last message in the STREAM. The decryptor can't be used to
+// decrypt any further messages afterward (but must still be freed).
+int miscreant_aes128siv_stream_decrypt_last(
+    miscreant_aes128siv_stream_decryptor *decryptor,
+    uint8_t *msg, size_t *msglen_p,
+    const uint8_t *ct, size_t ctlen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Free an AES-128-SIV STREAM decryptor (does nothing if decryptor is NULL)
+void miscreant_aes128siv_stream_decryptor_free(miscreant_aes128siv_stream_decryptor *decryptor);
+
+// Allocate a new AES-256-SIV STREAM encryptor with the given key and 8-byte nonce,
+// storing it in *out. Free it with miscreant_aes256siv_stream_encryptor_free.
+int miscreant_aes256siv_stream_encryptor_new(
+    miscreant_aes256siv_stream_encryptor **out,
+    const uint8_t *key, size_t keylen,
+    const uint8_t *nonce, size_t noncelen
+);
+
+// Encrypt the next message in the STREAM. *ctlen_p is the size of the ct
+// buffer, and is set to the length of the ciphertext (msglen + 16).
+int miscreant_aes256siv_stream_encrypt_next(
+    miscreant_aes256siv_stream_encryptor *encryptor,
+    uint8_t *ct, size_t *ctlen_p,
+    const uint8_t *msg, size_t msglen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Encrypt the last message in the STREAM. The encryptor can't be used to
+// encrypt any further messages afterward (but must still be freed).
+int miscreant_aes256siv_stream_encrypt_last(
+    miscreant_aes256siv_stream_encryptor *encryptor,
+    uint8_t *ct, size_t *ctlen_p,
+    const uint8_t *msg, size_t msglen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Free an AES-256-SIV STREAM encryptor (does nothing if encryptor is NULL)
+void miscreant_aes256siv_stream_encryptor_free(miscreant_aes256siv_stream_encryptor *encryptor);
+
+// Allocate a new AES-256-SIV STREAM decryptor with the given key and 8-byte nonce,
+// storing it in *out. Free it with miscreant_aes256siv_stream_decryptor_free.
+int miscreant_aes256siv_stream_decryptor_new(
+    miscreant_aes256siv_stream_decryptor **out,
+    const uint8_t *key, size_t keylen,
+    const uint8_t *nonce, size_t noncelen
+);
+
+// Decrypt the next message in the STREAM. *msglen_p is the size of the msg
+// buffer, and is set to the length of the plaintext (ctlen - 16). Returns
+// MISCREANT_ERR_UNEXPECTED_LAST_BLOCK if this is the last message, which
+// must be decrypted with miscreant_aes256siv_stream_decrypt_last instead.
+int miscreant_aes256siv_stream_decrypt_next(
+    miscreant_aes256siv_stream_decryptor *decryptor,
+    uint8_t *msg, size_t *msglen_p,
+    const uint8_t *ct, size_t ctlen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Decrypt the last message in the STREAM. The decryptor can't be used to
+// decrypt any further messages afterward (but must still be freed).
+int miscreant_aes256siv_stream_decrypt_last(
+    miscreant_aes256siv_stream_decryptor *decryptor,
+    uint8_t *msg, size_t *msglen_p,
+    const uint8_t *ct, size_t ctlen,
+    const uint8_t *ad, size_t adlen
+);
+
+// Free an AES-256-SIV STREAM decryptor (does nothing if decryptor is NULL)
+void miscreant_aes256siv_stream_decryptor_free(miscreant_aes256siv_stream_decryptor *decryptor);
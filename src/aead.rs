@@ -3,25 +3,182 @@
 //! and authenticity.
 
 use crate::{
-    generic_array::{typenum::U16, ArrayLength, GenericArray},
-    Error,
+    associated_data::AssociatedData,
+    backend::{self, Aes128, Aes192, Aes256, Backend},
+    generic_array::{
+        typenum::{Unsigned, U16},
+        ArrayLength, GenericArray,
+    },
+    incremental::{Keystream, SivEncryptor},
+    Error, Key,
 };
-use aes::{Aes128, Aes256};
-use aes_siv::siv::{Siv, IV_SIZE};
+use aes_siv::siv::{Siv, IV_SIZE, MAX_HEADERS};
 use cmac::Cmac;
-use core::ops::Add;
+use core::{convert::TryFrom, fmt, iter, ops::Add};
 use crypto_mac::Mac;
 use ctr::Ctr128;
 use stream_cipher::{NewStreamCipher, SyncStreamCipher};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
 
 #[cfg(feature = "alloc")]
-use alloc::vec::Vec;
+use crate::{SealedMessage, NONCE_SIZE};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+#[cfg(feature = "alloc")]
+use zeroize::Zeroizing;
+
+#[cfg(feature = "encoding")]
+use alloc::string::String;
+#[cfg(feature = "encoding")]
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+#[cfg(feature = "bytes")]
+use bytes::{Buf, BytesMut};
+#[cfg(feature = "encoding")]
+use subtle_encoding::hex;
 
 #[cfg(feature = "pmac")]
 use pmac_crate::Pmac;
 
+#[cfg(feature = "getrandom")]
+use rand_core::OsRng;
+#[cfg(feature = "rand_core")]
+use rand_core::{CryptoRng, RngCore};
+
+#[cfg(feature = "hkdf")]
+use hkdf_crate::Hkdf;
+#[cfg(feature = "hkdf")]
+use sha2::Sha256;
+#[cfg(feature = "committing")]
+use sha2::{Digest, Sha256 as CommitmentHash};
+
+#[cfg(feature = "secrecy")]
+use secrecy::{ExposeSecret, Secret};
+
+#[cfg(all(feature = "pmac", feature = "rayon"))]
+use crate::parallel;
+#[cfg(all(feature = "pmac", feature = "rayon"))]
+use block_cipher_trait::BlockCipher;
+
+/// Size of the key commitment prepended to messages by
+/// `SivAead::encrypt_committing`, in bytes
+#[cfg(feature = "committing")]
+pub const KEY_COMMITMENT_SIZE: usize = 32;
+
+/// Domain separation label for key commitments
+#[cfg(feature = "committing")]
+const KEY_COMMITMENT_LABEL: &[u8] = b"miscreant key commitment v1";
+
+/// Maximum length of a message (plaintext) in bytes: 2^31 AES blocks, or
+/// 32 GiB.
+///
+/// SIV clears the 31st and 63rd bits (from the right) of the synthetic IV
+/// before using it as the initial CTR counter block, so that the counter
+/// can be incremented with 32-bit or 64-bit integer arithmetic (RFC 5297
+/// Section 2.6). This guarantees at least 2^31 blocks can be encrypted
+/// before the counter's low 32 bits wrap, so longer messages are rejected
+/// rather than risking a counter wrap in an implementation which relies on
+/// that guarantee.
+pub const MAX_MESSAGE_LEN: u64 = 1 << 35;
+
+/// Minimum length of a truncated tag (see
+/// `SivAead::encrypt_in_place_detached_truncated`) in bytes
+pub const MIN_TRUNCATED_TAG_SIZE: usize = 8;
+
 /// AES-SIV tags (which have a dual role as the synthetic IV)
-pub type Tag = GenericArray<u8, U16>;
+///
+/// Tags are compared in constant time, both with `subtle::ConstantTimeEq`
+/// and with `==` (whose `PartialEq` impl is built on `ct_eq`). Every tag
+/// check in this crate does so (either directly, or via the `aes-siv`
+/// crate), so a forged tag is rejected in the same time regardless of which
+/// of its bytes are wrong.
+#[derive(Copy, Clone, Debug)]
+pub struct Tag([u8; IV_SIZE]);
+
+impl Tag {
+    /// Create a tag from its serialized bytes
+    pub fn new(bytes: [u8; IV_SIZE]) -> Self {
+        Tag(bytes)
+    }
+
+    /// Serialize this tag as bytes
+    pub fn to_bytes(self) -> [u8; IV_SIZE] {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for Tag {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; IV_SIZE]> for Tag {
+    fn from(bytes: [u8; IV_SIZE]) -> Self {
+        Tag(bytes)
+    }
+}
+
+impl From<Tag> for [u8; IV_SIZE] {
+    fn from(tag: Tag) -> [u8; IV_SIZE] {
+        tag.0
+    }
+}
+
+/// Tags must only be compared in constant time, so that the time taken to
+/// reject a forged tag doesn't reveal how much of it was correct
+impl ConstantTimeEq for Tag {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+/// Compares tags in constant time, using `ConstantTimeEq`
+impl PartialEq for Tag {
+    fn eq(&self, other: &Self) -> bool {
+        bool::from(self.ct_eq(other))
+    }
+}
+
+impl Eq for Tag {}
+
+impl TryFrom<&[u8]> for Tag {
+    type Error = Error;
+
+    /// Returns `Error::InvalidTagSize` if the slice is the wrong length
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != IV_SIZE {
+            return Err(Error::InvalidTagSize {
+                expected: IV_SIZE,
+                got: bytes.len(),
+            });
+        }
+
+        let mut tag = [0u8; IV_SIZE];
+        tag.copy_from_slice(bytes);
+        Ok(Tag(tag))
+    }
+}
+
+/// Where the SIV tag is placed relative to the ciphertext in a message.
+///
+/// Miscreant prepends the tag by default, but many other AEAD
+/// implementations append it. The position only affects the layout of the
+/// message, not the tag or ciphertext themselves.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TagPosition {
+    /// Tag precedes the ciphertext (the default layout)
+    Prefix,
+
+    /// Tag follows the ciphertext
+    Suffix,
+}
+
+impl Default for TagPosition {
+    fn default() -> Self {
+        TagPosition::Prefix
+    }
+}
 
 /// An Authenticated Encryption with Associated Data (AEAD) algorithm.
 pub trait Aead {
@@ -31,11 +188,127 @@ pub trait Aead {
     /// Size of a MAC tag
     type TagSize: ArrayLength<u8>;
 
+    /// Size of a key associated with this AEAD algorithm in bytes
+    const KEY_SIZE: usize = <Self::KeySize as Unsigned>::USIZE;
+
+    /// Size of a MAC tag in bytes
+    const TAG_SIZE: usize = <Self::TagSize as Unsigned>::USIZE;
+
+    /// Number of bytes by which a ciphertext is longer than its plaintext
+    /// (the tag, i.e. 16 bytes for SIV)
+    const TAG_OVERHEAD: usize = Self::TAG_SIZE;
+
+    /// Length of the ciphertext produced by encrypting a plaintext of the
+    /// given length
+    fn ciphertext_len(plaintext_len: usize) -> usize
+    where
+        Self: Sized,
+    {
+        plaintext_len + Self::TAG_OVERHEAD
+    }
+
     /// Create a new AEAD instance
     ///
     /// Panics if the key is the wrong length
     fn new(key: &[u8]) -> Self;
 
+    /// Implementation of AES which will be used by new instances of this
+    /// algorithm (if it is one of the type aliases defined in this crate)
+    fn backend() -> Backend
+    where
+        Self: Sized,
+    {
+        backend::detect()
+    }
+
+    /// Create a new AEAD instance, returning `Error::InvalidKeySize` if the
+    /// key is the wrong length
+    fn try_new(key: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let expected = Self::KEY_SIZE;
+
+        if key.len() != expected {
+            return Err(Error::InvalidKeySize {
+                expected,
+                got: key.len(),
+            });
+        }
+
+        Ok(Self::new(key))
+    }
+
+    /// Create a new AEAD instance from a key held in a `secrecy::Secret`
+    /// (e.g. `Secret<Vec<u8>>`), returning `Error::InvalidKeySize` if the key
+    /// is the wrong length.
+    ///
+    /// The secret is only exposed while the key schedule is expanded.
+    #[cfg(feature = "secrecy")]
+    fn try_new_secret<K>(key: &Secret<K>) -> Result<Self, Error>
+    where
+        Self: Sized,
+        K: AsRef<[u8]> + Zeroize,
+    {
+        Self::try_new(key.expose_secret().as_ref())
+    }
+
+    /// Create a new AEAD instance from a `Key`, which is always the correct
+    /// length for this algorithm
+    fn from_key(key: &Key<Self>) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(key.as_slice())
+    }
+
+    /// Create a new AEAD instance with a key derived from the given master
+    /// key and context string using HKDF-SHA256.
+    ///
+    /// Different contexts (e.g. one per file or purpose) produce independent
+    /// keys from the same master key.
+    ///
+    /// This is key *expansion*, not password hashing: the master key must
+    /// already be uniformly random (or at least high-entropy). Passphrases
+    /// and other low-entropy secrets must first be stretched with a password
+    /// hashing function such as Argon2, scrypt, or PBKDF2.
+    #[cfg(feature = "hkdf")]
+    fn from_master_key(master_key: &[u8], context: &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        let mut key = GenericArray::<u8, Self::KeySize>::default();
+
+        Hkdf::<Sha256>::new(None, master_key)
+            .expand(context, &mut key)
+            .expect("key too long for HKDF-SHA256");
+
+        let aead = Self::new(&key);
+        key.zeroize();
+        aead
+    }
+
+    /// Generate a random key for this algorithm using the given RNG
+    #[cfg(feature = "rand_core")]
+    fn generate_key(rng: &mut (impl CryptoRng + RngCore)) -> Key<Self>
+    where
+        Self: Sized,
+    {
+        let mut key = GenericArray::default();
+        rng.fill_bytes(&mut key);
+        Key::from(key)
+    }
+
+    /// Generate a random key for this algorithm using the operating system's
+    /// random number generator
+    #[cfg(feature = "getrandom")]
+    fn generate_key_with_os_rng() -> Key<Self>
+    where
+        Self: Sized,
+    {
+        Self::generate_key(&mut OsRng)
+    }
+
     /// Encrypt the given plaintext in-place, replacing it with the SIV tag and
     /// ciphertext. Requires a buffer with 16-bytes additional space.
     ///
@@ -64,6 +337,7 @@ pub trait Aead {
     /// Panics if `plaintext.len()` is less than `M::OutputSize`.
     /// Panics if `nonce.len()` is greater than `MAX_ASSOCIATED_DATA`.
     /// Panics if `associated_data.len()` is greater than `MAX_ASSOCIATED_DATA`.
+    /// Panics if the plaintext is longer than `MAX_MESSAGE_LEN`.
     fn encrypt_in_place(&mut self, nonce: &[u8], associated_data: &[u8], buffer: &mut [u8]);
 
     /// Decrypt the given ciphertext in-place, authenticating it against the
@@ -71,7 +345,8 @@ pub trait Aead {
     ///
     /// To decrypt data, it is recommended to use this API instead of the lower-level `Siv` API.
     ///
-    /// Returns a slice containing a decrypted message on success.
+    /// Returns a slice containing a decrypted message on success. On failure,
+    /// the buffer is zeroized, so unauthenticated data is never left in it.
     fn decrypt_in_place<'a>(
         &mut self,
         nonce: &[u8],
@@ -79,16 +354,65 @@ pub trait Aead {
         buffer: &'a mut [u8],
     ) -> Result<&'a [u8], Error>;
 
+    /// Decrypt the given ciphertext in-place as with `decrypt_in_place`, but
+    /// leaving the buffer containing the original ciphertext on failure
+    /// rather than zeroizing it, so decryption can be retried (e.g. with a
+    /// different nonce or key).
+    ///
+    /// The provided implementation checks the ciphertext with `verify`
+    /// before decrypting it.
+    fn decrypt_in_place_restoring<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.verify(nonce, associated_data, buffer)?;
+        self.decrypt_in_place(nonce, associated_data, buffer)
+    }
+
+    /// Check that the given ciphertext (SIV tag followed by the encrypted
+    /// message) authenticates, without returning the plaintext.
+    ///
+    /// Returns `Error::AuthenticationFailed` if it does not.
+    fn verify(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<(), Error>;
+
     /// Encrypt the given plaintext, allocating and returning a Vec<u8> for the ciphertext
+    ///
+    /// The ciphertext is allocated once, and the plaintext is copied into it
+    /// and encrypted in-place.
     #[cfg(feature = "alloc")]
     fn encrypt(&mut self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
-        let mut buffer = vec![0; IV_SIZE + plaintext.len()];
-        buffer[IV_SIZE..].copy_from_slice(plaintext);
+        let mut buffer = Vec::with_capacity(IV_SIZE + plaintext.len());
+        buffer.extend_from_slice(&[0; IV_SIZE]);
+        buffer.extend_from_slice(plaintext);
         self.encrypt_in_place(nonce, associated_data, &mut buffer);
         buffer
     }
 
+    /// Encrypt the given plaintext as with `encrypt`, returning
+    /// `Error::MessageTooLong` instead of panicking if it is longer than
+    /// `MAX_MESSAGE_LEN`
+    #[cfg(feature = "alloc")]
+    fn try_encrypt(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        check_message_len(plaintext.len())?;
+        Ok(self.encrypt(nonce, associated_data, plaintext))
+    }
+
     /// Decrypt the given ciphertext, allocating and returning a Vec<u8> for the plaintext
+    ///
+    /// Returns `Error::MessageTooLong` if the message is longer than
+    /// `MAX_MESSAGE_LEN`.
     #[cfg(feature = "alloc")]
     fn decrypt(
         &mut self,
@@ -101,17 +425,365 @@ pub trait Aead {
         buffer.drain(..IV_SIZE);
         Ok(buffer)
     }
+
+    /// Decrypt the given ciphertext as with `decrypt`, returning the
+    /// plaintext in a `Zeroizing` wrapper which wipes it when dropped
+    #[cfg(feature = "alloc")]
+    fn decrypt_zeroizing(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Error> {
+        self.decrypt(nonce, associated_data, ciphertext)
+            .map(Zeroizing::new)
+    }
+
+    /// Decrypt the given ciphertext in-place (as with `decrypt_in_place`),
+    /// returning the plaintext as a `Cow`, for generic code which wants the
+    /// plaintext bytes regardless of who owns them.
+    ///
+    /// SIV always decrypts in-place, so this returns a borrow of the
+    /// plaintext region of the buffer. Implementations which can't (or would
+    /// rather not) decrypt into the given buffer may return an owned
+    /// plaintext instead. On failure the buffer is zeroized.
+    #[cfg(feature = "alloc")]
+    fn decrypt_cow<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &'a mut [u8],
+    ) -> Result<Cow<'a, [u8]>, Error> {
+        self.decrypt_in_place(nonce, associated_data, ciphertext)
+            .map(Cow::Borrowed)
+    }
+
+    /// Encrypt the given plaintext in-place with no associated data (see
+    /// `encrypt_in_place`)
+    fn encrypt_in_place_no_ad(&mut self, nonce: &[u8], buffer: &mut [u8]) {
+        self.encrypt_in_place(nonce, &[], buffer)
+    }
+
+    /// Decrypt the given ciphertext in-place with no associated data (see
+    /// `decrypt_in_place`)
+    fn decrypt_in_place_no_ad<'a>(
+        &mut self,
+        nonce: &[u8],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.decrypt_in_place(nonce, &[], buffer)
+    }
+
+    /// Encrypt the given plaintext with no associated data, allocating and
+    /// returning a Vec<u8> for the ciphertext
+    #[cfg(feature = "alloc")]
+    fn encrypt_no_ad(&mut self, nonce: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        self.encrypt(nonce, &[], plaintext)
+    }
+
+    /// Decrypt the given ciphertext with no associated data, allocating and
+    /// returning a Vec<u8> for the plaintext
+    #[cfg(feature = "alloc")]
+    fn decrypt_no_ad(&mut self, nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        self.decrypt(nonce, &[], ciphertext)
+    }
+
+    /// Decrypt the given ciphertext into `out`, reusing its allocation
+    /// rather than allocating a new `Vec<u8>` for every message.
+    ///
+    /// Any existing contents of `out` are replaced: on success it contains
+    /// exactly the plaintext, and on failure it is left empty.
+    #[cfg(feature = "alloc")]
+    fn decrypt_into(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        out.clear();
+        out.extend_from_slice(ciphertext);
+
+        match self.decrypt_in_place(nonce, associated_data, out) {
+            Ok(_) => {
+                out.drain(..IV_SIZE);
+                Ok(())
+            }
+            Err(e) => {
+                out.clear();
+                Err(e)
+            }
+        }
+    }
+
+    /// Encrypt the plaintext in the given `Vec<u8>` in-place, growing it to
+    /// make room for the SIV tag at the beginning.
+    #[cfg(feature = "alloc")]
+    fn encrypt_in_place_vec(&mut self, nonce: &[u8], associated_data: &[u8], buffer: &mut Vec<u8>) {
+        let len = buffer.len();
+        buffer.resize(IV_SIZE + len, 0);
+        buffer.copy_within(..len, IV_SIZE);
+        self.encrypt_in_place(nonce, associated_data, buffer);
+    }
+
+    /// Decrypt the ciphertext in the given `Vec<u8>` in-place, removing the
+    /// SIV tag so it contains only the plaintext.
+    ///
+    /// On failure, the buffer is zeroized (see `decrypt_in_place`).
+    #[cfg(feature = "alloc")]
+    fn decrypt_in_place_vec(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.decrypt_in_place(nonce, associated_data, buffer)?;
+        buffer.drain(..IV_SIZE);
+        Ok(())
+    }
+
+    /// Encrypt the given plaintext, returning a `SealedMessage` which bundles
+    /// the ciphertext together with the nonce
+    #[cfg(feature = "alloc")]
+    fn seal_message(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> SealedMessage {
+        SealedMessage {
+            nonce: Vec::from(nonce),
+            ciphertext: self.encrypt(nonce, associated_data, plaintext),
+        }
+    }
+
+    /// Decrypt a `SealedMessage`, allocating and returning a Vec<u8> for the
+    /// plaintext
+    #[cfg(feature = "alloc")]
+    fn open_message(
+        &mut self,
+        associated_data: &[u8],
+        message: &SealedMessage,
+    ) -> Result<Vec<u8>, Error> {
+        self.decrypt(&message.nonce, associated_data, &message.ciphertext)
+    }
+
+    /// Encrypt the given plaintext under a random `NONCE_SIZE`-byte nonce
+    /// generated from the given RNG, returning a single message containing
+    /// the nonce followed by the SIV tag and the ciphertext:
+    ///
+    /// ```text
+    /// nonce (16 bytes) || tag (16 bytes) || ciphertext
+    /// ```
+    ///
+    /// This layout is stable, and can be decrypted with
+    /// `decrypt_with_prepended_nonce` (or by any SIV implementation, using
+    /// the first 16 bytes as the nonce).
+    #[cfg(all(feature = "alloc", feature = "rand_core"))]
+    fn encrypt_with_random_nonce(
+        &mut self,
+        rng: &mut (impl CryptoRng + RngCore),
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut message = vec![0; NONCE_SIZE + IV_SIZE + plaintext.len()];
+        let (nonce, buffer) = message.split_at_mut(NONCE_SIZE);
+        rng.fill_bytes(nonce);
+        buffer[IV_SIZE..].copy_from_slice(plaintext);
+        self.encrypt_in_place(nonce, associated_data, buffer);
+        message
+    }
+
+    /// Decrypt a message produced by `encrypt_with_random_nonce`, splitting
+    /// the nonce back out of it, and allocating and returning a Vec<u8> for
+    /// the plaintext.
+    ///
+    /// Returns `Error::AuthenticationFailed` if the message is too short to
+    /// contain a nonce and tag.
+    #[cfg(feature = "alloc")]
+    fn decrypt_with_prepended_nonce(
+        &mut self,
+        associated_data: &[u8],
+        message: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if message.len() < NONCE_SIZE + IV_SIZE {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let (nonce, ciphertext) = message.split_at(NONCE_SIZE);
+        self.decrypt(nonce, associated_data, ciphertext)
+    }
+
+    /// Encrypt the given plaintext, returning the ciphertext encoded as
+    /// URL-safe base64 (without padding)
+    #[cfg(feature = "encoding")]
+    fn encrypt_base64(&mut self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> String {
+        URL_SAFE_NO_PAD.encode(self.encrypt(nonce, associated_data, plaintext))
+    }
+
+    /// Decrypt the given URL-safe base64 (without padding) encoded
+    /// ciphertext, returning `Error::Encoding` if it is invalid base64
+    #[cfg(feature = "encoding")]
+    fn decrypt_base64(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &str,
+    ) -> Result<Vec<u8>, Error> {
+        let ciphertext = URL_SAFE_NO_PAD
+            .decode(ciphertext)
+            .map_err(|_| Error::Encoding)?;
+
+        self.decrypt(nonce, associated_data, &ciphertext)
+    }
+
+    /// Encrypt the given plaintext, returning the ciphertext encoded as
+    /// lower-case hex
+    #[cfg(feature = "encoding")]
+    fn encrypt_hex(&mut self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> String {
+        let encoded = hex::encode(self.encrypt(nonce, associated_data, plaintext));
+        String::from_utf8(encoded).expect("hex is valid UTF-8")
+    }
+
+    /// Decrypt the given hex encoded ciphertext, returning `Error::Encoding`
+    /// if it is invalid hex
+    #[cfg(feature = "encoding")]
+    fn decrypt_hex(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &str,
+    ) -> Result<Vec<u8>, Error> {
+        let ciphertext = hex::decode(ciphertext).map_err(|_| Error::Encoding)?;
+        self.decrypt(nonce, associated_data, &ciphertext)
+    }
+
+    /// Encrypt the plaintext in the given `BytesMut` in-place, growing it to
+    /// make room for the SIV tag at the beginning (within the same
+    /// allocation, if it already has capacity for the tag).
+    #[cfg(feature = "bytes")]
+    fn encrypt_bytes(&mut self, nonce: &[u8], associated_data: &[u8], buffer: &mut BytesMut) {
+        let len = buffer.len();
+        buffer.resize(IV_SIZE + len, 0);
+        buffer.copy_within(..len, IV_SIZE);
+        self.encrypt_in_place(nonce, associated_data, buffer);
+    }
+
+    /// Decrypt the ciphertext in the given `BytesMut` in-place, advancing it
+    /// past the SIV tag so it contains only the plaintext.
+    ///
+    /// On failure, the buffer is zeroized (see `decrypt_in_place`).
+    #[cfg(feature = "bytes")]
+    fn decrypt_bytes(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut BytesMut,
+    ) -> Result<(), Error> {
+        self.decrypt_in_place(nonce, associated_data, buffer)?;
+        buffer.advance(IV_SIZE);
+        Ok(())
+    }
+
+    /// Encrypt the plaintext in the given fixed-capacity buffer, growing it
+    /// to make room for the SIV tag at the beginning.
+    ///
+    /// Returns `Error::BufferTooSmall` (leaving the buffer unmodified) if
+    /// the buffer does not have the capacity for the tag.
+    #[cfg(feature = "heapless")]
+    fn encrypt_heapless<const N: usize>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), Error> {
+        let len = buffer.len();
+
+        buffer
+            .resize(IV_SIZE + len, 0)
+            .map_err(|_| Error::BufferTooSmall)?;
+
+        buffer.copy_within(..len, IV_SIZE);
+        self.encrypt_in_place(nonce, associated_data, buffer);
+        Ok(())
+    }
+
+    /// Decrypt the ciphertext in the given fixed-capacity buffer, shrinking
+    /// it to contain only the plaintext.
+    ///
+    /// On failure, the buffer is zeroized (see `decrypt_in_place`).
+    #[cfg(feature = "heapless")]
+    fn decrypt_heapless<const N: usize>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), Error> {
+        let len = self.decrypt_in_place(nonce, associated_data, buffer)?.len();
+        buffer.copy_within(IV_SIZE.., 0);
+        buffer.truncate(len);
+        Ok(())
+    }
+}
+
+/// Re-encrypt a ciphertext produced by `old` so it is instead encrypted by
+/// `new` (e.g. when rotating keys), using the same nonce and associated
+/// data, and allocating and returning a Vec<u8> for the new ciphertext.
+///
+/// The message is decrypted and re-encrypted within the returned buffer, so
+/// the plaintext is never exposed to the caller. On failure (which leaves
+/// nothing to re-encrypt), the buffer is zeroized.
+#[cfg(feature = "alloc")]
+pub fn reencrypt<A: Aead, B: Aead>(
+    old: &mut A,
+    new: &mut B,
+    nonce: &[u8],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::from(ciphertext);
+    old.decrypt_in_place(nonce, associated_data, &mut buffer)?;
+    new.encrypt_in_place(nonce, associated_data, &mut buffer);
+    Ok(buffer)
 }
 
 /// The `SivAead` type wraps the more powerful `Siv` interface in a more
 /// commonly used Authenticated Encryption with Associated Data (AEAD) API,
 /// which accepts a key, nonce, and associated data when encrypting/decrypting.
+///
+/// # Key sizes
+///
+/// `SivAead` is generic over its CTR cipher and MAC (as is the `Siv` type
+/// it wraps): the `Aes*SivAead` aliases pick the AES block cipher, which
+/// determines the key size. With the `const-generics` feature, the same
+/// types can be selected by key length instead, e.g. `AesSivAead<32>` is
+/// `Aes128SivAead`. Otherwise, to abstract over key sizes, be generic over
+/// `A: Aead` and use `A::KEY_SIZE` (or `A::KeySize`), as `stream::Encryptor`
+/// does.
+///
+/// # Zeroization
+///
+/// When a `SivAead` is dropped, its copy of the key and the SIV encryption
+/// (CTR) key are zeroized, along with the MAC's AES key schedule (the
+/// `backend` ciphers zeroize their key schedules when dropped, including
+/// the CTR ones created for each message). The CMAC/PMAC subkeys derived
+/// from the MAC key's encryption of a zero block are private to the `cmac`
+/// and `pmac` crates, and are not cleared.
+///
+/// Cloning a `SivAead` re-derives its key schedule from a copy of the key,
+/// which (like the original) is zeroized when the clone is dropped.
 pub struct SivAead<C, M>
 where
     C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
     M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
 {
     siv: Siv<C, M>,
+    key: GenericArray<u8, <C::KeySize as Add>::Output>,
 }
 
 //
@@ -124,6 +796,9 @@ pub type CmacSivAead<BlockCipher> = SivAead<Ctr128<BlockCipher>, Cmac<BlockCiphe
 /// AES-CMAC-SIV in AEAD mode with 256-bit key size (128-bit security)
 pub type Aes128SivAead = CmacSivAead<Aes128>;
 
+/// AES-CMAC-SIV in AEAD mode with 384-bit key size (192-bit security)
+pub type Aes192SivAead = CmacSivAead<Aes192>;
+
 /// AES-CMAC-SIV in AEAD mode with 512-bit key size (256-bit security)
 pub type Aes256SivAead = CmacSivAead<Aes256>;
 
@@ -139,6 +814,10 @@ pub type PmacSivAead<BlockCipher> = SivAead<Ctr128<BlockCipher>, Pmac<BlockCiphe
 #[cfg(feature = "pmac")]
 pub type Aes128PmacSivAead = PmacSivAead<Aes128>;
 
+/// AES-PMAC-SIV in AEAD mode with 384-bit key size (192-bit security)
+#[cfg(feature = "pmac")]
+pub type Aes192PmacSivAead = PmacSivAead<Aes192>;
+
 /// AES-PMAC-SIV in AEAD mode with 512-bit key size (256-bit security)
 #[cfg(feature = "pmac")]
 pub type Aes256PmacSivAead = PmacSivAead<Aes256>;
@@ -150,23 +829,20 @@ where
     C::KeySize: Add,
     <C::KeySize as Add>::Output: ArrayLength<u8>,
 {
-    type KeySize = <C as NewStreamCipher>::KeySize;
+    type KeySize = <C::KeySize as Add>::Output;
     type TagSize = U16;
 
     fn new(key: &[u8]) -> Self {
+        let key = GenericArray::clone_from_slice(key);
+
         Self {
-            siv: Siv::new(GenericArray::clone_from_slice(key)),
+            siv: Siv::new(key.clone()),
+            key,
         }
     }
 
     fn encrypt_in_place(&mut self, nonce: &[u8], associated_data: &[u8], buffer: &mut [u8]) {
-        assert!(buffer.len() >= IV_SIZE, "no space for IV in buffer");
-        let tag = self
-            .siv
-            .encrypt_in_place_detached(&[associated_data, nonce], &mut buffer[IV_SIZE..])
-            .expect("encryption failure!");
-
-        buffer[..IV_SIZE].copy_from_slice(&tag);
+        self.encrypt_headers_in_place(nonce, &[associated_data], buffer)
     }
 
     fn decrypt_in_place<'a>(
@@ -174,17 +850,1117 @@ where
         nonce: &[u8],
         associated_data: &[u8],
         buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.decrypt_headers_in_place(nonce, &[associated_data], buffer)
+    }
+
+    /// Restores the ciphertext by re-encrypting it on failure (SIV is
+    /// deterministic), rather than checking it with `verify` beforehand.
+    fn decrypt_in_place_restoring<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &'a mut [u8],
     ) -> Result<&'a [u8], Error> {
         if buffer.len() < IV_SIZE {
-            return Err(Error);
+            return Err(Error::AuthenticationFailed);
         }
 
-        let tag = Tag::clone_from_slice(&buffer[..IV_SIZE]);
+        let (tag, message) = buffer.split_at_mut(IV_SIZE);
+        check_message_len(message.len())?;
         self.siv.decrypt_in_place_detached(
-            &[associated_data, nonce],
-            &mut buffer[IV_SIZE..],
-            &tag,
+            iter::once(&associated_data).chain(iter::once(&nonce)),
+            message,
+            GenericArray::from_slice(tag),
         )?;
-        Ok(&buffer[IV_SIZE..])
+        Ok(message)
+    }
+
+    /// Copies the message (without the tag) into a single allocation of
+    /// the plaintext's size and decrypts it in-place, so the tag never has
+    /// to be shifted out of the buffer.
+    #[cfg(feature = "alloc")]
+    fn decrypt(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if ciphertext.len() < IV_SIZE {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let (tag, message) = ciphertext.split_at(IV_SIZE);
+        let mut buffer = Vec::from(message);
+        self.decrypt_in_place_detached(nonce, associated_data, &mut buffer, &Tag::try_from(tag)?)?;
+        Ok(buffer)
+    }
+
+    /// Decrypts the message a block at a time into a scratch buffer on the
+    /// stack (which is zeroized afterward), feeding each block into an
+    /// incremental S2V computation, so verifying never allocates.
+    fn verify(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<(), Error> {
+        if ciphertext.len() < IV_SIZE {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        check_message_len(ciphertext.len() - IV_SIZE)?;
+        let tag = Tag::try_from(&ciphertext[..IV_SIZE])?;
+        let mut s2v = SivEncryptor::<C, M>::new(&self.key);
+        s2v.update_ad(associated_data);
+        s2v.update_ad(nonce);
+
+        let mut keystream = s2v.keystream(tag);
+        let mut scratch = [0u8; 256];
+
+        for chunk in ciphertext[IV_SIZE..].chunks(scratch.len()) {
+            let block = &mut scratch[..chunk.len()];
+            block.copy_from_slice(chunk);
+            keystream.apply_keystream(block);
+            s2v.update_plaintext(block);
+        }
+
+        scratch.zeroize();
+        s2v.verify(&tag)
+    }
+}
+
+impl<C, M> SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    /// Borrow the two SIV subkeys derived from this instance's key: the MAC
+    /// (S2V) key, followed by the CTR encryption key. As specified by
+    /// RFC 5297, these are the first and second halves of the key.
+    ///
+    /// # Security Warning
+    ///
+    /// This exposes secret key material, and is only intended for debugging
+    /// interoperability problems and building compatible tooling. Using
+    /// either subkey for anything other than SIV (or leaking it) undermines
+    /// the security of every message encrypted with the key. Requires the
+    /// `unstable` feature.
+    #[cfg(feature = "unstable")]
+    pub fn subkeys(&self) -> (&[u8], &[u8]) {
+        self.key.split_at(self.key.len() / 2)
+    }
+
+    /// Create a new AEAD instance from separately stored SIV subkeys: the
+    /// MAC (S2V) key and the CTR encryption key, each half the size of the
+    /// combined key (e.g. 16 bytes for AES-128-SIV).
+    ///
+    /// This is equivalent to `try_new` with the concatenation of the two
+    /// subkeys. Returns `Error::InvalidKeySize` if either is the wrong size.
+    pub fn new_from_subkeys(mac_key: &[u8], ctr_key: &[u8]) -> Result<Self, Error> {
+        let expected = Self::KEY_SIZE / 2;
+
+        for subkey in &[mac_key, ctr_key] {
+            if subkey.len() != expected {
+                return Err(Error::InvalidKeySize {
+                    expected,
+                    got: subkey.len(),
+                });
+            }
+        }
+
+        let mut key = GenericArray::default();
+        key[..expected].copy_from_slice(mac_key);
+        key[expected..].copy_from_slice(ctr_key);
+
+        Ok(Self {
+            siv: Siv::new(key.clone()),
+            key,
+        })
+    }
+
+    /// Fill `out` with the raw CTR keystream which SIV uses to encrypt a
+    /// message with the given 16-byte synthetic IV (after clearing its 31st
+    /// and 63rd bits, as when encrypting), overwriting its contents.
+    ///
+    /// # Security Warning
+    ///
+    /// This is a low-level escape hatch which bypasses SIV entirely: the
+    /// keystream is plain AES-CTR, with no authentication, and reusing it
+    /// (or an IV which SIV has produced for another message) to encrypt
+    /// anything else reveals the XOR of the two plaintexts. Only use it if
+    /// you know exactly why you need it. Requires the `unstable` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the IV is not 16 bytes.
+    #[cfg(feature = "unstable")]
+    pub fn keystream(&self, iv: &[u8], out: &mut [u8]) {
+        let tag = Tag::try_from(iv).expect("IV must be 16 bytes");
+        out.zeroize();
+        self.ctr_keystream(tag).apply_keystream(out);
+    }
+
+    /// Encrypt the given plaintext in-place, authenticating each of the given
+    /// associated data headers as a separate input to S2V (followed by the
+    /// nonce), as with `seal` in the other Miscreant implementations.
+    ///
+    /// The buffer layout is the same as `encrypt_in_place`.
+    ///
+    /// Headers are never concatenated: S2V MACs each one separately and
+    /// folds the results together with doubling (RFC 5297 Section 2.4), so
+    /// the split between headers is authenticated along with their contents.
+    /// `["a", "bc"]`, `["ab", "c"]` and `["abc"]` produce unrelated tags,
+    /// as do headers which differ only by a trailing empty header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is smaller than the 16-byte tag.
+    /// Panics if more than `siv::MAX_HEADERS - 1` headers are given.
+    pub fn encrypt_headers_in_place(&mut self, nonce: &[u8], headers: &[&[u8]], buffer: &mut [u8]) {
+        assert!(buffer.len() >= IV_SIZE, "no space for IV in buffer");
+        let tag = self.encrypt_headers_in_place_detached(nonce, headers, &mut buffer[IV_SIZE..]);
+        buffer[..IV_SIZE].copy_from_slice(tag.as_ref());
+    }
+
+    /// Decrypt the given ciphertext in-place, authenticating it against each
+    /// of the given associated data headers (see `encrypt_headers_in_place`).
+    ///
+    /// Returns a slice containing a decrypted message on success. On failure,
+    /// the buffer (including the tag) is zeroized.
+    pub fn decrypt_headers_in_place<'a>(
+        &mut self,
+        nonce: &[u8],
+        headers: &[&[u8]],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        if buffer.len() < IV_SIZE {
+            buffer.zeroize();
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let mut tag = [0u8; IV_SIZE];
+        tag.copy_from_slice(&buffer[..IV_SIZE]);
+        self.decrypt_headers_in_place_detached(nonce, headers, &mut buffer[IV_SIZE..], &Tag(tag))
+            .map_err(|e| {
+                buffer.zeroize();
+                e
+            })?;
+        Ok(&buffer[IV_SIZE..])
+    }
+
+    /// Decrypt the given ciphertext in-place with multiple associated data
+    /// headers as with `decrypt_headers_in_place`, but leaving the original
+    /// ciphertext in the buffer on failure rather than zeroizing it (see
+    /// `Aead::decrypt_in_place_restoring`).
+    ///
+    /// More than `siv::MAX_HEADERS - 1` headers (which no ciphertext can
+    /// have been encrypted with) fails with `Error::AuthenticationFailed`
+    /// before the buffer is touched, as S2V rejects them only after the
+    /// keystream has already been applied.
+    pub fn decrypt_headers_in_place_restoring<'a>(
+        &mut self,
+        nonce: &[u8],
+        headers: &[&[u8]],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        if buffer.len() < IV_SIZE {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        check_headers_len(headers.len())?;
+
+        let (tag, message) = buffer.split_at_mut(IV_SIZE);
+        check_message_len(message.len())?;
+        self.siv.decrypt_in_place_detached(
+            headers.iter().chain(iter::once(&nonce)),
+            message,
+            GenericArray::from_slice(tag),
+        )?;
+        Ok(message)
+    }
+
+    /// Encrypt the given plaintext into the given output buffer, writing the
+    /// SIV tag followed by the ciphertext, and returning the number of bytes
+    /// written (`plaintext.len() + 16`).
+    ///
+    /// Returns `Error::BufferTooSmall` if the output buffer is too small.
+    pub fn encrypt_to_slice(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        let len = IV_SIZE + plaintext.len();
+
+        if out.len() < len {
+            return Err(Error::BufferTooSmall);
+        }
+
+        out[IV_SIZE..len].copy_from_slice(plaintext);
+        let tag = self.encrypt_in_place_detached(nonce, associated_data, &mut out[IV_SIZE..len]);
+        out[..IV_SIZE].copy_from_slice(tag.as_ref());
+        Ok(len)
+    }
+
+    /// Decrypt the given ciphertext (SIV tag followed by the ciphertext)
+    /// into the given output buffer, returning the length of the plaintext
+    /// (`ciphertext.len() - 16`).
+    ///
+    /// Returns `Error::BufferTooSmall` if the output buffer is too small.
+    /// If authentication fails, the whole output buffer is zeroized.
+    pub fn decrypt_to_slice(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        if ciphertext.len() < IV_SIZE {
+            out.zeroize();
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let len = ciphertext.len() - IV_SIZE;
+
+        if out.len() < len {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let mut tag = [0u8; IV_SIZE];
+        tag.copy_from_slice(&ciphertext[..IV_SIZE]);
+        out[..len].copy_from_slice(&ciphertext[IV_SIZE..]);
+
+        self.decrypt_in_place_detached(nonce, associated_data, &mut out[..len], &Tag(tag))
+            .map_err(|e| {
+                out.zeroize();
+                e
+            })?;
+
+        Ok(len)
+    }
+
+    /// Encrypt the concatenation of the given buffers as the plaintext,
+    /// without first copying them into a single buffer, allocating and
+    /// returning a Vec<u8> for the ciphertext.
+    ///
+    /// The result is identical to encrypting the concatenated plaintext.
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_vectored(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        bufs: &[&[u8]],
+    ) -> Vec<u8> {
+        assert!(
+            check_message_len(bufs.iter().map(|buf| buf.len()).sum()).is_ok(),
+            "message longer than MAX_MESSAGE_LEN"
+        );
+
+        let mut s2v = SivEncryptor::<C, M>::new(&self.key);
+        s2v.update_ad(associated_data);
+        s2v.update_ad(nonce);
+
+        for buf in bufs {
+            s2v.update_plaintext(buf);
+        }
+
+        let mut ciphertext = Vec::with_capacity(IV_SIZE + s2v.plaintext_len() as usize);
+        let mut keystream = s2v.finalize();
+        ciphertext.extend_from_slice(keystream.tag().as_ref());
+
+        for buf in bufs {
+            let offset = ciphertext.len();
+            ciphertext.extend_from_slice(buf);
+            keystream.apply_keystream(&mut ciphertext[offset..]);
+        }
+
+        ciphertext
+    }
+
+    /// Decrypt the given ciphertext (SIV tag followed by the ciphertext)
+    /// into the given buffers, filling each of them in turn, and returning
+    /// the length of the plaintext (`ciphertext.len() - 16`).
+    ///
+    /// Returns `Error::BufferTooSmall` if the buffers are too small in
+    /// total. If authentication fails, the buffers are zeroized.
+    pub fn decrypt_vectored(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+        bufs: &mut [&mut [u8]],
+    ) -> Result<usize, Error> {
+        if ciphertext.len() < IV_SIZE {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let len = ciphertext.len() - IV_SIZE;
+        check_message_len(len)?;
+
+        if bufs.iter().map(|buf| buf.len()).sum::<usize>() < len {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let tag = Tag::try_from(&ciphertext[..IV_SIZE])?;
+        let mut s2v = SivEncryptor::<C, M>::new(&self.key);
+        s2v.update_ad(associated_data);
+        s2v.update_ad(nonce);
+
+        let mut keystream = s2v.keystream(tag);
+        let mut remaining = &ciphertext[IV_SIZE..];
+
+        for buf in bufs.iter_mut() {
+            let n = buf.len().min(remaining.len());
+            let (part, rest) = remaining.split_at(n);
+            buf[..n].copy_from_slice(part);
+            keystream.apply_keystream(&mut buf[..n]);
+            s2v.update_plaintext(&buf[..n]);
+            remaining = rest;
+        }
+
+        if let Err(e) = s2v.verify(&tag) {
+            for buf in bufs.iter_mut() {
+                buf.zeroize();
+            }
+
+            return Err(e);
+        }
+
+        Ok(len)
+    }
+
+    /// Encrypt the given plaintext in-place (the entire buffer), returning
+    /// the SIV tag separately rather than prepending it to the ciphertext.
+    pub fn encrypt_in_place_detached(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Tag {
+        self.encrypt_headers_in_place_detached(nonce, &[associated_data], buffer)
+    }
+
+    /// Decrypt the given ciphertext in-place (the entire buffer),
+    /// authenticating it against the given detached SIV tag.
+    ///
+    /// The tag is compared against the recomputed SIV in constant time.
+    ///
+    /// # Failure
+    ///
+    /// If authentication fails, the buffer is zeroized, so unauthenticated
+    /// plaintext is never exposed to the caller (and a careless caller can't
+    /// mistake the ciphertext for a decrypted message). Failures are
+    /// indistinguishable regardless of which part of the message was
+    /// modified. Use `Aead::decrypt_in_place_restoring` to keep the
+    /// ciphertext instead.
+    pub fn decrypt_in_place_detached(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        self.decrypt_headers_in_place_detached(nonce, &[associated_data], buffer, tag)
+    }
+
+    /// Encrypt the given plaintext in-place (the entire buffer), returning
+    /// a synthetic IV truncated to `tag_len` bytes (followed by zeros in the
+    /// returned `Tag`, so only its first `tag_len` bytes need to be sent).
+    ///
+    /// Returns `Error::InvalidTagSize` if `tag_len` is less than
+    /// `MIN_TRUNCATED_TAG_SIZE` (8) or more than 16 bytes.
+    ///
+    /// # Security Warning
+    ///
+    /// Truncating the tag reduces security: a forgery succeeds with
+    /// probability 2^-(8 * `tag_len`) per attempt (2^-64 for an 8-byte tag,
+    /// which an attacker who can make many decryption attempts may be able
+    /// to exploit), and messages whose truncated tags collide become
+    /// distinguishable after around 2^(4 * `tag_len`) messages. Only use it
+    /// where bandwidth is so constrained that this trade-off is acceptable.
+    ///
+    /// The CTR pass is keyed by the truncated (zero-padded) tag, so the
+    /// decryptor needs nothing more, and the ciphertext differs from the
+    /// one produced by `encrypt_in_place_detached` unless `tag_len` is 16.
+    /// It can only be decrypted with `decrypt_in_place_detached_truncated`.
+    pub fn encrypt_in_place_detached_truncated(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag_len: usize,
+    ) -> Result<Tag, Error> {
+        check_truncated_tag_len(tag_len)?;
+        check_message_len(buffer.len())?;
+
+        let mut s2v = SivEncryptor::<C, M>::new(&self.key);
+        s2v.update_ad(associated_data);
+        s2v.update_ad(nonce);
+        s2v.update_plaintext(buffer);
+
+        let mut tag = s2v.finalize().tag().to_bytes();
+        tag[tag_len..].zeroize();
+
+        let tag = Tag::new(tag);
+        self.ctr_keystream(tag).apply_keystream(buffer);
+        Ok(tag)
+    }
+
+    /// Decrypt the given ciphertext in-place (the entire buffer),
+    /// authenticating it against the given truncated tag produced by
+    /// `encrypt_in_place_detached_truncated` (see its security warning).
+    ///
+    /// Only the `tag.len()` bytes given are compared (in constant time), so
+    /// the tag length must be transmitted (or fixed) out-of-band. Returns
+    /// `Error::InvalidTagSize` if it's less than `MIN_TRUNCATED_TAG_SIZE`
+    /// (8) or more than 16 bytes. If authentication fails, the buffer is
+    /// zeroized.
+    pub fn decrypt_in_place_detached_truncated(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), Error> {
+        check_truncated_tag_len(tag.len())?;
+        check_message_len(buffer.len())?;
+
+        let mut padded = [0u8; IV_SIZE];
+        padded[..tag.len()].copy_from_slice(tag);
+        self.ctr_keystream(Tag::new(padded)).apply_keystream(buffer);
+
+        let mut s2v = SivEncryptor::<C, M>::new(&self.key);
+        s2v.update_ad(associated_data);
+        s2v.update_ad(nonce);
+        s2v.update_plaintext(buffer);
+
+        let expected = s2v.finalize().tag();
+
+        if bool::from(expected.as_ref()[..tag.len()].ct_eq(tag)) {
+            Ok(())
+        } else {
+            buffer.zeroize();
+            Err(Error::AuthenticationFailed)
+        }
+    }
+
+    /// Create the CTR pass keyed by the given tag, using the second half of
+    /// the key
+    fn ctr_keystream(&self, tag: Tag) -> Keystream<C> {
+        let ctr_key = &self.key[self.key.len() - C::KeySize::USIZE..];
+        Keystream::from_key(GenericArray::from_slice(ctr_key), tag)
+    }
+
+    /// Encrypt the given plaintext in-place with multiple associated data
+    /// headers, returning the SIV tag separately.
+    ///
+    /// Panics if more than `siv::MAX_HEADERS - 1` headers are given.
+    pub fn encrypt_headers_in_place_detached(
+        &mut self,
+        nonce: &[u8],
+        headers: &[&[u8]],
+        buffer: &mut [u8],
+    ) -> Tag {
+        assert!(
+            check_message_len(buffer.len()).is_ok(),
+            "message longer than MAX_MESSAGE_LEN"
+        );
+
+        let tag = self
+            .siv
+            .encrypt_in_place_detached(headers.iter().chain(iter::once(&nonce)), buffer)
+            .expect("encryption failure!");
+
+        let mut bytes = [0u8; IV_SIZE];
+        bytes.copy_from_slice(&tag);
+        Tag(bytes)
+    }
+
+    /// Decrypt the given ciphertext in-place with multiple associated data
+    /// headers, authenticating it against the given detached SIV tag.
+    ///
+    /// See `decrypt_in_place_detached` for behavior on failure.
+    pub fn decrypt_headers_in_place_detached(
+        &mut self,
+        nonce: &[u8],
+        headers: &[&[u8]],
+        buffer: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        let result = check_message_len(buffer.len()).and_then(|()| {
+            self.siv
+                .decrypt_in_place_detached(
+                    headers.iter().chain(iter::once(&nonce)),
+                    buffer,
+                    GenericArray::from_slice(tag.as_ref()),
+                )
+                .map_err(Error::from)
+        });
+
+        if result.is_err() {
+            buffer.zeroize();
+        }
+
+        result
+    }
+
+    /// Encrypt the given plaintext with multiple associated data headers,
+    /// allocating and returning a Vec<u8> for the ciphertext
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_headers(
+        &mut self,
+        nonce: &[u8],
+        headers: &[&[u8]],
+        plaintext: &[u8],
+    ) -> Vec<u8> {
+        let mut buffer = vec![0; IV_SIZE + plaintext.len()];
+        buffer[IV_SIZE..].copy_from_slice(plaintext);
+        self.encrypt_headers_in_place(nonce, headers, &mut buffer);
+        buffer
+    }
+
+    /// Decrypt the given ciphertext with multiple associated data headers,
+    /// allocating and returning a Vec<u8> for the plaintext
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_headers(
+        &mut self,
+        nonce: &[u8],
+        headers: &[&[u8]],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::from(ciphertext);
+        self.decrypt_headers_in_place(nonce, headers, &mut buffer)?;
+        buffer.drain(..IV_SIZE);
+        Ok(buffer)
+    }
+
+    /// Encrypt the given plaintext in-place, placing the SIV tag at the
+    /// given position in the buffer: the plaintext occupies the rest of the
+    /// buffer, which must include 16 bytes of additional space for the tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is smaller than the 16-byte tag.
+    pub fn encrypt_in_place_with_tag_position(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        position: TagPosition,
+    ) {
+        assert!(buffer.len() >= IV_SIZE, "no space for IV in buffer");
+
+        match position {
+            TagPosition::Prefix => self.encrypt_in_place(nonce, associated_data, buffer),
+            TagPosition::Suffix => {
+                let (message, tag) = buffer.split_at_mut(buffer.len() - IV_SIZE);
+                let siv = self.encrypt_in_place_detached(nonce, associated_data, message);
+                tag.copy_from_slice(siv.as_ref());
+            }
+        }
+    }
+
+    /// Decrypt the given ciphertext in-place, reading the SIV tag from the
+    /// given position in the buffer.
+    ///
+    /// Returns a slice containing a decrypted message on success. On failure,
+    /// the buffer (including the tag) is zeroized.
+    pub fn decrypt_in_place_with_tag_position<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &'a mut [u8],
+        position: TagPosition,
+    ) -> Result<&'a [u8], Error> {
+        match position {
+            TagPosition::Prefix => self.decrypt_in_place(nonce, associated_data, buffer),
+            TagPosition::Suffix => {
+                if buffer.len() < IV_SIZE {
+                    buffer.zeroize();
+                    return Err(Error::AuthenticationFailed);
+                }
+
+                let (message, tag_bytes) = buffer.split_at_mut(buffer.len() - IV_SIZE);
+                let tag = Tag::try_from(&*tag_bytes)?;
+                self.decrypt_in_place_detached(nonce, associated_data, message, &tag)
+                    .map_err(|e| {
+                        tag_bytes.zeroize();
+                        e
+                    })?;
+                Ok(message)
+            }
+        }
+    }
+
+    /// Encrypt the given plaintext with the SIV tag at the given position,
+    /// allocating and returning a Vec<u8> for the ciphertext
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_with_tag_position(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+        position: TagPosition,
+    ) -> Vec<u8> {
+        let mut buffer = vec![0; IV_SIZE + plaintext.len()];
+
+        match position {
+            TagPosition::Prefix => buffer[IV_SIZE..].copy_from_slice(plaintext),
+            TagPosition::Suffix => buffer[..plaintext.len()].copy_from_slice(plaintext),
+        }
+
+        self.encrypt_in_place_with_tag_position(nonce, associated_data, &mut buffer, position);
+        buffer
+    }
+
+    /// Decrypt the given ciphertext with the SIV tag at the given position,
+    /// allocating and returning a Vec<u8> for the plaintext
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_with_tag_position(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+        position: TagPosition,
+    ) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::from(ciphertext);
+        self.decrypt_in_place_with_tag_position(nonce, associated_data, &mut buffer, position)?;
+
+        match position {
+            TagPosition::Prefix => {
+                buffer.drain(..IV_SIZE);
+            }
+            TagPosition::Suffix => buffer.truncate(buffer.len() - IV_SIZE),
+        }
+
+        Ok(buffer)
+    }
+
+    /// Encrypt the given plaintext in-place, authenticating each of the fields
+    /// of the given `AssociatedData` as a separate header.
+    ///
+    /// The buffer layout is the same as `encrypt_in_place`.
+    pub fn encrypt_in_place_with_ad(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &AssociatedData<'_>,
+        buffer: &mut [u8],
+    ) {
+        self.encrypt_headers_in_place(nonce, associated_data.as_slice(), buffer)
+    }
+
+    /// Decrypt the given ciphertext in-place, authenticating it against each
+    /// of the fields of the given `AssociatedData`.
+    ///
+    /// Returns a slice containing a decrypted message on success.
+    pub fn decrypt_in_place_with_ad<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &AssociatedData<'_>,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.decrypt_headers_in_place(nonce, associated_data.as_slice(), buffer)
+    }
+
+    /// Encrypt the given plaintext, authenticating each of the fields of the
+    /// given `AssociatedData` as a separate header, and allocating and
+    /// returning a Vec<u8> for the ciphertext
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_with_ad(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &AssociatedData<'_>,
+        plaintext: &[u8],
+    ) -> Vec<u8> {
+        self.encrypt_headers(nonce, associated_data.as_slice(), plaintext)
+    }
+
+    /// Decrypt the given ciphertext, authenticating it against each of the
+    /// fields of the given `AssociatedData`, and allocating and returning a
+    /// Vec<u8> for the plaintext
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_with_ad(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &AssociatedData<'_>,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.decrypt_headers(nonce, associated_data.as_slice(), ciphertext)
+    }
+
+    /// Encrypt the given plaintext in-place without a nonce, using the
+    /// deterministic authenticated encryption (DAE) mode of RFC 5297: the
+    /// associated data is the only header authenticated by S2V.
+    ///
+    /// The buffer layout is the same as `encrypt_in_place`.
+    ///
+    /// # Privacy
+    ///
+    /// Encrypting the same plaintext with the same associated data always
+    /// produces the same ciphertext, so anyone who can see the ciphertexts
+    /// learns which messages are equal (though nothing else about them
+    /// besides their length). This is what makes it possible to look up
+    /// encrypted database fields, but if messages must be unlinkable, use a
+    /// unique nonce (or unique associated data) for each one instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is smaller than the 16-byte tag.
+    pub fn seal_deterministic_in_place(&mut self, associated_data: &[u8], buffer: &mut [u8]) {
+        assert!(buffer.len() >= IV_SIZE, "no space for IV in buffer");
+        let (tag, message) = buffer.split_at_mut(IV_SIZE);
+        assert!(
+            check_message_len(message.len()).is_ok(),
+            "message longer than MAX_MESSAGE_LEN"
+        );
+
+        let siv = self
+            .siv
+            .encrypt_in_place_detached(iter::once(&associated_data), message)
+            .expect("encryption failure!");
+
+        tag.copy_from_slice(&siv);
+    }
+
+    /// Decrypt the given ciphertext in-place, authenticating it against the
+    /// associated data without a nonce (see `seal_deterministic_in_place`).
+    ///
+    /// Returns a slice containing a decrypted message on success. On failure,
+    /// the buffer (including the tag) is zeroized.
+    pub fn open_deterministic_in_place<'a>(
+        &mut self,
+        associated_data: &[u8],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        if buffer.len() < IV_SIZE {
+            buffer.zeroize();
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let (tag, message) = buffer.split_at_mut(IV_SIZE);
+
+        if let Err(e) = check_message_len(message.len()).and_then(|()| {
+            self.siv
+                .decrypt_in_place_detached(
+                    iter::once(&associated_data),
+                    message,
+                    GenericArray::from_slice(tag),
+                )
+                .map_err(Error::from)
+        }) {
+            tag.zeroize();
+            message.zeroize();
+            return Err(e);
+        }
+
+        Ok(message)
+    }
+
+    /// Encrypt the given plaintext deterministically, without a nonce (see
+    /// `seal_deterministic_in_place`), allocating and returning a Vec<u8>
+    /// for the ciphertext
+    #[cfg(feature = "alloc")]
+    pub fn seal_deterministic(&mut self, associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let mut buffer = vec![0; IV_SIZE + plaintext.len()];
+        buffer[IV_SIZE..].copy_from_slice(plaintext);
+        self.seal_deterministic_in_place(associated_data, &mut buffer);
+        buffer
+    }
+
+    /// Decrypt a ciphertext produced by `seal_deterministic`, allocating and
+    /// returning a Vec<u8> for the plaintext
+    #[cfg(feature = "alloc")]
+    pub fn open_deterministic(
+        &mut self,
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::from(ciphertext);
+        self.open_deterministic_in_place(associated_data, &mut buffer)?;
+        buffer.drain(..IV_SIZE);
+        Ok(buffer)
+    }
+
+    /// Wrap (encrypt and authenticate) the given key material with
+    /// deterministic SIV and no associated data: the key wrapping scheme
+    /// RFC 5297 was designed for.
+    ///
+    /// Since key material is random (and usually unique), determinism
+    /// reveals nothing here, so no nonce is needed.
+    #[cfg(feature = "alloc")]
+    pub fn wrap_key(&mut self, key_to_wrap: &[u8]) -> Vec<u8> {
+        self.seal_deterministic(&[], key_to_wrap)
+    }
+
+    /// Unwrap key material wrapped with `wrap_key`, returning
+    /// `Error::AuthenticationFailed` if it isn't authentic.
+    ///
+    /// The caller is responsible for zeroizing the returned key material.
+    #[cfg(feature = "alloc")]
+    pub fn unwrap_key(&mut self, wrapped_key: &[u8]) -> Result<Vec<u8>, Error> {
+        self.open_deterministic(&[], wrapped_key)
+    }
+
+    /// Encrypt the given plaintext as with `encrypt`, prepending a
+    /// commitment to the key (and nonce), so the message can only be
+    /// decrypted with `decrypt_committing` under the key which encrypted it.
+    ///
+    /// # Key commitment
+    ///
+    /// SIV is not key-committing: it is possible to construct a single
+    /// ciphertext which decrypts successfully under two (or more) different
+    /// keys. Where an attacker can choose the keys which are tried (e.g.
+    /// keys derived from guessed passwords, or a multi-recipient setting),
+    /// this enables "partitioning oracle" attacks which test many keys with
+    /// each decryption attempt.
+    ///
+    /// The commitment is a SHA-256 hash of the key and nonce, so finding a
+    /// message which commits to two keys requires a SHA-256 collision. Its
+    /// overhead is `KEY_COMMITMENT_SIZE` (32) bytes per message, in
+    /// addition to the 16-byte tag, and one SHA-256 computation over the key
+    /// and nonce per message. Requires the `committing` feature.
+    ///
+    /// The message layout is:
+    ///
+    /// ```text
+    /// commitment (32 bytes) || tag (16 bytes) || ciphertext
+    /// ```
+    #[cfg(feature = "committing")]
+    pub fn encrypt_committing(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Vec<u8> {
+        let mut message = vec![0; KEY_COMMITMENT_SIZE + IV_SIZE + plaintext.len()];
+        let (commitment, buffer) = message.split_at_mut(KEY_COMMITMENT_SIZE);
+        commitment.copy_from_slice(&self.key_commitment(nonce));
+        buffer[IV_SIZE..].copy_from_slice(plaintext);
+        self.encrypt_in_place(nonce, associated_data, buffer);
+        message
+    }
+
+    /// Decrypt a message produced by `encrypt_committing`, allocating and
+    /// returning a Vec<u8> for the plaintext.
+    ///
+    /// The key commitment is checked (in constant time) before the message
+    /// is decrypted, so decryption under any other key fails with
+    /// `Error::AuthenticationFailed`, as does a message too short to contain
+    /// a commitment and tag.
+    #[cfg(feature = "committing")]
+    pub fn decrypt_committing(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        message: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if message.len() < KEY_COMMITMENT_SIZE + IV_SIZE {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let (commitment, ciphertext) = message.split_at(KEY_COMMITMENT_SIZE);
+
+        if !bool::from(self.key_commitment(nonce)[..].ct_eq(commitment)) {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        self.decrypt(nonce, associated_data, ciphertext)
+    }
+
+    /// Compute the commitment to this instance's key and the given nonce,
+    /// each prefixed by its length
+    #[cfg(feature = "committing")]
+    fn key_commitment(&self, nonce: &[u8]) -> [u8; KEY_COMMITMENT_SIZE] {
+        let mut hash = CommitmentHash::new();
+        hash.update(KEY_COMMITMENT_LABEL);
+        hash.update((self.key.len() as u64).to_be_bytes());
+        hash.update(&self.key);
+        hash.update((nonce.len() as u64).to_be_bytes());
+        hash.update(nonce);
+
+        let mut commitment = [0u8; KEY_COMMITMENT_SIZE];
+        commitment.copy_from_slice(&hash.finalize());
+        commitment
+    }
+}
+
+#[cfg(all(feature = "pmac", feature = "rayon"))]
+impl<B> PmacSivAead<B>
+where
+    B: BlockCipher<BlockSize = U16> + Clone + Sync,
+    B::ParBlocks: Clone,
+    Ctr128<B>: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    Pmac<B>: Mac<OutputSize = U16>,
+    <Ctr128<B> as NewStreamCipher>::KeySize: Add,
+    <<Ctr128<B> as NewStreamCipher>::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    /// Encrypt the given plaintext in-place as with `encrypt_in_place`, but
+    /// computing PMAC over large plaintexts and associated data on multiple
+    /// threads using rayon.
+    ///
+    /// Inputs smaller than 64 KiB are processed on the calling thread. The
+    /// ciphertext is identical to the one produced by `encrypt_in_place`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is smaller than the 16-byte tag.
+    pub fn encrypt_in_place_parallel(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) {
+        assert!(buffer.len() >= IV_SIZE, "no space for IV in buffer");
+        let (tag_bytes, plaintext) = buffer.split_at_mut(IV_SIZE);
+        assert!(
+            check_message_len(plaintext.len()).is_ok(),
+            "message longer than MAX_MESSAGE_LEN"
+        );
+
+        let tag = self.parallel_s2v(nonce, associated_data, plaintext);
+        self.ctr_keystream(tag).apply_keystream(plaintext);
+        tag_bytes.copy_from_slice(tag.as_ref());
+    }
+
+    /// Decrypt the given ciphertext in-place as with `decrypt_in_place`, but
+    /// computing PMAC on multiple threads (see `encrypt_in_place_parallel`).
+    ///
+    /// Returns a slice containing a decrypted message on success. On failure,
+    /// the buffer (including the tag) is zeroized.
+    pub fn decrypt_in_place_parallel<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        if buffer.len() < IV_SIZE {
+            buffer.zeroize();
+            return Err(Error::AuthenticationFailed);
+        }
+
+        if let Err(e) = check_message_len(buffer.len() - IV_SIZE) {
+            buffer.zeroize();
+            return Err(e);
+        }
+
+        let (tag_bytes, ciphertext) = buffer.split_at_mut(IV_SIZE);
+        let tag = Tag::try_from(&*tag_bytes)?;
+        self.ctr_keystream(tag).apply_keystream(ciphertext);
+
+        let expected = self.parallel_s2v(nonce, associated_data, ciphertext);
+
+        if bool::from(expected.ct_eq(&tag)) {
+            Ok(ciphertext)
+        } else {
+            // Never leave the unauthenticated plaintext in the buffer
+            tag_bytes.zeroize();
+            ciphertext.zeroize();
+            Err(Error::AuthenticationFailed)
+        }
+    }
+
+    /// Compute the S2V tag with `parallel::s2v`
+    fn parallel_s2v(&self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Tag {
+        let mac_key = &self.key[..self.key.len() / 2];
+        parallel::s2v::<B>(mac_key, &[associated_data, nonce], plaintext)
+    }
+}
+
+impl<C, M> Clone for SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    /// `Siv` isn't `Clone`, so the key schedule is re-derived from the key.
+    /// Copying the MAC state instead would leave copies of its subkeys which
+    /// are never zeroized.
+    fn clone(&self) -> Self {
+        Self {
+            siv: Siv::new(self.key.clone()),
+            key: self.key.clone(),
+        }
+    }
+}
+
+impl<C, M> TryFrom<&[u8]> for SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    type Error = Error;
+
+    /// Returns `Error::InvalidKeySize` if the key is the wrong length
+    fn try_from(key: &[u8]) -> Result<Self, Error> {
+        Self::try_new(key)
+    }
+}
+
+impl<C, M> fmt::Debug for SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    /// Print the type name, but never the key
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SivAead { .. }")
+    }
+}
+
+impl<C, M> Drop for SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    fn drop(&mut self) {
+        self.key.zeroize()
+    }
+}
+
+/// Check a truncated tag length is between `MIN_TRUNCATED_TAG_SIZE` and the
+/// full tag size
+fn check_truncated_tag_len(tag_len: usize) -> Result<(), Error> {
+    let expected = if tag_len < MIN_TRUNCATED_TAG_SIZE {
+        MIN_TRUNCATED_TAG_SIZE
+    } else if tag_len > IV_SIZE {
+        IV_SIZE
+    } else {
+        return Ok(());
+    };
+
+    Err(Error::InvalidTagSize {
+        expected,
+        got: tag_len,
+    })
+}
+
+/// Check the given number of headers leaves room for the nonce within
+/// `siv::MAX_HEADERS`, returning `Error::AuthenticationFailed` if not
+pub(crate) fn check_headers_len(len: usize) -> Result<(), Error> {
+    if len >= MAX_HEADERS {
+        Err(Error::AuthenticationFailed)
+    } else {
+        Ok(())
+    }
+}
+
+/// Check a message is no longer than `MAX_MESSAGE_LEN`, returning
+/// `Error::MessageTooLong` if it is
+fn check_message_len(len: usize) -> Result<(), Error> {
+    if len as u64 > MAX_MESSAGE_LEN {
+        Err(Error::MessageTooLong)
+    } else {
+        Ok(())
     }
 }
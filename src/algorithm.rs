@@ -0,0 +1,206 @@
+//! `algorithm.rs`: Selecting an AEAD algorithm at runtime
+//!
+//! `Algorithm` names each of the `*SivAead` types, and can be parsed from
+//! (and displayed as) a name such as `"AES-256-PMAC-SIV"`, e.g. when the
+//! algorithm is chosen by configuration. `new_dyn` creates an instance of
+//! the algorithm behind a `DynAead` trait object.
+//!
+//! The `Aead` trait itself can't be used as a trait object: its key and tag
+//! sizes are associated types (which differ between algorithms) and
+//! constants, and it has constructors returning `Self`. `DynAead` is an
+//! object-safe subset of it, implemented for every `Aead`. If both traits
+//! are in scope, calls on a concrete type are ambiguous, so disambiguate
+//! with `Aead::encrypt(&mut aead, ...)`.
+
+use crate::{Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead, Error};
+use core::{fmt, str::FromStr};
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(feature = "pmac")]
+use crate::{Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead};
+
+/// AEAD algorithms provided by this crate
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// AES-SIV with a 128-bit key (`Aes128SivAead`)
+    Aes128Siv,
+
+    /// AES-SIV with a 192-bit key (`Aes192SivAead`)
+    Aes192Siv,
+
+    /// AES-SIV with a 256-bit key (`Aes256SivAead`)
+    Aes256Siv,
+
+    /// AES-PMAC-SIV with a 128-bit key (`Aes128PmacSivAead`)
+    #[cfg(feature = "pmac")]
+    Aes128PmacSiv,
+
+    /// AES-PMAC-SIV with a 192-bit key (`Aes192PmacSivAead`)
+    #[cfg(feature = "pmac")]
+    Aes192PmacSiv,
+
+    /// AES-PMAC-SIV with a 256-bit key (`Aes256PmacSivAead`)
+    #[cfg(feature = "pmac")]
+    Aes256PmacSiv,
+}
+
+impl Algorithm {
+    /// Name of this algorithm, as parsed by `from_str`
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Aes128Siv => "AES-128-SIV",
+            Algorithm::Aes192Siv => "AES-192-SIV",
+            Algorithm::Aes256Siv => "AES-256-SIV",
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes128PmacSiv => "AES-128-PMAC-SIV",
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes192PmacSiv => "AES-192-PMAC-SIV",
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes256PmacSiv => "AES-256-PMAC-SIV",
+        }
+    }
+
+    /// Size of a key for this algorithm in bytes
+    pub fn key_size(self) -> usize {
+        match self {
+            Algorithm::Aes128Siv => Aes128SivAead::KEY_SIZE,
+            Algorithm::Aes192Siv => Aes192SivAead::KEY_SIZE,
+            Algorithm::Aes256Siv => Aes256SivAead::KEY_SIZE,
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes128PmacSiv => Aes128PmacSivAead::KEY_SIZE,
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes192PmacSiv => Aes192PmacSivAead::KEY_SIZE,
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes256PmacSiv => Aes256PmacSivAead::KEY_SIZE,
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    /// Parse an algorithm name such as `"AES-128-SIV"` or
+    /// `"AES-256-PMAC-SIV"` (ignoring ASCII case), returning
+    /// `Error::UnknownAlgorithm` if it isn't recognized (including
+    /// PMAC-based algorithms when the `pmac` feature is disabled)
+    fn from_str(name: &str) -> Result<Self, Error> {
+        [
+            Algorithm::Aes128Siv,
+            Algorithm::Aes192Siv,
+            Algorithm::Aes256Siv,
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes128PmacSiv,
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes192PmacSiv,
+            #[cfg(feature = "pmac")]
+            Algorithm::Aes256PmacSiv,
+        ]
+        .iter()
+        .copied()
+        .find(|alg| alg.name().eq_ignore_ascii_case(name))
+        .ok_or(Error::UnknownAlgorithm)
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Object-safe subset of the `Aead` trait, so instances of different
+/// algorithms can be used through the same type (e.g. `Box<dyn DynAead>`)
+pub trait DynAead {
+    /// Encrypt the given plaintext in-place (see `Aead::encrypt_in_place`)
+    fn encrypt_in_place(&mut self, nonce: &[u8], associated_data: &[u8], buffer: &mut [u8]);
+
+    /// Decrypt the given ciphertext in-place (see `Aead::decrypt_in_place`)
+    fn decrypt_in_place<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error>;
+
+    /// Check that the given ciphertext authenticates (see `Aead::verify`)
+    fn verify(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<(), Error>;
+
+    /// Encrypt the given plaintext, allocating and returning a Vec<u8> for
+    /// the ciphertext (see `Aead::encrypt`)
+    #[cfg(feature = "alloc")]
+    fn encrypt(&mut self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8>;
+
+    /// Decrypt the given ciphertext, allocating and returning a Vec<u8> for
+    /// the plaintext (see `Aead::decrypt`)
+    #[cfg(feature = "alloc")]
+    fn decrypt(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+}
+
+impl<A: Aead> DynAead for A {
+    fn encrypt_in_place(&mut self, nonce: &[u8], associated_data: &[u8], buffer: &mut [u8]) {
+        Aead::encrypt_in_place(self, nonce, associated_data, buffer)
+    }
+
+    fn decrypt_in_place<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        Aead::decrypt_in_place(self, nonce, associated_data, buffer)
+    }
+
+    fn verify(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<(), Error> {
+        Aead::verify(self, nonce, associated_data, ciphertext)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn encrypt(&mut self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        Aead::encrypt(self, nonce, associated_data, plaintext)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn decrypt(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        Aead::decrypt(self, nonce, associated_data, ciphertext)
+    }
+}
+
+/// Create a new instance of the given algorithm behind a trait object,
+/// returning `Error::InvalidKeySize` if the key is the wrong size for it
+#[cfg(feature = "alloc")]
+pub fn new_dyn(alg: Algorithm, key: &[u8]) -> Result<Box<dyn DynAead + Send + Sync>, Error> {
+    Ok(match alg {
+        Algorithm::Aes128Siv => Box::new(Aes128SivAead::try_new(key)?),
+        Algorithm::Aes192Siv => Box::new(Aes192SivAead::try_new(key)?),
+        Algorithm::Aes256Siv => Box::new(Aes256SivAead::try_new(key)?),
+        #[cfg(feature = "pmac")]
+        Algorithm::Aes128PmacSiv => Box::new(Aes128PmacSivAead::try_new(key)?),
+        #[cfg(feature = "pmac")]
+        Algorithm::Aes192PmacSiv => Box::new(Aes192PmacSivAead::try_new(key)?),
+        #[cfg(feature = "pmac")]
+        Algorithm::Aes256PmacSiv => Box::new(Aes256PmacSivAead::try_new(key)?),
+    })
+}
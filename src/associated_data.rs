@@ -0,0 +1,73 @@
+//! `associated_data.rs`: Builder for multi-part associated data
+
+use aes_siv::siv::MAX_HEADERS;
+use core::fmt;
+
+/// Associated data assembled from several fields, each of which is
+/// authenticated as a separate input to S2V.
+///
+/// Unlike concatenating the fields into a single buffer, this makes it
+/// impossible for two different lists of fields to produce the same
+/// associated data (e.g. `["ab", "c"]` and `["a", "bc"]`). The fields are
+/// borrowed rather than copied, so building an `AssociatedData` does not
+/// allocate.
+///
+/// Note that an empty `AssociatedData` is distinct from associated data
+/// consisting of a single empty field.
+#[derive(Clone)]
+pub struct AssociatedData<'a> {
+    items: [&'a [u8]; MAX_HEADERS - 1],
+    len: usize,
+}
+
+impl<'a> AssociatedData<'a> {
+    /// Maximum number of fields which can be pushed (one S2V input is
+    /// reserved for the nonce)
+    pub const MAX_ITEMS: usize = MAX_HEADERS - 1;
+
+    /// Create a new, empty `AssociatedData`
+    pub fn new() -> Self {
+        Self {
+            items: [&[]; Self::MAX_ITEMS],
+            len: 0,
+        }
+    }
+
+    /// Add a field to the associated data
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `MAX_ITEMS` fields are pushed.
+    pub fn push(&mut self, item: &'a [u8]) {
+        assert!(self.len < Self::MAX_ITEMS, "too many associated data items");
+        self.items[self.len] = item;
+        self.len += 1;
+    }
+
+    /// Borrow the fields which have been pushed, in order
+    pub fn as_slice(&self) -> &[&'a [u8]] {
+        &self.items[..self.len]
+    }
+
+    /// Number of fields which have been pushed
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Have no fields been pushed?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<'a> Default for AssociatedData<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> fmt::Debug for AssociatedData<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
@@ -0,0 +1,315 @@
+//! `async_io.rs`: `futures::io` adapters for encrypting and decrypting STREAMs
+//!
+//! These are the asynchronous counterparts of the adapters in the `io`
+//! module, and use the same length-prefixed message framing.
+
+use crate::{
+    generic_array::typenum::Unsigned,
+    io::{check_chunk_size, message_len, truncated, DEFAULT_CHUNK_SIZE, LENGTH_PREFIX_SIZE},
+    stream::{Counter, Decryptor, Encryptor, NonceEncoder32},
+    Aead,
+};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_io::{AsyncRead, AsyncWrite};
+use std::{io, vec::Vec};
+
+/// Return early if the given poll is pending or has failed
+macro_rules! ready {
+    ($e:expr) => {
+        match $e {
+            Poll::Ready(Ok(value)) => value,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+    };
+}
+
+/// Encrypts data written to it as a STREAM, splitting it into fixed-size
+/// messages (chunks) and writing their ciphertexts to the inner writer.
+///
+/// At most one message is buffered at a time: once a message is full, it
+/// is written to the inner writer before any more data is accepted.
+///
+/// The last message in the STREAM is written when the writer is closed.
+/// A STREAM which is dropped without being closed will be rejected as
+/// truncated when decrypting.
+pub struct AsyncStreamWriter<A: Aead, W: AsyncWrite + Unpin, C: Counter = NonceEncoder32> {
+    encryptor: Option<Encryptor<A, C>>,
+    writer: W,
+    buffer: Vec<u8>,
+    written: Option<usize>,
+    chunk_size: usize,
+}
+
+// Neither the writer nor any other field is ever pinned
+impl<A: Aead, W: AsyncWrite + Unpin, C: Counter> Unpin for AsyncStreamWriter<A, W, C> {}
+
+impl<A: Aead, W: AsyncWrite + Unpin, C: Counter> AsyncStreamWriter<A, W, C> {
+    /// Create a new STREAM writer using the default chunk size
+    pub fn new(encryptor: Encryptor<A, C>, writer: W) -> Self {
+        Self::with_chunk_size(encryptor, writer, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new STREAM writer which encrypts plaintext in messages of
+    /// the given size (in bytes).
+    ///
+    /// Panics if the chunk size is zero or too large for the length prefix.
+    pub fn with_chunk_size(encryptor: Encryptor<A, C>, writer: W, chunk_size: usize) -> Self {
+        check_chunk_size(chunk_size, A::TagSize::to_usize());
+
+        let header_size = LENGTH_PREFIX_SIZE + A::TagSize::to_usize();
+        let mut buffer = Vec::with_capacity(header_size + chunk_size);
+        buffer.resize(header_size, 0);
+
+        Self {
+            encryptor: Some(encryptor),
+            writer,
+            buffer,
+            written: None,
+            chunk_size,
+        }
+    }
+
+    /// Borrow the inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Consume this STREAM writer, returning the inner writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Length of the plaintext currently buffered
+    fn buffered(&self) -> usize {
+        self.buffer.len() - LENGTH_PREFIX_SIZE - A::TagSize::to_usize()
+    }
+
+    /// Encrypt the buffered message, preparing it to be written
+    fn seal(&mut self, last: bool) -> io::Result<()> {
+        let message = &mut self.buffer[LENGTH_PREFIX_SIZE..];
+
+        if last {
+            self.encryptor
+                .take()
+                .expect("STREAM writer already closed")
+                .encrypt_last_in_place(b"", message);
+        } else {
+            self.encryptor
+                .as_mut()
+                .expect("STREAM writer already closed")
+                .try_encrypt_next_in_place(b"", message)?;
+        }
+
+        let len = (self.buffer.len() - LENGTH_PREFIX_SIZE) as u32;
+        self.buffer[..LENGTH_PREFIX_SIZE].copy_from_slice(&len.to_be_bytes());
+        self.written = Some(0);
+        Ok(())
+    }
+
+    /// Write any encrypted message to the inner writer
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while let Some(written) = self.written {
+            if written == self.buffer.len() {
+                self.buffer
+                    .truncate(LENGTH_PREFIX_SIZE + A::TagSize::to_usize());
+                self.written = None;
+                break;
+            }
+
+            let n = ready!(Pin::new(&mut self.writer).poll_write(cx, &self.buffer[written..]));
+
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+
+            self.written = Some(written + n);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<A: Aead, W: AsyncWrite + Unpin, C: Counter> AsyncWrite for AsyncStreamWriter<A, W, C> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx));
+
+        if this.encryptor.is_none() {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Other,
+                "STREAM writer already closed",
+            )));
+        }
+
+        let len = (this.chunk_size - this.buffered()).min(data.len());
+        this.buffer.extend_from_slice(&data[..len]);
+
+        if this.buffered() == this.chunk_size {
+            this.seal(false)?;
+        }
+
+        Poll::Ready(Ok(len))
+    }
+
+    /// Write any full messages and flush the inner writer.
+    ///
+    /// Buffered plaintext is not written until a full message has been
+    /// accumulated, or the writer is closed.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx));
+        Pin::new(&mut this.writer).poll_flush(cx)
+    }
+
+    /// Encrypt and write the last message in the STREAM (which may be
+    /// empty), then close the inner writer.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx));
+
+        if this.encryptor.is_some() {
+            this.seal(true)?;
+            ready!(this.poll_drain(cx));
+        }
+
+        Pin::new(&mut this.writer).poll_close(cx)
+    }
+}
+
+/// Decrypts a STREAM read from the inner reader, authenticating each
+/// message before serving its plaintext.
+///
+/// Ciphertext may arrive from the inner reader in arbitrarily sized pieces:
+/// it is buffered until a complete message (of at most the chunk size) has
+/// been received, and plaintext is only served once that message has been
+/// authenticated.
+///
+/// Reads return `Ok(0)` only after the last message in the STREAM has been
+/// authenticated. If the inner reader ends before then, the STREAM has been
+/// truncated and an `io::ErrorKind::UnexpectedEof` error is returned.
+/// Messages which fail to authenticate result in an
+/// `io::ErrorKind::InvalidData` error.
+pub struct AsyncStreamReader<A: Aead, R: AsyncRead + Unpin, C: Counter = NonceEncoder32> {
+    decryptor: Decryptor<A, C>,
+    reader: R,
+    buffer: Vec<u8>,
+    filled: usize,
+    position: Option<usize>,
+    chunk_size: usize,
+    finished: bool,
+}
+
+// Neither the reader nor any other field is ever pinned
+impl<A: Aead, R: AsyncRead + Unpin, C: Counter> Unpin for AsyncStreamReader<A, R, C> {}
+
+impl<A: Aead, R: AsyncRead + Unpin, C: Counter> AsyncStreamReader<A, R, C> {
+    /// Create a new STREAM reader using the default chunk size
+    pub fn new(decryptor: Decryptor<A, C>, reader: R) -> Self {
+        Self::with_chunk_size(decryptor, reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new STREAM reader which accepts messages containing at most
+    /// the given number of bytes of plaintext. This must match the chunk
+    /// size of the `AsyncStreamWriter` which produced the STREAM.
+    pub fn with_chunk_size(decryptor: Decryptor<A, C>, reader: R, chunk_size: usize) -> Self {
+        Self {
+            decryptor,
+            reader,
+            buffer: vec![0; LENGTH_PREFIX_SIZE],
+            filled: 0,
+            position: None,
+            chunk_size,
+            finished: false,
+        }
+    }
+
+    /// Borrow the inner reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consume this STREAM reader, returning the inner reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the next length-prefixed message into the buffer
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            while self.filled < self.buffer.len() {
+                let n = ready!(
+                    Pin::new(&mut self.reader).poll_read(cx, &mut self.buffer[self.filled..])
+                );
+
+                if n == 0 {
+                    return Poll::Ready(Err(truncated()));
+                }
+
+                self.filled += n;
+            }
+
+            // Messages are never empty, so a buffer holding more than the
+            // length prefix contains a complete message
+            if self.buffer.len() > LENGTH_PREFIX_SIZE {
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut prefix = [0u8; LENGTH_PREFIX_SIZE];
+            prefix.copy_from_slice(&self.buffer);
+
+            let len = message_len(prefix, A::TagSize::to_usize(), self.chunk_size)?;
+            self.buffer.resize(LENGTH_PREFIX_SIZE + len, 0);
+        }
+    }
+}
+
+impl<A: Aead, R: AsyncRead + Unpin, C: Counter> AsyncRead for AsyncStreamReader<A, R, C> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        loop {
+            if let Some(position) = this.position {
+                if position < this.buffer.len() {
+                    let plaintext = &this.buffer[position..];
+                    let len = plaintext.len().min(buf.len());
+                    buf[..len].copy_from_slice(&plaintext[..len]);
+                    this.position = Some(position + len);
+                    return Poll::Ready(Ok(len));
+                }
+
+                if this.finished {
+                    return Poll::Ready(Ok(0));
+                }
+
+                this.buffer.truncate(LENGTH_PREFIX_SIZE);
+                this.filled = 0;
+                this.position = None;
+            }
+
+            ready!(this.poll_fill(cx));
+
+            let (last, _) = this
+                .decryptor
+                .decrypt_auto_in_place(b"", &mut this.buffer[LENGTH_PREFIX_SIZE..])?;
+
+            this.position = Some(LENGTH_PREFIX_SIZE + A::TagSize::to_usize());
+            this.finished = last;
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! `backend.rs`: AES block ciphers which select a hardware accelerated
+//! implementation at runtime, falling back to the constant-time software
+//! implementation from the `aes` crate.
+//!
+//! The hardware implementations are Intel AES-NI on `x86`/`x86_64`, and the
+//! ARMv8 Cryptography Extensions on `aarch64`. The latter are opt-in with
+//! the `armv8` feature (which enables `std` for runtime detection), since
+//! their intrinsics need Rust 1.72: without it, `aarch64` targets use the
+//! software implementation.
+//!
+//! PMAC only uses doubling in GF(2^128) (to precompute its offsets), not
+//! field multiplication, so there's nothing for carryless multiply (CLMUL
+//! or PMULL) to accelerate. Instead, PMAC-SIV benefits from the hardware
+//! implementations encrypting 8 blocks at a time.
+//!
+//! CPU feature detection happens once and is then cached, so selecting the
+//! implementation when a key is initialized is cheap.
+//!
+//! The `force-soft` feature compiles out the hardware implementations, so
+//! the software implementation is always used (e.g. for reproducible
+//! benchmarks, or to avoid relying on the hardware AES instructions).
+//!
+//! The `*SivAead` type aliases in this crate (and the STREAM types built on
+//! them) use these ciphers. `siv::Siv` and its aliases use the `aes` crate
+//! directly.
+
+use crate::generic_array::{
+    typenum::{U16, U24, U32, U8},
+    GenericArray,
+};
+use block_cipher_trait::BlockCipher;
+use core::{
+    fmt, mem, ptr,
+    sync::atomic::{self, Ordering},
+};
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(feature = "force-soft")
+))]
+mod aesni;
+// The intrinsics need Rust 1.72, the MSRV of the `armv8` feature (see
+// `lib.rs`), rather than the crate's MSRV
+#[cfg(all(
+    target_arch = "aarch64",
+    feature = "armv8",
+    not(feature = "force-soft")
+))]
+#[clippy::msrv = "1.72.0"]
+mod armv8;
+
+// The hardware implementation available on this target (if any). Each one
+// has the same interface, so the dispatch below is shared between them.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(feature = "force-soft")
+))]
+use self::aesni as hw;
+#[cfg(all(
+    target_arch = "aarch64",
+    feature = "armv8",
+    not(feature = "force-soft")
+))]
+use self::armv8 as hw;
+
+/// Stand-in for targets without a hardware implementation (or when the
+/// `force-soft` feature is enabled)
+#[cfg(any(
+    feature = "force-soft",
+    not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", feature = "armv8")
+    ))
+))]
+#[allow(unsafe_code)]
+mod hw {
+    use super::Backend;
+    use crate::generic_array::{typenum::U16, GenericArray};
+
+    pub(super) const BACKEND: Backend = Backend::Soft;
+
+    pub(super) fn detect() -> bool {
+        false
+    }
+
+    /// Never constructed, since `detect` never succeeds
+    #[derive(Clone)]
+    pub(super) struct Keys(());
+
+    impl Keys {
+        pub(super) unsafe fn new(_key: &[u8]) -> Self {
+            unreachable!("no hardware AES implementation")
+        }
+
+        pub(super) unsafe fn encrypt_block(&self, _block: &mut [u8]) {
+            unreachable!("no hardware AES implementation")
+        }
+
+        pub(super) unsafe fn encrypt_blocks(&self, _blocks: &mut [GenericArray<u8, U16>]) {
+            unreachable!("no hardware AES implementation")
+        }
+
+        pub(super) unsafe fn decrypt_block(&self, _block: &mut [u8]) {
+            unreachable!("no hardware AES implementation")
+        }
+    }
+}
+
+/// Implementations of AES which can be used by this crate
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Backend {
+    /// Constant-time software implementation
+    Soft,
+
+    /// Intel AES-NI instructions
+    AesNi,
+
+    /// ARMv8 Cryptography Extensions
+    Armv8,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Backend::Soft => "software",
+            Backend::AesNi => "AES-NI",
+            Backend::Armv8 => "ARMv8 Cryptography Extensions",
+        })
+    }
+}
+
+/// Implementation of AES which the `*SivAead` types (and the STREAM types
+/// built on them) use on this CPU, e.g. for logging at startup.
+///
+/// The first call performs CPU feature detection, and later calls return
+/// the cached result.
+pub fn active_backend() -> Backend {
+    detect()
+}
+
+/// Implementation of AES which will be used on this CPU
+pub(crate) fn detect() -> Backend {
+    if cached::detect() {
+        hw::BACKEND
+    } else {
+        Backend::Soft
+    }
+}
+
+/// Cached runtime detection of support for the hardware implementation
+mod cached {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    const UNKNOWN: u8 = 0;
+    const UNSUPPORTED: u8 = 1;
+    const SUPPORTED: u8 = 2;
+
+    static STATE: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+    /// Does this CPU support the hardware implementation?
+    pub(super) fn detect() -> bool {
+        match STATE.load(Ordering::Relaxed) {
+            UNKNOWN => {
+                let supported = super::hw::detect();
+                let state = if supported { SUPPORTED } else { UNSUPPORTED };
+                STATE.store(state, Ordering::Relaxed);
+                supported
+            }
+            state => state == SUPPORTED,
+        }
+    }
+}
+
+macro_rules! define_aes {
+    ($name:ident, $soft:ty, $key_size:ty, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone)]
+        pub struct $name {
+            inner: Inner<$soft>,
+        }
+
+        impl BlockCipher for $name {
+            type KeySize = $key_size;
+            type BlockSize = U16;
+            type ParBlocks = U8;
+
+            fn new(key: &GenericArray<u8, $key_size>) -> Self {
+                let inner = match detect() {
+                    Backend::Soft => Inner::Soft(<$soft>::new(key)),
+                    _ => Inner::Hw(hw_keys(key)),
+                };
+
+                Self { inner }
+            }
+
+            #[inline]
+            fn encrypt_block(&self, block: &mut GenericArray<u8, U16>) {
+                match &self.inner {
+                    Inner::Soft(cipher) => cipher.encrypt_block(block),
+                    Inner::Hw(keys) => hw_encrypt_block(keys, block),
+                }
+            }
+
+            #[inline]
+            fn decrypt_block(&self, block: &mut GenericArray<u8, U16>) {
+                match &self.inner {
+                    Inner::Soft(cipher) => cipher.decrypt_block(block),
+                    Inner::Hw(keys) => hw_decrypt_block(keys, block),
+                }
+            }
+
+            #[inline]
+            fn encrypt_blocks(&self, blocks: &mut GenericArray<GenericArray<u8, U16>, U8>) {
+                match &self.inner {
+                    Inner::Soft(cipher) => cipher.encrypt_blocks(blocks),
+                    Inner::Hw(keys) => hw_encrypt_blocks(keys, blocks),
+                }
+            }
+        }
+
+        impl $name {
+            /// Implementation of AES used by this cipher instance
+            pub fn backend(&self) -> Backend {
+                match &self.inner {
+                    Inner::Soft(_) => Backend::Soft,
+                    Inner::Hw(_) => hw::BACKEND,
+                }
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} {{ .. }}", stringify!($name))
+            }
+        }
+
+        impl Drop for $name {
+            /// Zeroize the key schedule (the hardware ones zeroize themselves)
+            fn drop(&mut self) {
+                if let Inner::Soft(cipher) = &mut self.inner {
+                    zeroize_soft(cipher);
+                }
+            }
+        }
+    };
+}
+
+define_aes!(Aes128, aes::Aes128, U16, "AES-128 block cipher");
+define_aes!(Aes192, aes::Aes192, U24, "AES-192 block cipher");
+define_aes!(Aes256, aes::Aes256, U32, "AES-256 block cipher");
+
+/// Key schedule of one of the available AES implementations
+// The software key schedules are larger than the hardware ones, but clippy
+// can't see that through the type parameter
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+enum Inner<S> {
+    Soft(S),
+    Hw(hw::Keys),
+}
+
+/// Overwrite a software key schedule with zeroes.
+///
+/// Only used with the `aes` crate's ciphers, whose key schedules are plain
+/// arrays of integers (so all zeroes is a valid value) with no destructor.
+#[allow(unsafe_code)]
+fn zeroize_soft<S: BlockCipher>(cipher: &mut S) {
+    // Volatile so the write isn't optimized away
+    unsafe { ptr::write_volatile(cipher, mem::zeroed()) };
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+// The functions below are only called after `detect` has returned the
+// hardware backend, which guarantees the CPU supports the instructions.
+
+#[allow(unsafe_code)]
+fn hw_keys(key: &[u8]) -> hw::Keys {
+    unsafe { hw::Keys::new(key) }
+}
+
+#[allow(unsafe_code)]
+#[inline]
+fn hw_encrypt_block(keys: &hw::Keys, block: &mut [u8]) {
+    unsafe { keys.encrypt_block(block) }
+}
+
+#[allow(unsafe_code)]
+#[inline]
+fn hw_decrypt_block(keys: &hw::Keys, block: &mut [u8]) {
+    unsafe { keys.decrypt_block(block) }
+}
+
+#[allow(unsafe_code)]
+#[inline]
+fn hw_encrypt_blocks(keys: &hw::Keys, blocks: &mut [GenericArray<u8, U16>]) {
+    unsafe { keys.encrypt_blocks(blocks) }
+}
@@ -0,0 +1,191 @@
+//! `backend/aesni.rs`: AES using the Intel AES-NI instructions
+//!
+//! Everything here must only be called after checking at runtime that the
+//! CPU supports AES-NI (see `detect`).
+
+// The hardware backends and the FFI are the only code allowed to be unsafe
+#![allow(unsafe_code)]
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use super::Backend;
+use crate::generic_array::{typenum::U16, GenericArray};
+use core::ptr;
+use zeroize::Zeroize;
+
+/// Backend implemented by this module
+pub(super) const BACKEND: Backend = Backend::AesNi;
+
+/// Maximum number of round keys (for AES-256)
+const MAX_ROUND_KEYS: usize = 15;
+
+/// Round constants used by the key schedule
+const RCON: [u32; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Does this CPU support AES-NI?
+#[cfg(feature = "std")]
+pub(super) fn detect() -> bool {
+    use std::is_x86_feature_detected;
+    is_x86_feature_detected!("aes") && is_x86_feature_detected!("sse2")
+}
+
+/// Without `std`, AES-NI is only used if it's enabled at compile time
+#[cfg(not(feature = "std"))]
+pub(super) fn detect() -> bool {
+    cfg!(all(target_feature = "aes", target_feature = "sse2"))
+}
+
+/// Expanded AES encryption and decryption key schedules
+#[derive(Clone)]
+pub(super) struct Keys {
+    encrypt: [__m128i; MAX_ROUND_KEYS],
+    decrypt: [__m128i; MAX_ROUND_KEYS],
+    rounds: usize,
+}
+
+impl Keys {
+    /// Expand the given AES-128, AES-192, or AES-256 key
+    ///
+    /// Safety: the CPU must support AES-NI
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn new(key: &[u8]) -> Self {
+        let nk = key.len() / 4;
+        let rounds = nk + 6;
+        let mut words = [0u32; 4 * MAX_ROUND_KEYS];
+
+        for (word, bytes) in words.iter_mut().zip(key.chunks(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        // FIPS 197 Section 5.2, using AESKEYGENASSIST for SubWord so the key
+        // schedule doesn't use any lookup tables
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+
+            if i % nk == 0 {
+                temp = sub_word(temp).1 ^ RCON[i / nk - 1];
+            } else if nk > 6 && i % nk == 4 {
+                temp = sub_word(temp).0;
+            }
+
+            words[i] = words[i - nk] ^ temp;
+        }
+
+        let mut keys = Keys {
+            encrypt: [_mm_setzero_si128(); MAX_ROUND_KEYS],
+            decrypt: [_mm_setzero_si128(); MAX_ROUND_KEYS],
+            rounds,
+        };
+
+        for (round_key, words) in keys.encrypt.iter_mut().zip(words.chunks(4)) {
+            *round_key = _mm_set_epi32(
+                words[3] as i32,
+                words[2] as i32,
+                words[1] as i32,
+                words[0] as i32,
+            );
+        }
+
+        words.zeroize();
+
+        // Equivalent inverse cipher (FIPS 197 Section 5.3.5)
+        keys.decrypt[0] = keys.encrypt[rounds];
+        keys.decrypt[rounds] = keys.encrypt[0];
+
+        for i in 1..rounds {
+            keys.decrypt[i] = _mm_aesimc_si128(keys.encrypt[rounds - i]);
+        }
+
+        keys
+    }
+
+    /// Encrypt a block in-place
+    ///
+    /// Safety: the CPU must support AES-NI
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn encrypt_block(&self, block: &mut [u8]) {
+        let keys = &self.encrypt[..=self.rounds];
+        let mut state = _mm_xor_si128(load(block), keys[0]);
+
+        for key in &keys[1..self.rounds] {
+            state = _mm_aesenc_si128(state, *key);
+        }
+
+        store(block, _mm_aesenclast_si128(state, keys[self.rounds]));
+    }
+
+    /// Encrypt 8 blocks in-place, interleaving them to make use of the
+    /// pipelining of the AES instructions
+    ///
+    /// Safety: the CPU must support AES-NI
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn encrypt_blocks(&self, blocks: &mut [GenericArray<u8, U16>]) {
+        let keys = &self.encrypt[..=self.rounds];
+        assert_eq!(blocks.len(), 8);
+        let mut state = [_mm_setzero_si128(); 8];
+
+        for (state, block) in state.iter_mut().zip(blocks.iter()) {
+            *state = _mm_xor_si128(load(block), keys[0]);
+        }
+
+        for key in &keys[1..self.rounds] {
+            for state in state.iter_mut() {
+                *state = _mm_aesenc_si128(*state, *key);
+            }
+        }
+
+        for (state, block) in state.iter().zip(blocks.iter_mut()) {
+            store(block, _mm_aesenclast_si128(*state, keys[self.rounds]));
+        }
+    }
+
+    /// Decrypt a block in-place
+    ///
+    /// Safety: the CPU must support AES-NI
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn decrypt_block(&self, block: &mut [u8]) {
+        let keys = &self.decrypt[..=self.rounds];
+        let mut state = _mm_xor_si128(load(block), keys[0]);
+
+        for key in &keys[1..self.rounds] {
+            state = _mm_aesdec_si128(state, *key);
+        }
+
+        store(block, _mm_aesdeclast_si128(state, keys[self.rounds]));
+    }
+}
+
+impl Drop for Keys {
+    fn drop(&mut self) {
+        for key in self.encrypt.iter_mut().chain(self.decrypt.iter_mut()) {
+            // Volatile so the writes aren't optimized away
+            unsafe { ptr::write_volatile(key, _mm_set1_epi8(0)) };
+        }
+    }
+}
+
+/// Compute `(SubWord(word), SubWord(RotWord(word)))` with AESKEYGENASSIST
+#[target_feature(enable = "aes,sse2")]
+unsafe fn sub_word(word: u32) -> (u32, u32) {
+    let result = _mm_aeskeygenassist_si128(_mm_set_epi32(0, 0, word as i32, 0), 0);
+    let sub = _mm_cvtsi128_si32(result) as u32;
+    let sub_rot = _mm_cvtsi128_si32(_mm_shuffle_epi32(result, 0x55)) as u32;
+    (sub, sub_rot)
+}
+
+/// Load a 16-byte block
+#[target_feature(enable = "sse2")]
+unsafe fn load(block: &[u8]) -> __m128i {
+    assert_eq!(block.len(), 16);
+    _mm_loadu_si128(block.as_ptr() as *const __m128i)
+}
+
+/// Store a 16-byte block
+#[target_feature(enable = "sse2")]
+unsafe fn store(block: &mut [u8], value: __m128i) {
+    assert_eq!(block.len(), 16);
+    _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, value)
+}
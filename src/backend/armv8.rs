@@ -0,0 +1,186 @@
+//! `backend/armv8.rs`: AES using the ARMv8 Cryptography Extensions
+//!
+//! Everything here must only be called after checking at runtime that the
+//! CPU supports the AES instructions (see `detect`).
+
+// The hardware backends and the FFI are the only code allowed to be unsafe.
+#![allow(unsafe_code)]
+
+use super::Backend;
+use crate::generic_array::{typenum::U16, GenericArray};
+use core::{arch::aarch64::*, ptr};
+use zeroize::Zeroize;
+
+/// Backend implemented by this module
+pub(super) const BACKEND: Backend = Backend::Armv8;
+
+/// Maximum number of round keys (for AES-256)
+const MAX_ROUND_KEYS: usize = 15;
+
+/// Round constants used by the key schedule
+const RCON: [u32; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Does this CPU support the AES instructions?
+pub(super) fn detect() -> bool {
+    std::arch::is_aarch64_feature_detected!("aes")
+}
+
+/// Expanded AES encryption and decryption key schedules
+#[derive(Clone)]
+pub(super) struct Keys {
+    encrypt: [uint8x16_t; MAX_ROUND_KEYS],
+    decrypt: [uint8x16_t; MAX_ROUND_KEYS],
+    rounds: usize,
+}
+
+impl Keys {
+    /// Expand the given AES-128, AES-192, or AES-256 key
+    ///
+    /// Safety: the CPU must support the AES instructions
+    #[target_feature(enable = "aes")]
+    pub(super) unsafe fn new(key: &[u8]) -> Self {
+        let nk = key.len() / 4;
+        let rounds = nk + 6;
+        let mut words = [0u32; 4 * MAX_ROUND_KEYS];
+
+        for (word, bytes) in words.iter_mut().zip(key.chunks(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        // FIPS 197 Section 5.2, using AESE for SubWord so the key schedule
+        // doesn't use any lookup tables
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+
+            if i % nk == 0 {
+                // RotWord of a little endian word
+                temp = sub_word(temp.rotate_right(8)) ^ RCON[i / nk - 1];
+            } else if nk > 6 && i % nk == 4 {
+                temp = sub_word(temp);
+            }
+
+            words[i] = words[i - nk] ^ temp;
+        }
+
+        let mut keys = Keys {
+            encrypt: [vdupq_n_u8(0); MAX_ROUND_KEYS],
+            decrypt: [vdupq_n_u8(0); MAX_ROUND_KEYS],
+            rounds,
+        };
+
+        let mut bytes = [0u8; 16];
+
+        for (round_key, words) in keys.encrypt.iter_mut().zip(words.chunks(4)) {
+            for (chunk, word) in bytes.chunks_mut(4).zip(words) {
+                chunk.copy_from_slice(&word.to_le_bytes());
+            }
+
+            *round_key = vld1q_u8(bytes.as_ptr());
+        }
+
+        words.zeroize();
+        bytes.zeroize();
+
+        // Equivalent inverse cipher (FIPS 197 Section 5.3.5)
+        keys.decrypt[0] = keys.encrypt[rounds];
+        keys.decrypt[rounds] = keys.encrypt[0];
+
+        for i in 1..rounds {
+            keys.decrypt[i] = vaesimcq_u8(keys.encrypt[rounds - i]);
+        }
+
+        keys
+    }
+
+    /// Encrypt a block in-place
+    ///
+    /// Safety: the CPU must support the AES instructions
+    #[target_feature(enable = "aes")]
+    pub(super) unsafe fn encrypt_block(&self, block: &mut [u8]) {
+        let keys = &self.encrypt[..=self.rounds];
+        let mut state = load(block);
+
+        for key in &keys[..self.rounds - 1] {
+            state = vaesmcq_u8(vaeseq_u8(state, *key));
+        }
+
+        state = vaeseq_u8(state, keys[self.rounds - 1]);
+        store(block, veorq_u8(state, keys[self.rounds]));
+    }
+
+    /// Encrypt 8 blocks in-place, interleaving them to make use of the
+    /// pipelining of the AES instructions
+    ///
+    /// Safety: the CPU must support the AES instructions
+    #[target_feature(enable = "aes")]
+    pub(super) unsafe fn encrypt_blocks(&self, blocks: &mut [GenericArray<u8, U16>]) {
+        let keys = &self.encrypt[..=self.rounds];
+        assert_eq!(blocks.len(), 8);
+        let mut state = [vdupq_n_u8(0); 8];
+
+        for (state, block) in state.iter_mut().zip(blocks.iter()) {
+            *state = load(block);
+        }
+
+        for key in &keys[..self.rounds - 1] {
+            for state in state.iter_mut() {
+                *state = vaesmcq_u8(vaeseq_u8(*state, *key));
+            }
+        }
+
+        for (state, block) in state.iter().zip(blocks.iter_mut()) {
+            let state = vaeseq_u8(*state, keys[self.rounds - 1]);
+            store(block, veorq_u8(state, keys[self.rounds]));
+        }
+    }
+
+    /// Decrypt a block in-place
+    ///
+    /// Safety: the CPU must support the AES instructions
+    #[target_feature(enable = "aes")]
+    pub(super) unsafe fn decrypt_block(&self, block: &mut [u8]) {
+        let keys = &self.decrypt[..=self.rounds];
+        let mut state = load(block);
+
+        for key in &keys[..self.rounds - 1] {
+            state = vaesimcq_u8(vaesdq_u8(state, *key));
+        }
+
+        state = vaesdq_u8(state, keys[self.rounds - 1]);
+        store(block, veorq_u8(state, keys[self.rounds]));
+    }
+}
+
+impl Drop for Keys {
+    fn drop(&mut self) {
+        for key in self.encrypt.iter_mut().chain(self.decrypt.iter_mut()) {
+            // Volatile so the writes aren't optimized away
+            unsafe { ptr::write_volatile(key, vdupq_n_u8(0)) };
+        }
+    }
+}
+
+/// Compute `SubWord(word)` with AESE.
+///
+/// With the word copied into every column of the state and a zero round
+/// key, ShiftRows has no effect, so each column of the result is SubWord.
+#[target_feature(enable = "aes")]
+unsafe fn sub_word(word: u32) -> u32 {
+    let state = vreinterpretq_u8_u32(vdupq_n_u32(word));
+    let result = vaeseq_u8(state, vdupq_n_u8(0));
+    vgetq_lane_u32(vreinterpretq_u32_u8(result), 0)
+}
+
+/// Load a 16-byte block
+#[target_feature(enable = "neon")]
+unsafe fn load(block: &[u8]) -> uint8x16_t {
+    assert_eq!(block.len(), 16);
+    vld1q_u8(block.as_ptr())
+}
+
+/// Store a 16-byte block
+#[target_feature(enable = "neon")]
+unsafe fn store(block: &mut [u8], value: uint8x16_t) {
+    assert_eq!(block.len(), 16);
+    vst1q_u8(block.as_mut_ptr(), value)
+}
@@ -0,0 +1,113 @@
+//! `compat.rs`: Free-standing `seal` and `open` functions in the style of
+//! the other Miscreant libraries, to make porting code which uses them
+//! straightforward
+//!
+//! ```text
+//! Go:    aead.Seal(nil, nonce, plaintext, ad)   (created with NewAEAD)
+//! JS:    await aead.seal(plaintext, nonce, ad)   (created with importKey)
+//! Rust:  compat::seal(key, plaintext, nonce, &[ad])
+//! ```
+//!
+//! As with `AEAD.importKey` in the JavaScript library, the size of the key
+//! (32, 48, or 64 bytes) selects AES-128, AES-192, or AES-256. Each element
+//! of the associated data is authenticated as a separate S2V header, as
+//! with the variadic associated data of the other libraries.
+//!
+//! Every call expands the key schedule from scratch, so for encrypting more
+//! than a handful of messages, create one of the `*SivAead` types instead.
+
+use crate::{Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead, Error};
+use alloc::vec::Vec;
+
+#[cfg(feature = "pmac")]
+use crate::{Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead};
+
+/// Call the same method on the AES-CMAC-SIV or AES-PMAC-SIV type selected
+/// by the size of the key
+macro_rules! with_key_size {
+    ($key:expr, $aes128:ty, $aes192:ty, $aes256:ty, $aead:ident => $body:expr) => {
+        match $key.len() {
+            48 => {
+                let mut $aead = <$aes192>::try_new($key)?;
+                $body
+            }
+            64 => {
+                let mut $aead = <$aes256>::try_new($key)?;
+                $body
+            }
+            _ => {
+                let mut $aead = <$aes128>::try_new($key)?;
+                $body
+            }
+        }
+    };
+}
+
+/// Encrypt and authenticate the given plaintext with AES-CMAC-SIV,
+/// returning the SIV tag followed by the ciphertext
+///
+/// Returns `Error::InvalidKeySize` if the key isn't 32, 48, or 64 bytes.
+///
+/// Panics if more than `siv::MAX_HEADERS - 1` associated data items are
+/// given.
+pub fn seal(
+    key: &[u8],
+    plaintext: &[u8],
+    nonce: &[u8],
+    associated_data: &[&[u8]],
+) -> Result<Vec<u8>, Error> {
+    with_key_size!(key, Aes128SivAead, Aes192SivAead, Aes256SivAead, aead => {
+        Ok(aead.encrypt_headers(nonce, associated_data, plaintext))
+    })
+}
+
+/// Decrypt and authenticate the given AES-CMAC-SIV ciphertext (as produced
+/// by `seal`), returning `Error::AuthenticationFailed` if it isn't authentic
+///
+/// Returns `Error::InvalidKeySize` if the key isn't 32, 48, or 64 bytes.
+pub fn open(
+    key: &[u8],
+    ciphertext: &[u8],
+    nonce: &[u8],
+    associated_data: &[&[u8]],
+) -> Result<Vec<u8>, Error> {
+    with_key_size!(key, Aes128SivAead, Aes192SivAead, Aes256SivAead, aead => {
+        aead.decrypt_headers(nonce, associated_data, ciphertext)
+    })
+}
+
+/// Encrypt and authenticate the given plaintext with AES-PMAC-SIV,
+/// returning the SIV tag followed by the ciphertext
+///
+/// Returns `Error::InvalidKeySize` if the key isn't 32, 48, or 64 bytes.
+///
+/// Panics if more than `siv::MAX_HEADERS - 1` associated data items are
+/// given.
+#[cfg(feature = "pmac")]
+pub fn seal_pmac(
+    key: &[u8],
+    plaintext: &[u8],
+    nonce: &[u8],
+    associated_data: &[&[u8]],
+) -> Result<Vec<u8>, Error> {
+    with_key_size!(key, Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead, aead => {
+        Ok(aead.encrypt_headers(nonce, associated_data, plaintext))
+    })
+}
+
+/// Decrypt and authenticate the given AES-PMAC-SIV ciphertext (as produced
+/// by `seal_pmac`), returning `Error::AuthenticationFailed` if it isn't
+/// authentic
+///
+/// Returns `Error::InvalidKeySize` if the key isn't 32, 48, or 64 bytes.
+#[cfg(feature = "pmac")]
+pub fn open_pmac(
+    key: &[u8],
+    ciphertext: &[u8],
+    nonce: &[u8],
+    associated_data: &[&[u8]],
+) -> Result<Vec<u8>, Error> {
+    with_key_size!(key, Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead, aead => {
+        aead.decrypt_headers(nonce, associated_data, ciphertext)
+    })
+}
@@ -0,0 +1,134 @@
+//! `domain.rs`: Domain separation between the uses of a single key
+//!
+//! `SivAead::with_domain` binds a fixed label to every message, as an
+//! associated data header which S2V authenticates before the caller's
+//! associated data and the nonce. Messages encrypted under one label fail
+//! to authenticate under any other label (or without one), so a key shared
+//! between several contexts can't have its ciphertexts moved between them,
+//! without deriving a separate key for each.
+
+use crate::{
+    generic_array::{typenum::U16, ArrayLength},
+    Error, SivAead,
+};
+use core::{fmt, ops::Add};
+use crypto_mac::Mac;
+use stream_cipher::{NewStreamCipher, SyncStreamCipher};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A `SivAead` which authenticates a fixed domain separation label along
+/// with every message (see `SivAead::with_domain`)
+pub struct DomainSivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    aead: SivAead<C, M>,
+    label: &'static [u8],
+}
+
+impl<C, M> SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    /// Create a copy of this AEAD instance which authenticates the given
+    /// label as the first associated data header of every message, so its
+    /// ciphertexts can't be confused with those of any other label.
+    ///
+    /// The label is a separate input to S2V rather than being concatenated
+    /// with the associated data, so no choice of associated data under one
+    /// label produces a ciphertext which verifies under another.
+    pub fn with_domain(&self, label: &'static [u8]) -> DomainSivAead<C, M> {
+        DomainSivAead {
+            aead: self.clone(),
+            label,
+        }
+    }
+}
+
+impl<C, M> DomainSivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    /// Domain separation label authenticated with every message
+    pub fn label(&self) -> &'static [u8] {
+        self.label
+    }
+
+    /// Encrypt the given plaintext in-place (see `Aead::encrypt_in_place`),
+    /// authenticating the label along with the associated data
+    pub fn encrypt_in_place(&mut self, nonce: &[u8], associated_data: &[u8], buffer: &mut [u8]) {
+        self.aead
+            .encrypt_headers_in_place(nonce, &[self.label, associated_data], buffer)
+    }
+
+    /// Decrypt the given ciphertext in-place (see `Aead::decrypt_in_place`),
+    /// which only authenticates if it was encrypted under the same label
+    pub fn decrypt_in_place<'a>(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.aead
+            .decrypt_headers_in_place(nonce, &[self.label, associated_data], buffer)
+    }
+
+    /// Encrypt the given plaintext, allocating and returning a Vec<u8> for
+    /// the ciphertext (see `Aead::encrypt`)
+    #[cfg(feature = "alloc")]
+    pub fn encrypt(&mut self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        self.aead
+            .encrypt_headers(nonce, &[self.label, associated_data], plaintext)
+    }
+
+    /// Decrypt the given ciphertext, allocating and returning a Vec<u8> for
+    /// the plaintext (see `Aead::decrypt`)
+    #[cfg(feature = "alloc")]
+    pub fn decrypt(
+        &mut self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.aead
+            .decrypt_headers(nonce, &[self.label, associated_data], ciphertext)
+    }
+}
+
+impl<C, M> Clone for DomainSivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    fn clone(&self) -> Self {
+        self.aead.with_domain(self.label)
+    }
+}
+
+impl<C, M> fmt::Debug for DomainSivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    /// Print the label, but never the key
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DomainSivAead")
+            .field("label", &self.label)
+            .finish()
+    }
+}
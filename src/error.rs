@@ -0,0 +1,146 @@
+//! `error.rs`: Error type
+
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::io;
+
+/// Errors returned by Miscreant's encryption and decryption APIs
+///
+/// `AuthenticationFailed` indicates a message was not authentic, which may
+/// be the result of an attack. The other variants indicate invalid usage
+/// (e.g. the wrong key size or a too-small buffer). More variants may be
+/// added in the future.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Ciphertext failed to authenticate (or was malformed)
+    AuthenticationFailed,
+
+    /// Output buffer is too small to hold the result
+    BufferTooSmall,
+
+    /// STREAM nonce counter would overflow
+    CounterOverflow,
+
+    /// Input is not validly encoded (e.g. as base64 or hex)
+    Encoding,
+
+    /// Key is the wrong size for the algorithm
+    InvalidKeySize {
+        /// Expected key size in bytes
+        expected: usize,
+
+        /// Size of the key which was given in bytes
+        got: usize,
+    },
+
+    /// Nonce is the wrong size (for a STREAM or a fixed-size `Nonce`)
+    InvalidNonceSize {
+        /// Expected nonce size in bytes
+        expected: usize,
+
+        /// Size of the nonce which was given in bytes
+        got: usize,
+    },
+
+    /// Tag is the wrong size
+    InvalidTagSize {
+        /// Expected tag size in bytes
+        expected: usize,
+
+        /// Size of the tag which was given in bytes
+        got: usize,
+    },
+
+    /// Message is longer than `MAX_MESSAGE_LEN`
+    MessageTooLong,
+
+    /// Nonce has already been used (detected by `stream::NonceTracker`)
+    NonceReused,
+
+    /// Message is the last one in a STREAM, but was decrypted as a message
+    /// in the middle of it
+    UnexpectedLastBlock,
+
+    /// Algorithm name isn't recognized (by `Algorithm::from_str`)
+    UnknownAlgorithm,
+
+    /// Framed message has an algorithm identifier this build doesn't support
+    UnsupportedAlgorithm {
+        /// Algorithm identifier in the message
+        id: u8,
+    },
+
+    /// Framed message has a format version this build doesn't support
+    UnsupportedVersion {
+        /// Format version in the message
+        version: u8,
+    },
+}
+
+impl From<aes_siv::aead::Error> for Error {
+    fn from(_: aes_siv::aead::Error) -> Error {
+        Error::AuthenticationFailed
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AuthenticationFailed => f.write_str("authentication failed"),
+            Error::BufferTooSmall => f.write_str("buffer too small"),
+            Error::CounterOverflow => f.write_str("STREAM nonce counter overflowed"),
+            Error::Encoding => f.write_str("invalid encoding"),
+            Error::InvalidKeySize { expected, got } => write!(
+                f,
+                "invalid key size (expected {} bytes, got {})",
+                expected, got
+            ),
+            Error::InvalidNonceSize { expected, got } => write!(
+                f,
+                "invalid nonce size (expected {} bytes, got {})",
+                expected, got
+            ),
+            Error::InvalidTagSize { expected, got } => write!(
+                f,
+                "invalid tag size (expected {} bytes, got {})",
+                expected, got
+            ),
+            Error::MessageTooLong => f.write_str("message too long"),
+            Error::NonceReused => f.write_str("nonce reused"),
+            Error::UnexpectedLastBlock => f.write_str("unexpected last block in STREAM"),
+            Error::UnknownAlgorithm => f.write_str("unknown algorithm"),
+            Error::UnsupportedAlgorithm { id } => write!(f, "unsupported algorithm (ID {})", id),
+            Error::UnsupportedVersion { version } => {
+                write!(f, "unsupported message format version ({})", version)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        let kind = match err {
+            Error::AuthenticationFailed
+            | Error::Encoding
+            | Error::UnexpectedLastBlock
+            | Error::UnsupportedAlgorithm { .. }
+            | Error::UnsupportedVersion { .. } => io::ErrorKind::InvalidData,
+            Error::BufferTooSmall
+            | Error::InvalidKeySize { .. }
+            | Error::InvalidNonceSize { .. }
+            | Error::InvalidTagSize { .. }
+            | Error::MessageTooLong
+            | Error::NonceReused
+            | Error::UnknownAlgorithm => io::ErrorKind::InvalidInput,
+            Error::CounterOverflow => io::ErrorKind::Other,
+        };
+
+        io::Error::new(kind, err)
+    }
+}
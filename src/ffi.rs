@@ -1,8 +1,14 @@
 //! `ffi.rs`: Foreign Function Interface providing C ABI
 //!
+//! The `crypto_aead_*` functions below expand the key schedule on every
+//! call. With the `ffi` feature, the `miscreant_*` functions additionally
+//! allow AEAD and STREAM instances to be allocated once and reused (see
+//! `include/miscreant.h`).
+//!
 //! TODO: replace this with cbindgen?
 
-// This is the only code in Miscreant allowed to be unsafe
+// This and the hardware AES backends are the only code in Miscreant
+// allowed to be unsafe
 #![allow(unsafe_code, non_upper_case_globals, unknown_lints)]
 #![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
 
@@ -13,6 +19,12 @@ use core::{ptr, slice};
 #[cfg(feature = "pmac")]
 use crate::{Aes128PmacSivAead, Aes256PmacSivAead};
 
+#[cfg(feature = "ffi")]
+mod handle;
+
+#[cfg(feature = "ffi")]
+pub use self::handle::*;
+
 //
 // AES-128-CMAC-SIV AEAD
 //
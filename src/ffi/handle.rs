@@ -0,0 +1,500 @@
+//! `ffi/handle.rs`: C ABI for AEAD and STREAM instances which are allocated
+//! once (expanding the key schedule) and then reused for many messages.
+//!
+//! Every length is validated before use, and panics are caught and returned
+//! as `MISCREANT_ERR_PANIC` rather than unwinding into the caller. Input and
+//! output buffers must not overlap.
+
+use crate::{
+    stream::{Decryptor, Encryptor},
+    Aead, Aes128SivAead, Aes256SivAead, Error, MAX_MESSAGE_LEN,
+};
+use aes_siv::siv::IV_SIZE;
+use std::{
+    boxed::Box,
+    panic::{self, AssertUnwindSafe},
+    slice,
+};
+use zeroize::Zeroize;
+
+/// Success
+pub const MISCREANT_OK: i32 = 0;
+
+/// The ciphertext was not authentic (`Error::AuthenticationFailed`)
+pub const MISCREANT_ERR_AUTHENTICATION_FAILED: i32 = -1;
+
+/// The output buffer was too small (`Error::BufferTooSmall`)
+pub const MISCREANT_ERR_BUFFER_TOO_SMALL: i32 = -2;
+
+/// The STREAM nonce counter would overflow (`Error::CounterOverflow`)
+pub const MISCREANT_ERR_COUNTER_OVERFLOW: i32 = -3;
+
+/// Malformed encoding (`Error::Encoding`)
+pub const MISCREANT_ERR_ENCODING: i32 = -4;
+
+/// The key was the wrong size (`Error::InvalidKeySize`)
+pub const MISCREANT_ERR_INVALID_KEY_SIZE: i32 = -5;
+
+/// The nonce was the wrong size (`Error::InvalidNonceSize`)
+pub const MISCREANT_ERR_INVALID_NONCE_SIZE: i32 = -6;
+
+/// The tag was the wrong size (`Error::InvalidTagSize`)
+pub const MISCREANT_ERR_INVALID_TAG_SIZE: i32 = -7;
+
+/// The message was the last one in the STREAM (`Error::UnexpectedLastBlock`)
+pub const MISCREANT_ERR_UNEXPECTED_LAST_BLOCK: i32 = -8;
+
+/// A required pointer was null
+pub const MISCREANT_ERR_NULL_POINTER: i32 = -9;
+
+/// The STREAM has already been finished with its last message
+pub const MISCREANT_ERR_FINISHED: i32 = -10;
+
+/// Miscreant panicked (this is a bug)
+pub const MISCREANT_ERR_PANIC: i32 = -11;
+
+/// Unknown algorithm identifier in a framed message
+/// (`Error::UnsupportedAlgorithm`), or unknown algorithm name
+/// (`Error::UnknownAlgorithm`)
+pub const MISCREANT_ERR_UNSUPPORTED_ALGORITHM: i32 = -12;
+
+/// Unknown framed message format version (`Error::UnsupportedVersion`)
+pub const MISCREANT_ERR_UNSUPPORTED_VERSION: i32 = -13;
+
+/// The nonce had already been used (`Error::NonceReused`)
+pub const MISCREANT_ERR_NONCE_REUSED: i32 = -14;
+
+/// The message was longer than `MAX_MESSAGE_LEN` (`Error::MessageTooLong`)
+pub const MISCREANT_ERR_MESSAGE_TOO_LONG: i32 = -15;
+
+/// STREAM encryptor which can be used from C. It's finished (and can no
+/// longer be used) once the last message has been encrypted.
+pub struct StreamEncryptorHandle<A: Aead>(Option<Encryptor<A>>);
+
+/// STREAM decryptor which can be used from C. It's finished (and can no
+/// longer be used) once the last message has been decrypted.
+pub struct StreamDecryptorHandle<A: Aead>(Option<Decryptor<A>>);
+
+macro_rules! aead_handle {
+    ($aead:ty, $new:ident, $encrypt:ident, $decrypt:ident, $free:ident, $name:expr) => {
+        #[doc = "Allocate a new "]
+        #[doc = $name]
+        #[doc = " instance with the given key, storing it in `*out`"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $new(out: *mut *mut $aead, key: *const u8, keylen: usize) -> i32 {
+            guard(|| {
+                let key = input(key, keylen)?;
+                let aead = <$aead>::try_new(key).map_err(status)?;
+                store(out, aead)
+            })
+        }
+
+        #[doc = $name]
+        #[doc = ": authenticated encryption. `*ctlen_p` is the size of the `ct` "]
+        #[doc = "buffer, and is set to the length of the ciphertext (which is "]
+        #[doc = "`msglen + 16`)."]
+        #[no_mangle]
+        pub unsafe extern "C" fn $encrypt(
+            aead: *mut $aead,
+            ct: *mut u8,
+            ctlen_p: *mut usize,
+            msg: *const u8,
+            msglen: usize,
+            nonce: *const u8,
+            noncelen: usize,
+            ad: *const u8,
+            adlen: usize,
+        ) -> i32 {
+            guard(|| {
+                let aead = aead.as_mut().ok_or(MISCREANT_ERR_NULL_POINTER)?;
+                ciphertext_len(msglen)?;
+                let msg = input(msg, msglen)?;
+                let nonce = input(nonce, noncelen)?;
+                let ad = input(ad, adlen)?;
+
+                write_with(ct, ctlen_p, msg, |buffer| {
+                    aead.encrypt_in_place(nonce, ad, buffer);
+                    Ok(())
+                })
+            })
+        }
+
+        #[doc = $name]
+        #[doc = ": authenticated decryption. `*msglen_p` is the size of the "]
+        #[doc = "`msg` buffer, and is set to the length of the plaintext (which "]
+        #[doc = "is `ctlen - 16`)."]
+        #[no_mangle]
+        pub unsafe extern "C" fn $decrypt(
+            aead: *mut $aead,
+            msg: *mut u8,
+            msglen_p: *mut usize,
+            ct: *const u8,
+            ctlen: usize,
+            nonce: *const u8,
+            noncelen: usize,
+            ad: *const u8,
+            adlen: usize,
+        ) -> i32 {
+            guard(|| {
+                let aead = aead.as_mut().ok_or(MISCREANT_ERR_NULL_POINTER)?;
+                let ct = input(ct, ctlen)?;
+                let nonce = input(nonce, noncelen)?;
+                let ad = input(ad, adlen)?;
+
+                let mut plaintext = aead.decrypt(nonce, ad, ct).map_err(status)?;
+                let result = copy_out(msg, msglen_p, &plaintext);
+                plaintext.zeroize();
+                result
+            })
+        }
+
+        #[doc = "Free an "]
+        #[doc = $name]
+        #[doc = " instance, zeroizing its key (does nothing if it's null)"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $free(aead: *mut $aead) {
+            free(aead)
+        }
+    };
+}
+
+macro_rules! stream_handle {
+    (
+        $aead:ty,
+        $encryptor_new:ident,
+        $encrypt_next:ident,
+        $encrypt_last:ident,
+        $encryptor_free:ident,
+        $decryptor_new:ident,
+        $decrypt_next:ident,
+        $decrypt_last:ident,
+        $decryptor_free:ident,
+        $name:expr
+    ) => {
+        #[doc = "Allocate a new "]
+        #[doc = $name]
+        #[doc = " STREAM encryptor with the given key and 8-byte nonce, "]
+        #[doc = "storing it in `*out`"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $encryptor_new(
+            out: *mut *mut StreamEncryptorHandle<$aead>,
+            key: *const u8,
+            keylen: usize,
+            nonce: *const u8,
+            noncelen: usize,
+        ) -> i32 {
+            guard(|| {
+                let encryptor = Encryptor::try_new(input(key, keylen)?, input(nonce, noncelen)?)
+                    .map_err(status)?;
+                store(out, StreamEncryptorHandle(Some(encryptor)))
+            })
+        }
+
+        #[doc = $name]
+        #[doc = " STREAM: encrypt the next message"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $encrypt_next(
+            encryptor: *mut StreamEncryptorHandle<$aead>,
+            ct: *mut u8,
+            ctlen_p: *mut usize,
+            msg: *const u8,
+            msglen: usize,
+            ad: *const u8,
+            adlen: usize,
+        ) -> i32 {
+            guard(|| {
+                let encryptor = encryptor.as_mut().ok_or(MISCREANT_ERR_NULL_POINTER)?;
+                let encryptor = encryptor.0.as_mut().ok_or(MISCREANT_ERR_FINISHED)?;
+                ciphertext_len(msglen)?;
+                let msg = input(msg, msglen)?;
+                let ad = input(ad, adlen)?;
+
+                write_with(ct, ctlen_p, msg, |buffer| {
+                    encryptor.try_encrypt_next_in_place(ad, buffer)
+                })
+            })
+        }
+
+        #[doc = $name]
+        #[doc = " STREAM: encrypt the last message, finishing the encryptor"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $encrypt_last(
+            encryptor: *mut StreamEncryptorHandle<$aead>,
+            ct: *mut u8,
+            ctlen_p: *mut usize,
+            msg: *const u8,
+            msglen: usize,
+            ad: *const u8,
+            adlen: usize,
+        ) -> i32 {
+            guard(|| {
+                let handle = encryptor.as_mut().ok_or(MISCREANT_ERR_NULL_POINTER)?;
+
+                // Check the message and output buffer before the encryptor
+                // is used up
+                let len = ciphertext_len(msglen)?;
+                let msg = input(msg, msglen)?;
+                let ad = input(ad, adlen)?;
+                output(ct, ctlen_p, len)?;
+
+                let encryptor = handle.0.take().ok_or(MISCREANT_ERR_FINISHED)?;
+
+                write_with(ct, ctlen_p, msg, |buffer| {
+                    encryptor.encrypt_last_in_place(ad, buffer);
+                    Ok(())
+                })
+            })
+        }
+
+        #[doc = "Free an "]
+        #[doc = $name]
+        #[doc = " STREAM encryptor (does nothing if it's null)"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $encryptor_free(encryptor: *mut StreamEncryptorHandle<$aead>) {
+            free(encryptor)
+        }
+
+        #[doc = "Allocate a new "]
+        #[doc = $name]
+        #[doc = " STREAM decryptor with the given key and 8-byte nonce, "]
+        #[doc = "storing it in `*out`"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $decryptor_new(
+            out: *mut *mut StreamDecryptorHandle<$aead>,
+            key: *const u8,
+            keylen: usize,
+            nonce: *const u8,
+            noncelen: usize,
+        ) -> i32 {
+            guard(|| {
+                let decryptor = Decryptor::try_new(input(key, keylen)?, input(nonce, noncelen)?)
+                    .map_err(status)?;
+                store(out, StreamDecryptorHandle(Some(decryptor)))
+            })
+        }
+
+        #[doc = $name]
+        #[doc = " STREAM: decrypt the next message, returning "]
+        #[doc = "`MISCREANT_ERR_UNEXPECTED_LAST_BLOCK` if it's the last one"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $decrypt_next(
+            decryptor: *mut StreamDecryptorHandle<$aead>,
+            msg: *mut u8,
+            msglen_p: *mut usize,
+            ct: *const u8,
+            ctlen: usize,
+            ad: *const u8,
+            adlen: usize,
+        ) -> i32 {
+            guard(|| {
+                let decryptor = decryptor.as_mut().ok_or(MISCREANT_ERR_NULL_POINTER)?;
+                let decryptor = decryptor.0.as_mut().ok_or(MISCREANT_ERR_FINISHED)?;
+                let ct = input(ct, ctlen)?;
+                let ad = input(ad, adlen)?;
+
+                // Check the output buffer before the message is used up, so
+                // the caller can retry it with a larger buffer
+                output(msg, msglen_p, ct.len().saturating_sub(IV_SIZE))?;
+
+                let mut plaintext = decryptor.decrypt_next(ad, ct).map_err(status)?;
+                let result = copy_out(msg, msglen_p, &plaintext);
+                plaintext.zeroize();
+                result
+            })
+        }
+
+        #[doc = $name]
+        #[doc = " STREAM: decrypt the last message, finishing the decryptor"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $decrypt_last(
+            decryptor: *mut StreamDecryptorHandle<$aead>,
+            msg: *mut u8,
+            msglen_p: *mut usize,
+            ct: *const u8,
+            ctlen: usize,
+            ad: *const u8,
+            adlen: usize,
+        ) -> i32 {
+            guard(|| {
+                let handle = decryptor.as_mut().ok_or(MISCREANT_ERR_NULL_POINTER)?;
+                let ct = input(ct, ctlen)?;
+                let ad = input(ad, adlen)?;
+
+                let len = ct.len().saturating_sub(IV_SIZE);
+                output(msg, msglen_p, len)?;
+
+                let decryptor = handle.0.take().ok_or(MISCREANT_ERR_FINISHED)?;
+                let mut plaintext = decryptor.decrypt_last(ad, ct).map_err(status)?;
+                let result = copy_out(msg, msglen_p, &plaintext);
+                plaintext.zeroize();
+                result
+            })
+        }
+
+        #[doc = "Free an "]
+        #[doc = $name]
+        #[doc = " STREAM decryptor (does nothing if it's null)"]
+        #[no_mangle]
+        pub unsafe extern "C" fn $decryptor_free(decryptor: *mut StreamDecryptorHandle<$aead>) {
+            free(decryptor)
+        }
+    };
+}
+
+aead_handle!(
+    Aes128SivAead,
+    miscreant_aes128siv_new,
+    miscreant_aes128siv_encrypt,
+    miscreant_aes128siv_decrypt,
+    miscreant_aes128siv_free,
+    "AES-128-SIV"
+);
+
+aead_handle!(
+    Aes256SivAead,
+    miscreant_aes256siv_new,
+    miscreant_aes256siv_encrypt,
+    miscreant_aes256siv_decrypt,
+    miscreant_aes256siv_free,
+    "AES-256-SIV"
+);
+
+stream_handle!(
+    Aes128SivAead,
+    miscreant_aes128siv_stream_encryptor_new,
+    miscreant_aes128siv_stream_encrypt_next,
+    miscreant_aes128siv_stream_encrypt_last,
+    miscreant_aes128siv_stream_encryptor_free,
+    miscreant_aes128siv_stream_decryptor_new,
+    miscreant_aes128siv_stream_decrypt_next,
+    miscreant_aes128siv_stream_decrypt_last,
+    miscreant_aes128siv_stream_decryptor_free,
+    "AES-128-SIV"
+);
+
+stream_handle!(
+    Aes256SivAead,
+    miscreant_aes256siv_stream_encryptor_new,
+    miscreant_aes256siv_stream_encrypt_next,
+    miscreant_aes256siv_stream_encrypt_last,
+    miscreant_aes256siv_stream_encryptor_free,
+    miscreant_aes256siv_stream_decryptor_new,
+    miscreant_aes256siv_stream_decrypt_next,
+    miscreant_aes256siv_stream_decrypt_last,
+    miscreant_aes256siv_stream_decryptor_free,
+    "AES-256-SIV"
+);
+
+/// Status code for the given error
+fn status(err: Error) -> i32 {
+    match err {
+        Error::AuthenticationFailed => MISCREANT_ERR_AUTHENTICATION_FAILED,
+        Error::BufferTooSmall => MISCREANT_ERR_BUFFER_TOO_SMALL,
+        Error::CounterOverflow => MISCREANT_ERR_COUNTER_OVERFLOW,
+        Error::Encoding => MISCREANT_ERR_ENCODING,
+        Error::InvalidKeySize { .. } => MISCREANT_ERR_INVALID_KEY_SIZE,
+        Error::InvalidNonceSize { .. } => MISCREANT_ERR_INVALID_NONCE_SIZE,
+        Error::InvalidTagSize { .. } => MISCREANT_ERR_INVALID_TAG_SIZE,
+        Error::MessageTooLong => MISCREANT_ERR_MESSAGE_TOO_LONG,
+        Error::NonceReused => MISCREANT_ERR_NONCE_REUSED,
+        Error::UnexpectedLastBlock => MISCREANT_ERR_UNEXPECTED_LAST_BLOCK,
+        Error::UnknownAlgorithm | Error::UnsupportedAlgorithm { .. } => {
+            MISCREANT_ERR_UNSUPPORTED_ALGORITHM
+        }
+        Error::UnsupportedVersion { .. } => MISCREANT_ERR_UNSUPPORTED_VERSION,
+    }
+}
+
+/// Run the body of an FFI function, converting its result (or a panic) to a
+/// status code
+fn guard(body: impl FnOnce() -> Result<(), i32>) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => MISCREANT_OK,
+        Ok(Err(status)) => status,
+        Err(_) => MISCREANT_ERR_PANIC,
+    }
+}
+
+/// Borrow an input buffer, which may only be null if it's empty
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8], i32> {
+    if len == 0 {
+        Ok(&[])
+    } else if data.is_null() {
+        Err(MISCREANT_ERR_NULL_POINTER)
+    } else {
+        Ok(slice::from_raw_parts(data, len))
+    }
+}
+
+/// Borrow the first `len` bytes of an output buffer whose size is `*len_p`.
+///
+/// Sets `*len_p` to the required size and returns
+/// `MISCREANT_ERR_BUFFER_TOO_SMALL` if the buffer is smaller than that.
+unsafe fn output<'a>(data: *mut u8, len_p: *mut usize, len: usize) -> Result<&'a mut [u8], i32> {
+    let capacity = len_p.as_mut().ok_or(MISCREANT_ERR_NULL_POINTER)?;
+
+    if *capacity < len {
+        *capacity = len;
+        return Err(MISCREANT_ERR_BUFFER_TOO_SMALL);
+    }
+
+    if len == 0 {
+        Ok(&mut [])
+    } else if data.is_null() {
+        Err(MISCREANT_ERR_NULL_POINTER)
+    } else {
+        Ok(slice::from_raw_parts_mut(data, len))
+    }
+}
+
+/// Copy the plaintext into the end of the output buffer after room for the
+/// tag, and encrypt it in-place with `encrypt`
+unsafe fn write_with(
+    out: *mut u8,
+    len_p: *mut usize,
+    plaintext: &[u8],
+    encrypt: impl FnOnce(&mut [u8]) -> Result<(), Error>,
+) -> Result<(), i32> {
+    let len = ciphertext_len(plaintext.len())?;
+    let buffer = output(out, len_p, len)?;
+
+    buffer[IV_SIZE..].copy_from_slice(plaintext);
+    encrypt(buffer).map_err(status)?;
+    *len_p = len;
+    Ok(())
+}
+
+/// Length of the ciphertext of a `msglen`-byte message, returning
+/// `MISCREANT_ERR_MESSAGE_TOO_LONG` if the message is longer than
+/// `MAX_MESSAGE_LEN` (which encrypting would otherwise panic on)
+fn ciphertext_len(msglen: usize) -> Result<usize, i32> {
+    if msglen as u64 > MAX_MESSAGE_LEN {
+        return Err(MISCREANT_ERR_MESSAGE_TOO_LONG);
+    }
+
+    msglen
+        .checked_add(IV_SIZE)
+        .ok_or(MISCREANT_ERR_MESSAGE_TOO_LONG)
+}
+
+/// Copy a decrypted plaintext into the output buffer
+unsafe fn copy_out(out: *mut u8, len_p: *mut usize, plaintext: &[u8]) -> Result<(), i32> {
+    output(out, len_p, plaintext.len())?.copy_from_slice(plaintext);
+    *len_p = plaintext.len();
+    Ok(())
+}
+
+/// Move a newly created instance to the heap and store it in `*out`
+unsafe fn store<T>(out: *mut *mut T, value: T) -> Result<(), i32> {
+    let out = out.as_mut().ok_or(MISCREANT_ERR_NULL_POINTER)?;
+    *out = Box::into_raw(Box::new(value));
+    Ok(())
+}
+
+/// Drop an instance created with `store` (zeroizing its keys)
+unsafe fn free<T>(value: *mut T) {
+    if !value.is_null() {
+        // Don't let a panic in a destructor unwind into C
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(value))));
+    }
+}
@@ -0,0 +1,145 @@
+//! `framed.rs`: Self-describing message format identifying the algorithm
+//! which encrypted it
+//!
+//! A framed message consists of:
+//!
+//! - a 1-byte format version (currently `FRAME_VERSION`)
+//! - a 1-byte algorithm identifier (see `FramedAead::ALGORITHM_ID`)
+//! - a 1-byte nonce length, followed by the nonce
+//! - the SIV tag followed by the ciphertext
+//!
+//! so a reader who knows the key can decrypt it with `decrypt_framed`
+//! without knowing beforehand which algorithm was used. The framing is
+//! not authenticated separately: a message altered to name a different
+//! algorithm fails to decrypt (or its key is the wrong size).
+
+use crate::{Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead, Error};
+use alloc::vec::Vec;
+
+#[cfg(feature = "pmac")]
+use crate::{Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead};
+
+/// Version of the framed message format produced by `encrypt_framed`
+pub const FRAME_VERSION: u8 = 1;
+
+/// Size of the header preceding the nonce in a framed message
+const HEADER_SIZE: usize = 3;
+
+/// AEAD algorithms which can produce framed messages
+pub trait FramedAead: Aead {
+    /// Identifier for this algorithm in framed messages
+    const ALGORITHM_ID: u8;
+
+    /// Encrypt the given plaintext, returning a framed message containing
+    /// the algorithm identifier and the nonce along with the ciphertext
+    ///
+    /// # Panics
+    ///
+    /// Panics if the nonce is longer than 255 bytes.
+    fn encrypt_framed(&mut self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        assert!(nonce.len() <= 0xff, "nonce too long for framed message");
+
+        let mut framed =
+            Vec::with_capacity(HEADER_SIZE + nonce.len() + Self::TAG_SIZE + plaintext.len());
+        framed.extend_from_slice(&[FRAME_VERSION, Self::ALGORITHM_ID, nonce.len() as u8]);
+        framed.extend_from_slice(nonce);
+
+        let offset = framed.len();
+        framed.resize(offset + Self::TAG_SIZE, 0);
+        framed.extend_from_slice(plaintext);
+        self.encrypt_in_place(nonce, associated_data, &mut framed[offset..]);
+        framed
+    }
+}
+
+impl FramedAead for Aes128SivAead {
+    const ALGORITHM_ID: u8 = 1;
+}
+
+impl FramedAead for Aes192SivAead {
+    const ALGORITHM_ID: u8 = 2;
+}
+
+impl FramedAead for Aes256SivAead {
+    const ALGORITHM_ID: u8 = 3;
+}
+
+#[cfg(feature = "pmac")]
+impl FramedAead for Aes128PmacSivAead {
+    const ALGORITHM_ID: u8 = 4;
+}
+
+#[cfg(feature = "pmac")]
+impl FramedAead for Aes192PmacSivAead {
+    const ALGORITHM_ID: u8 = 5;
+}
+
+#[cfg(feature = "pmac")]
+impl FramedAead for Aes256PmacSivAead {
+    const ALGORITHM_ID: u8 = 6;
+}
+
+/// Decrypt a framed message produced by `FramedAead::encrypt_framed`, using
+/// the algorithm it identifies, and allocating and returning a Vec<u8> for
+/// the plaintext.
+///
+/// Returns `Error::UnsupportedVersion` or `Error::UnsupportedAlgorithm` if
+/// the message has a version or algorithm identifier which isn't supported
+/// (including PMAC-based algorithms when the `pmac` feature is disabled),
+/// or `Error::InvalidKeySize` if the key is the wrong size for its
+/// algorithm.
+pub fn decrypt_framed(key: &[u8], associated_data: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
+    if framed.len() < HEADER_SIZE {
+        return Err(Error::AuthenticationFailed);
+    }
+
+    if framed[0] != FRAME_VERSION {
+        return Err(Error::UnsupportedVersion { version: framed[0] });
+    }
+
+    let id = framed[1];
+    let nonce_len = framed[2] as usize;
+
+    if framed.len() < HEADER_SIZE + nonce_len {
+        return Err(Error::AuthenticationFailed);
+    }
+
+    let (nonce, ciphertext) = framed[HEADER_SIZE..].split_at(nonce_len);
+
+    match id {
+        Aes128SivAead::ALGORITHM_ID => {
+            open::<Aes128SivAead>(key, nonce, associated_data, ciphertext)
+        }
+        Aes192SivAead::ALGORITHM_ID => {
+            open::<Aes192SivAead>(key, nonce, associated_data, ciphertext)
+        }
+        Aes256SivAead::ALGORITHM_ID => {
+            open::<Aes256SivAead>(key, nonce, associated_data, ciphertext)
+        }
+        #[cfg(feature = "pmac")]
+        Aes128PmacSivAead::ALGORITHM_ID => {
+            open::<Aes128PmacSivAead>(key, nonce, associated_data, ciphertext)
+        }
+        #[cfg(feature = "pmac")]
+        Aes192PmacSivAead::ALGORITHM_ID => {
+            open::<Aes192PmacSivAead>(key, nonce, associated_data, ciphertext)
+        }
+        #[cfg(feature = "pmac")]
+        Aes256PmacSivAead::ALGORITHM_ID => {
+            open::<Aes256PmacSivAead>(key, nonce, associated_data, ciphertext)
+        }
+        _ => Err(Error::UnsupportedAlgorithm { id }),
+    }
+}
+
+fn open<A: Aead>(
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    A::try_new(key)?.decrypt(nonce, associated_data, ciphertext)
+}
@@ -0,0 +1,356 @@
+//! `incremental.rs`: Incremental AES-SIV encryption of a single message
+//! which is too large to be held in memory.
+//!
+//! AES-SIV is a two-pass construction: the tag (synthetic IV) is a MAC over
+//! the entire message computed with S2V, and the ciphertext is produced by
+//! a second CTR mode pass which is keyed by that tag. `SivEncryptor` performs
+//! the first (S2V) pass incrementally, after which the plaintext must be
+//! supplied again for the CTR pass (e.g. by re-reading it from a file).
+//!
+//! Unlike STREAM, the result is a single ordinary AES-SIV ciphertext: the
+//! inputs given with `update_ad` correspond to the headers of `siv::Siv`, so
+//! `SivAead::encrypt(nonce, ad, plaintext)` is equivalent to calling
+//! `update_ad(ad)`, then `update_ad(nonce)`, then `update_plaintext`.
+//!
+//! ## Decryption
+//!
+//! Decryption is unavoidably two-pass as well: the ciphertext must first be
+//! decrypted in full using the `Keystream` returned by
+//! `SivEncryptor::keystream` for the tag, and the S2V pass then run over the
+//! resulting plaintext with that `SivEncryptor` and checked with
+//! `SivEncryptor::verify`. The plaintext is unauthenticated until this check
+//! succeeds, so it must not be used (or released to anything else) before
+//! then, and must be discarded if the check fails.
+//! Messages which can't be held in memory while they are decrypted should
+//! be encrypted with STREAM instead.
+
+use crate::{
+    backend::{Aes128, Aes192, Aes256},
+    generic_array::{
+        typenum::{Unsigned, U16},
+        GenericArray,
+    },
+    Error, Tag,
+};
+use aes_siv::siv::{IV_SIZE, MAX_HEADERS};
+use cmac::Cmac;
+use core::fmt;
+use crypto_mac::Mac;
+use ctr::Ctr128;
+use stream_cipher::{NewStreamCipher, SyncStreamCipher};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+#[cfg(feature = "pmac")]
+use pmac_crate::Pmac;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+/// Incrementally computes the S2V pass of AES-SIV encryption over a single
+/// large message, after which `finalize` performs the CTR pass.
+pub struct SivEncryptor<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+{
+    mac: M,
+    ctr_key: GenericArray<u8, C::KeySize>,
+    state: GenericArray<u8, U16>,
+    headers: usize,
+    buffer: [u8; IV_SIZE],
+    buffered: usize,
+    plaintext_len: u64,
+    plaintext_started: bool,
+}
+
+/// Incremental AES-CMAC-SIV with a 256-bit key
+pub type Aes128SivEncryptor = SivEncryptor<Ctr128<Aes128>, Cmac<Aes128>>;
+
+/// Incremental AES-CMAC-SIV with a 384-bit key
+pub type Aes192SivEncryptor = SivEncryptor<Ctr128<Aes192>, Cmac<Aes192>>;
+
+/// Incremental AES-CMAC-SIV with a 512-bit key
+pub type Aes256SivEncryptor = SivEncryptor<Ctr128<Aes256>, Cmac<Aes256>>;
+
+/// Incremental AES-PMAC-SIV with a 256-bit key
+#[cfg(feature = "pmac")]
+pub type Aes128PmacSivEncryptor = SivEncryptor<Ctr128<Aes128>, Pmac<Aes128>>;
+
+/// Incremental AES-PMAC-SIV with a 384-bit key
+#[cfg(feature = "pmac")]
+pub type Aes192PmacSivEncryptor = SivEncryptor<Ctr128<Aes192>, Pmac<Aes192>>;
+
+/// Incremental AES-PMAC-SIV with a 512-bit key
+#[cfg(feature = "pmac")]
+pub type Aes256PmacSivEncryptor = SivEncryptor<Ctr128<Aes256>, Pmac<Aes256>>;
+
+impl<C, M> SivEncryptor<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+{
+    /// Create a new incremental encryptor with the given (double length)
+    /// AES-SIV key
+    ///
+    /// Panics if the key is the wrong length
+    pub fn new(key: &[u8]) -> Self {
+        let (mac_key, ctr_key) = split_key::<C>(key);
+        let mut mac = M::new_varkey(mac_key).expect("invalid MAC key");
+        mac.input(&[0u8; IV_SIZE]);
+        let state = mac.result_reset().code();
+
+        Self {
+            mac,
+            ctr_key: GenericArray::clone_from_slice(ctr_key),
+            state,
+            headers: 0,
+            buffer: [0u8; IV_SIZE],
+            buffered: 0,
+            plaintext_len: 0,
+            plaintext_started: false,
+        }
+    }
+
+    /// Authenticate the given associated data (or nonce) as the next header.
+    ///
+    /// Each call is a separate input to S2V, so the order and boundaries of
+    /// the headers are authenticated along with their contents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after `update_plaintext`.
+    /// Panics if more than `siv::MAX_HEADERS` headers are given.
+    pub fn update_ad(&mut self, associated_data: &[u8]) {
+        assert!(
+            !self.plaintext_started,
+            "associated data must be given before the plaintext"
+        );
+        assert!(self.headers < MAX_HEADERS, "too many associated data items");
+
+        self.mac.input(associated_data);
+        let code = self.mac.result_reset().code();
+        dbl(&mut self.state);
+        xor(&mut self.state, &code);
+        self.headers += 1;
+    }
+
+    /// Authenticate the next part of the plaintext.
+    ///
+    /// The final 16 bytes of the plaintext given so far are held back, as
+    /// S2V treats the end of the message specially.
+    pub fn update_plaintext(&mut self, plaintext: &[u8]) {
+        self.plaintext_started = true;
+        self.plaintext_len += plaintext.len() as u64;
+
+        let total = self.buffered + plaintext.len();
+
+        if total <= IV_SIZE {
+            self.buffer[self.buffered..total].copy_from_slice(plaintext);
+            self.buffered = total;
+            return;
+        }
+
+        let mac_len = total - IV_SIZE;
+        let from_buffer = mac_len.min(self.buffered);
+        let from_plaintext = mac_len - from_buffer;
+        self.mac.input(&self.buffer[..from_buffer]);
+        self.mac.input(&plaintext[..from_plaintext]);
+
+        let kept = self.buffered - from_buffer;
+        self.buffer.copy_within(from_buffer..self.buffered, 0);
+        self.buffer[kept..].copy_from_slice(&plaintext[from_plaintext..]);
+        self.buffered = IV_SIZE;
+    }
+
+    /// Total length of the plaintext given so far in bytes
+    pub fn plaintext_len(&self) -> u64 {
+        self.plaintext_len
+    }
+
+    /// Create the keystream for the message with the given tag, which is
+    /// used for the first pass of decryption (see the module documentation)
+    pub fn keystream(&self, tag: Tag) -> Keystream<C> {
+        Keystream::from_key(&self.ctr_key, tag)
+    }
+
+    /// Finish the S2V pass, returning a `Keystream` which performs the CTR
+    /// pass (and contains the tag)
+    pub fn finalize(mut self) -> Keystream<C> {
+        let tag = self.tag();
+        Keystream::from_key(&self.ctr_key, tag)
+    }
+
+    /// Finish the S2V pass, then encrypt the plaintext read from the given
+    /// reader (which must be identical to the plaintext given to
+    /// `update_plaintext`), writing the tag followed by the ciphertext to
+    /// the given writer.
+    ///
+    /// Returns an `io::ErrorKind::InvalidData` error if the reader yields a
+    /// different amount of plaintext than was authenticated. A plaintext
+    /// with the same length but different contents can't be detected, and
+    /// results in a ciphertext which fails to decrypt.
+    #[cfg(feature = "std")]
+    pub fn finalize_into<R: Read, W: Write>(self, mut plaintext: R, mut out: W) -> io::Result<Tag> {
+        let expected_len = self.plaintext_len;
+        let mut keystream = self.finalize();
+        let tag = keystream.tag();
+        out.write_all(tag.as_ref())?;
+
+        let mut buffer = [0u8; 4096];
+        let mut len = 0u64;
+
+        loop {
+            let n = match plaintext.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            len += n as u64;
+
+            if len > expected_len {
+                break;
+            }
+
+            keystream.apply_keystream(&mut buffer[..n]);
+            out.write_all(&buffer[..n])?;
+        }
+
+        buffer.zeroize();
+
+        if len != expected_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "plaintext changed between passes",
+            ));
+        }
+
+        Ok(tag)
+    }
+
+    /// Finish the S2V pass, checking the result against the given tag in
+    /// constant time. Used to authenticate a message after the decryption
+    /// pass (see the module documentation).
+    pub fn verify(mut self, tag: &Tag) -> Result<(), Error> {
+        if bool::from(self.tag().ct_eq(tag)) {
+            Ok(())
+        } else {
+            Err(Error::AuthenticationFailed)
+        }
+    }
+
+    /// Compute the S2V output for the headers and plaintext given so far
+    fn tag(&mut self) -> Tag {
+        if self.buffered == IV_SIZE {
+            xor(&mut self.buffer, &self.state);
+        } else {
+            // Messages shorter than a block are padded
+            dbl(&mut self.state);
+            self.buffer[self.buffered] = 0x80;
+            self.buffer[self.buffered + 1..].zeroize();
+            xor(&mut self.buffer, &self.state);
+        }
+
+        self.mac.input(&self.buffer);
+        let mut tag = [0u8; IV_SIZE];
+        tag.copy_from_slice(&self.mac.result_reset().code());
+        Tag::new(tag)
+    }
+}
+
+impl<C, M> fmt::Debug for SivEncryptor<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SivEncryptor")
+            .field("headers", &self.headers)
+            .field("plaintext_len", &self.plaintext_len)
+            .finish()
+    }
+}
+
+impl<C, M> Drop for SivEncryptor<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+{
+    fn drop(&mut self) {
+        self.ctr_key.zeroize();
+        self.state.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+/// The CTR pass of AES-SIV, keyed by the tag (synthetic IV) of a message.
+///
+/// Since CTR mode is symmetric, this both encrypts and decrypts.
+pub struct Keystream<C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher> {
+    cipher: C,
+    tag: Tag,
+}
+
+impl<C> Keystream<C>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+{
+    /// Create the keystream from the CTR key
+    pub(crate) fn from_key(ctr_key: &GenericArray<u8, C::KeySize>, tag: Tag) -> Self {
+        // Clear the 31st and 63rd bits (from the right) of the IV, as
+        // described in RFC 5297 Section 2.6
+        let mut iv = GenericArray::clone_from_slice(tag.as_ref());
+        iv[8] &= 0x7f;
+        iv[12] &= 0x7f;
+
+        Self {
+            cipher: C::new(ctr_key, &iv),
+            tag,
+        }
+    }
+
+    /// Tag (synthetic IV) of the message
+    pub fn tag(&self) -> Tag {
+        self.tag
+    }
+
+    /// Encrypt or decrypt the next part of the message in-place
+    pub fn apply_keystream(&mut self, buffer: &mut [u8]) {
+        self.cipher.apply_keystream(buffer);
+    }
+}
+
+impl<C> fmt::Debug for Keystream<C>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Keystream { .. }")
+    }
+}
+
+/// Split an AES-SIV key into its MAC and CTR halves
+fn split_key<C: NewStreamCipher>(key: &[u8]) -> (&[u8], &[u8]) {
+    let half = C::KeySize::to_usize();
+    assert_eq!(key.len(), half * 2, "invalid key size");
+    key.split_at(half)
+}
+
+/// Multiply a block by x in GF(2^128)
+pub(crate) fn dbl(block: &mut GenericArray<u8, U16>) {
+    let carry = block[0] >> 7;
+
+    for i in 0..IV_SIZE - 1 {
+        block[i] = block[i] << 1 | block[i + 1] >> 7;
+    }
+
+    block[IV_SIZE - 1] = block[IV_SIZE - 1] << 1 ^ (carry * 0x87);
+}
+
+/// XOR `other` into `block`
+pub(crate) fn xor(block: &mut [u8], other: &[u8]) {
+    for (a, b) in block.iter_mut().zip(other) {
+        *a ^= b;
+    }
+}
@@ -0,0 +1,283 @@
+//! `io.rs`: `std::io` adapters for encrypting and decrypting STREAMs
+//!
+//! Each STREAM message is framed by a 32-bit big endian length prefix,
+//! followed by the message ciphertext (including its tag).
+
+use crate::{
+    generic_array::typenum::Unsigned,
+    stream::{Counter, Decryptor, Encryptor, NonceEncoder32},
+    Aead,
+};
+use core::convert::TryFrom;
+use std::{
+    io::{self, Read, Write},
+    vec::Vec,
+};
+
+/// Default size of the plaintext of each message in the STREAM (64 KiB)
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Size of the length prefix on each message in the STREAM
+pub(crate) const LENGTH_PREFIX_SIZE: usize = 4;
+
+/// Encrypts data written to it as a STREAM, splitting it into fixed-size
+/// messages (chunks) and writing their ciphertexts to the inner writer.
+///
+/// At most one message is buffered at a time: once a message is full, it
+/// is written to the inner writer before any more data is accepted. If the
+/// inner writer fails (e.g. with `io::ErrorKind::WouldBlock`), the next
+/// call resumes writing the encrypted message where it left off.
+///
+/// `finish` must be called once all data has been written in order to
+/// encrypt and write the last message. A STREAM which is dropped without
+/// calling `finish` will be rejected as truncated when decrypting.
+pub struct StreamWriter<A: Aead, W: Write, C: Counter = NonceEncoder32> {
+    encryptor: Option<Encryptor<A, C>>,
+    writer: W,
+    buffer: Vec<u8>,
+    written: Option<usize>,
+    chunk_size: usize,
+}
+
+impl<A: Aead, W: Write, C: Counter> StreamWriter<A, W, C> {
+    /// Create a new STREAM writer using the default chunk size
+    pub fn new(encryptor: Encryptor<A, C>, writer: W) -> Self {
+        Self::with_chunk_size(encryptor, writer, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new STREAM writer which encrypts plaintext in messages of
+    /// the given size (in bytes).
+    ///
+    /// Panics if the chunk size is zero or too large for the length prefix.
+    pub fn with_chunk_size(encryptor: Encryptor<A, C>, writer: W, chunk_size: usize) -> Self {
+        check_chunk_size(chunk_size, A::TagSize::to_usize());
+
+        let header_size = LENGTH_PREFIX_SIZE + A::TagSize::to_usize();
+        let mut buffer = Vec::with_capacity(header_size + chunk_size);
+        buffer.resize(header_size, 0);
+
+        Self {
+            encryptor: Some(encryptor),
+            writer,
+            buffer,
+            written: None,
+            chunk_size,
+        }
+    }
+
+    /// Borrow the inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Encrypt and write the last message in the STREAM (which may be
+    /// empty), returning the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.drain()?;
+        self.seal(true)?;
+        self.drain()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Length of the plaintext currently buffered
+    fn buffered(&self) -> usize {
+        self.buffer.len() - LENGTH_PREFIX_SIZE - A::TagSize::to_usize()
+    }
+
+    /// Encrypt the buffered message, preparing it to be written
+    fn seal(&mut self, last: bool) -> io::Result<()> {
+        let message = &mut self.buffer[LENGTH_PREFIX_SIZE..];
+
+        if last {
+            self.encryptor
+                .take()
+                .expect("STREAM writer already finished")
+                .encrypt_last_in_place(b"", message);
+        } else {
+            self.encryptor
+                .as_mut()
+                .expect("STREAM writer already finished")
+                .try_encrypt_next_in_place(b"", message)?;
+        }
+
+        let len = (self.buffer.len() - LENGTH_PREFIX_SIZE) as u32;
+        self.buffer[..LENGTH_PREFIX_SIZE].copy_from_slice(&len.to_be_bytes());
+        self.written = Some(0);
+        Ok(())
+    }
+
+    /// Write any encrypted message to the inner writer
+    fn drain(&mut self) -> io::Result<()> {
+        while let Some(written) = self.written {
+            if written == self.buffer.len() {
+                self.buffer
+                    .truncate(LENGTH_PREFIX_SIZE + A::TagSize::to_usize());
+                self.written = None;
+                break;
+            }
+
+            match self.writer.write(&self.buffer[written..]) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => self.written = Some(written + n),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<A: Aead, W: Write, C: Counter> Write for StreamWriter<A, W, C> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.drain()?;
+
+        let len = (self.chunk_size - self.buffered()).min(data.len());
+        self.buffer.extend_from_slice(&data[..len]);
+
+        if self.buffered() == self.chunk_size {
+            self.seal(false)?;
+        }
+
+        Ok(len)
+    }
+
+    /// Write any full messages and flush the inner writer.
+    ///
+    /// Buffered plaintext is not written until a full message has been
+    /// accumulated, or `finish` is called.
+    fn flush(&mut self) -> io::Result<()> {
+        self.drain()?;
+        self.writer.flush()
+    }
+}
+
+/// Decrypts a STREAM read from the inner reader, authenticating each
+/// message before serving its plaintext.
+///
+/// Reads return `Ok(0)` only after the last message in the STREAM has been
+/// authenticated. If the inner reader ends before then, the STREAM has been
+/// truncated and an `io::ErrorKind::UnexpectedEof` error is returned.
+/// Messages which fail to authenticate result in an
+/// `io::ErrorKind::InvalidData` error. Any data following the last message
+/// is not read.
+pub struct StreamReader<A: Aead, R: Read, C: Counter = NonceEncoder32> {
+    decryptor: Decryptor<A, C>,
+    reader: R,
+    buffer: Vec<u8>,
+    position: usize,
+    chunk_size: usize,
+    finished: bool,
+}
+
+impl<A: Aead, R: Read, C: Counter> StreamReader<A, R, C> {
+    /// Create a new STREAM reader using the default chunk size
+    pub fn new(decryptor: Decryptor<A, C>, reader: R) -> Self {
+        Self::with_chunk_size(decryptor, reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new STREAM reader which accepts messages containing at most
+    /// the given number of bytes of plaintext. This must match the chunk
+    /// size of the `StreamWriter` which produced the STREAM.
+    pub fn with_chunk_size(decryptor: Decryptor<A, C>, reader: R, chunk_size: usize) -> Self {
+        Self {
+            decryptor,
+            reader,
+            buffer: Vec::new(),
+            position: 0,
+            chunk_size,
+            finished: false,
+        }
+    }
+
+    /// Borrow the inner reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consume this STREAM reader, returning the inner reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read, authenticate, and decrypt the next message in the STREAM
+    fn read_message(&mut self) -> io::Result<()> {
+        let tag_size = A::TagSize::to_usize();
+        let mut len = [0u8; LENGTH_PREFIX_SIZE];
+        read_exact(&mut self.reader, &mut len)?;
+
+        let len = message_len(len, tag_size, self.chunk_size)?;
+        self.buffer.resize(len, 0);
+        read_exact(&mut self.reader, &mut self.buffer)?;
+
+        let (last, _) = self
+            .decryptor
+            .decrypt_auto_in_place(b"", &mut self.buffer)?;
+
+        self.position = tag_size;
+        self.finished = last;
+        Ok(())
+    }
+}
+
+impl<A: Aead, R: Read, C: Counter> Read for StreamReader<A, R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.buffer.len() {
+            if self.finished {
+                return Ok(0);
+            }
+
+            self.read_message()?;
+        }
+
+        let plaintext = &self.buffer[self.position..];
+        let len = plaintext.len().min(buf.len());
+        buf[..len].copy_from_slice(&plaintext[..len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Fill the given buffer from the reader, treating EOF as truncation
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<()> {
+    reader.read_exact(buf).map_err(|e| {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            truncated()
+        } else {
+            e
+        }
+    })
+}
+
+/// Ensure messages of the given chunk size can be length-prefixed
+pub(crate) fn check_chunk_size(chunk_size: usize, tag_size: usize) {
+    assert!(chunk_size > 0, "chunk size must be non-zero");
+    assert!(
+        u32::try_from(chunk_size + tag_size).is_ok(),
+        "chunk size too large"
+    );
+}
+
+/// Parse and validate the length prefix of a STREAM message
+pub(crate) fn message_len(
+    prefix: [u8; LENGTH_PREFIX_SIZE],
+    tag_size: usize,
+    chunk_size: usize,
+) -> io::Result<usize> {
+    let len = u32::from_be_bytes(prefix) as usize;
+
+    if len < tag_size || len - tag_size > chunk_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid STREAM message length",
+        ));
+    }
+
+    Ok(len)
+}
+
+/// Error returned when a STREAM ends before its last message
+pub(crate) fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "truncated STREAM")
+}
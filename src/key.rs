@@ -0,0 +1,127 @@
+//! `key.rs`: Fixed-size key and nonce types
+
+use crate::{generic_array::GenericArray, Aead, Error};
+use core::{convert::TryFrom, fmt};
+use zeroize::Zeroize;
+
+/// Size of a `Nonce` in bytes
+pub const NONCE_SIZE: usize = 16;
+
+/// A key for the AEAD algorithm `A`, which is always the correct size for
+/// that algorithm.
+///
+/// The key is zeroized when it is dropped.
+pub struct Key<A: Aead>(GenericArray<u8, A::KeySize>);
+
+impl<A: Aead> Key<A> {
+    /// Create a key from the given bytes
+    pub fn new(bytes: GenericArray<u8, A::KeySize>) -> Self {
+        Key(bytes)
+    }
+
+    /// Borrow the serialized bytes of this key
+    pub fn as_slice(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl<A: Aead> AsRef<[u8]> for Key<A> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<A: Aead> Clone for Key<A> {
+    fn clone(&self) -> Self {
+        Key(self.0.clone())
+    }
+}
+
+impl<A: Aead> fmt::Debug for Key<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Key { .. }")
+    }
+}
+
+impl<A: Aead> Drop for Key<A> {
+    fn drop(&mut self) {
+        self.0.as_mut_slice().zeroize();
+    }
+}
+
+impl<A: Aead> From<GenericArray<u8, A::KeySize>> for Key<A> {
+    fn from(bytes: GenericArray<u8, A::KeySize>) -> Self {
+        Key(bytes)
+    }
+}
+
+impl<A: Aead> TryFrom<&[u8]> for Key<A> {
+    type Error = Error;
+
+    /// Returns `Error::InvalidKeySize` if the slice is the wrong length
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != A::KEY_SIZE {
+            return Err(Error::InvalidKeySize {
+                expected: A::KEY_SIZE,
+                got: bytes.len(),
+            });
+        }
+
+        Ok(Key(GenericArray::clone_from_slice(bytes)))
+    }
+}
+
+/// A 128-bit nonce, the size recommended for use with AES-SIV.
+///
+/// AES-SIV accepts nonces of any length, so the slice-based APIs remain
+/// available for nonces of other sizes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Nonce([u8; NONCE_SIZE]);
+
+impl Nonce {
+    /// Create a nonce from its serialized bytes
+    pub fn new(bytes: [u8; NONCE_SIZE]) -> Self {
+        Nonce(bytes)
+    }
+
+    /// Serialize this nonce as bytes
+    pub fn to_bytes(self) -> [u8; NONCE_SIZE] {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for Nonce {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; NONCE_SIZE]> for Nonce {
+    fn from(bytes: [u8; NONCE_SIZE]) -> Self {
+        Nonce(bytes)
+    }
+}
+
+impl From<Nonce> for [u8; NONCE_SIZE] {
+    fn from(nonce: Nonce) -> [u8; NONCE_SIZE] {
+        nonce.0
+    }
+}
+
+impl TryFrom<&[u8]> for Nonce {
+    type Error = Error;
+
+    /// Returns `Error::InvalidNonceSize` if the slice is the wrong length
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != NONCE_SIZE {
+            return Err(Error::InvalidNonceSize {
+                expected: NONCE_SIZE,
+                got: bytes.len(),
+            });
+        }
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce.copy_from_slice(bytes);
+        Ok(Nonce(nonce))
+    }
+}
@@ -0,0 +1,58 @@
+//! `key_len.rs`: Selecting a `*SivAead` type by its key length with const
+//! generics (requires the `const-generics` feature, and Rust 1.51)
+//!
+//! `AesSivAead<KEY_LEN>` is the AES-SIV AEAD with a `KEY_LEN`-byte key, so
+//! `AesSivAead<32>`, `AesSivAead<48>` and `AesSivAead<64>` are the same
+//! types as `Aes128SivAead`, `Aes192SivAead` and `Aes256SivAead` (and
+//! likewise `AesPmacSivAead<KEY_LEN>` with the `pmac` feature). The key
+//! length selects the AES block cipher through the `SivKeyLen` trait, which
+//! `KeyLen<KEY_LEN>` implements for each supported length.
+//!
+//! Code which is generic over the key length can require
+//! `KeyLen<KEY_LEN>: SivKeyLen`, and use the selected type through the
+//! `Aead` trait.
+
+use crate::{Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead};
+
+#[cfg(feature = "pmac")]
+use crate::{Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead};
+
+/// Key length in bytes, used to select a `*SivAead` type (see `SivKeyLen`)
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct KeyLen<const KEY_LEN: usize>;
+
+/// Key lengths supported by AES-SIV: implemented by `KeyLen<32>`,
+/// `KeyLen<48>`, and `KeyLen<64>` (for AES-128, AES-192, and AES-256)
+pub trait SivKeyLen {
+    /// AES-CMAC-SIV with this key length
+    type SivAead: Aead + Clone + Send + Sync;
+
+    /// AES-PMAC-SIV with this key length
+    #[cfg(feature = "pmac")]
+    type PmacSivAead: Aead + Clone + Send + Sync;
+}
+
+impl SivKeyLen for KeyLen<32> {
+    type SivAead = Aes128SivAead;
+    #[cfg(feature = "pmac")]
+    type PmacSivAead = Aes128PmacSivAead;
+}
+
+impl SivKeyLen for KeyLen<48> {
+    type SivAead = Aes192SivAead;
+    #[cfg(feature = "pmac")]
+    type PmacSivAead = Aes192PmacSivAead;
+}
+
+impl SivKeyLen for KeyLen<64> {
+    type SivAead = Aes256SivAead;
+    #[cfg(feature = "pmac")]
+    type PmacSivAead = Aes256PmacSivAead;
+}
+
+/// AES-CMAC-SIV in AEAD mode with a `KEY_LEN`-byte key (32, 48, or 64)
+pub type AesSivAead<const KEY_LEN: usize> = <KeyLen<KEY_LEN> as SivKeyLen>::SivAead;
+
+/// AES-PMAC-SIV in AEAD mode with a `KEY_LEN`-byte key (32, 48, or 64)
+#[cfg(feature = "pmac")]
+pub type AesPmacSivAead<const KEY_LEN: usize> = <KeyLen<KEY_LEN> as SivKeyLen>::PmacSivAead;
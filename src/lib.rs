@@ -1,25 +1,53 @@
 //! `Miscreant`: Misuse resistant symmetric encryption library providing the
 //! AES-SIV (RFC 5297), AES-PMAC-SIV, and STREAM constructions
 //!
+//! ## Raw AES-SIV
+//!
+//! The RFC 5297 AES-SIV primitive is available as [`siv::Siv`] (with the
+//! [`Aes128Siv`] and [`Aes256Siv`] aliases, and PMAC-based equivalents),
+//! which accepts an arbitrary vector of headers. In the nonce-based mode of
+//! RFC 5297, the nonce is the last header: for example, [`SivAead`] encrypts
+//! with the headers `[associated_data, nonce]`.
+//!
 //! ## Minimum Supported Rust Version (MSRV)
 //!
-//! - Rust **1.36.0**
+//! - Rust **1.40.0**
+//! - Rust **1.51.0** when the `heapless` feature is enabled (const generics)
+//! - Rust **1.51.0** when the `const-generics` feature is enabled
+//! - Rust **1.56.0** when the `rustcrypto-aead` feature is enabled (required
+//!   by the `aead` crate)
+//! - Rust **1.80.0** when the `rayon` feature is enabled (required by rayon)
+//! - Rust **1.81.0** when the `wasm` feature is enabled (required by
+//!   wasm-bindgen)
+//! - Rust **1.72.0** on `aarch64` targets when the `armv8` feature is enabled
+//!   (for the ARMv8 AES intrinsics)
 //!
 //! ### `x86`/`x86_64` targets with AES-NI support
 //!
-//! To build this crate with hardware acceleration support on `x86`/`x86_64`
-//! targets with [AES-NI] support, set the following `RUSTFLAGS` environment
-//! variable:
+//! On `x86`/`x86_64` targets, the `*SivAead` types (and the STREAM types
+//! built on them) detect [AES-NI] support at runtime, and use it if it's
+//! available (see the `backend` module), unless the `force-soft` feature
+//! is enabled. Without the `std` feature, AES-NI
+//! is only used if it's enabled at compile time with the following
+//! `RUSTFLAGS` environment variable:
 //!
 //! `RUSTFLAGS=-Ctarget-feature=+aes,+ssse3`
 //!
-//! You can configure your `~/.cargo/config` to always pass these flags:
+//! These flags are also needed for the lower-level `Siv` types to use
+//! AES-NI. You can configure your `~/.cargo/config` to always pass them:
 //!
 //! ```toml
 //! [build]
 //! rustflags = ["-Ctarget-feature=+aes,+ssse3"]
 //! ```
 //!
+//! ### `aarch64` targets with the ARMv8 Cryptography Extensions
+//!
+//! On `aarch64` targets, the `*SivAead` types use the software AES
+//! implementation unless the `armv8` feature is enabled, in which case they
+//! detect support for the ARMv8 AES instructions at runtime and use them if
+//! they're available (unless the `force-soft` feature is also enabled).
+//!
 //! [AES-NI]: https://en.wikipedia.org/wiki/AES_instruction_set#x86_architecture_processors
 
 #![no_std]
@@ -34,17 +62,72 @@ extern crate alloc;
 extern crate std;
 
 mod aead;
+mod algorithm;
+mod associated_data;
+#[cfg(feature = "async")]
+pub mod async_io;
+pub mod backend;
+#[cfg(feature = "alloc")]
+pub mod compat;
+mod domain;
+mod error;
 pub mod ffi;
+#[cfg(feature = "alloc")]
+mod framed;
+pub mod incremental;
+#[cfg(all(feature = "std", feature = "stream"))]
+pub mod io;
+mod key;
+#[cfg(feature = "const-generics")]
+mod key_len;
+pub mod mac;
+#[cfg(feature = "alloc")]
+mod message;
+#[cfg(all(feature = "pmac", feature = "rayon"))]
+mod parallel;
+#[cfg(feature = "rustcrypto-aead")]
+mod rustcrypto;
 #[cfg(feature = "stream")]
 pub mod stream;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use crate::aead::{Aead, Aes128SivAead, Aes256SivAead, SivAead};
+pub use crate::{
+    aead::{
+        Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead, SivAead, Tag, TagPosition,
+        MAX_MESSAGE_LEN, MIN_TRUNCATED_TAG_SIZE,
+    },
+    algorithm::{Algorithm, DynAead},
+    associated_data::AssociatedData,
+    backend::{active_backend, Backend},
+    domain::DomainSivAead,
+    error::Error,
+    key::{Key, Nonce, NONCE_SIZE},
+};
 pub use aes_siv::{
-    aead::{generic_array, Error},
+    aead::generic_array,
     siv::{self, Aes128Siv, Aes256Siv},
 };
 
+#[cfg(feature = "alloc")]
+pub use crate::{
+    aead::reencrypt,
+    algorithm::new_dyn,
+    framed::{decrypt_framed, FramedAead, FRAME_VERSION},
+    message::SealedMessage,
+};
+#[cfg(feature = "alloc")]
+pub use zeroize::Zeroizing;
+
+#[cfg(feature = "committing")]
+pub use crate::aead::KEY_COMMITMENT_SIZE;
+
+#[cfg(all(feature = "const-generics", feature = "pmac"))]
+pub use crate::key_len::AesPmacSivAead;
+#[cfg(feature = "const-generics")]
+pub use crate::key_len::{AesSivAead, KeyLen, SivKeyLen};
+
 #[cfg(feature = "pmac")]
-pub use crate::aead::{Aes128PmacSivAead, Aes256PmacSivAead};
+pub use crate::aead::{Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead};
 #[cfg(feature = "pmac")]
 pub use aes_siv::siv::{Aes128PmacSiv, Aes256PmacSiv};
@@ -0,0 +1,117 @@
+//! `mac.rs`: Standalone message authentication codes, using the same MAC
+//! implementations as S2V (and the same runtime-selected AES backends as the
+//! `*SivAead` types)
+//!
+//! The CMAC types are specifically AES-CMAC (NIST SP 800-38B, RFC 4493),
+//! and the PMAC types (with the `pmac` feature) are PMAC with AES.
+//!
+//! Each MAC is computed incrementally: create it with a key, `update` it
+//! with any number of pieces of the message, and then either `finalize` it
+//! to obtain the 16-byte tag or `verify` it against an expected tag in
+//! constant time.
+
+use crate::{
+    backend::{Aes128, Aes256},
+    generic_array::GenericArray,
+    Error,
+};
+use crypto_mac::Mac;
+use subtle::ConstantTimeEq;
+
+/// Size of a MAC tag in bytes
+pub const MAC_SIZE: usize = 16;
+
+macro_rules! define_mac {
+    ($name:ident, $mac:ty, $key_size:expr, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone)]
+        pub struct $name {
+            mac: $mac,
+        }
+
+        impl $name {
+            /// Size of a key in bytes
+            pub const KEY_SIZE: usize = $key_size;
+
+            /// Create a new MAC instance with the given key
+            ///
+            /// Panics if the key is the wrong length
+            pub fn new(key: &[u8]) -> Self {
+                Self::try_new(key).expect("invalid key size")
+            }
+
+            /// Create a new MAC instance with the given key, returning
+            /// `Error::InvalidKeySize` if the key is the wrong length
+            pub fn try_new(key: &[u8]) -> Result<Self, Error> {
+                if key.len() != Self::KEY_SIZE {
+                    return Err(Error::InvalidKeySize {
+                        expected: Self::KEY_SIZE,
+                        got: key.len(),
+                    });
+                }
+
+                Ok(Self {
+                    mac: <$mac>::new(GenericArray::from_slice(key)),
+                })
+            }
+
+            /// Add the given data to the message being authenticated
+            pub fn update(&mut self, data: &[u8]) {
+                self.mac.input(data);
+            }
+
+            /// Compute the tag for the message
+            pub fn finalize(self) -> [u8; MAC_SIZE] {
+                let mut tag = [0u8; MAC_SIZE];
+                tag.copy_from_slice(&self.mac.result().code());
+                tag
+            }
+
+            /// Check the message against the given tag in constant time,
+            /// returning `Error::AuthenticationFailed` if it doesn't match
+            pub fn verify(self, tag: &[u8]) -> Result<(), Error> {
+                if bool::from(self.finalize()[..].ct_eq(tag)) {
+                    Ok(())
+                } else {
+                    Err(Error::AuthenticationFailed)
+                }
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{} {{ .. }}", stringify!($name))
+            }
+        }
+    };
+}
+
+define_mac!(
+    Aes128Cmac,
+    cmac::Cmac<Aes128>,
+    16,
+    "AES-CMAC with a 128-bit key (as used by AES-128-SIV)"
+);
+
+define_mac!(
+    Aes256Cmac,
+    cmac::Cmac<Aes256>,
+    32,
+    "AES-CMAC with a 256-bit key (as used by AES-256-SIV)"
+);
+
+#[cfg(feature = "pmac")]
+define_mac!(
+    Aes128Pmac,
+    pmac_crate::Pmac<Aes128>,
+    16,
+    "PMAC with AES-128 (a parallelizable MAC, as used by AES-128-PMAC-SIV)"
+);
+
+#[cfg(feature = "pmac")]
+define_mac!(
+    Aes256Pmac,
+    pmac_crate::Pmac<Aes256>,
+    32,
+    "PMAC with AES-256 (a parallelizable MAC, as used by AES-256-PMAC-SIV)"
+);
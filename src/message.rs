@@ -0,0 +1,22 @@
+//! `message.rs`: Self-describing encrypted message envelope
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An encrypted message bundled with the nonce it was encrypted under,
+/// ready to be transmitted or stored.
+///
+/// Produced by `Aead::seal_message` and opened with `Aead::open_message`.
+/// The associated data (if any) is not included and must be supplied
+/// again when opening the message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SealedMessage {
+    /// Nonce the message was encrypted under
+    pub nonce: Vec<u8>,
+
+    /// SIV tag followed by the ciphertext
+    pub ciphertext: Vec<u8>,
+}
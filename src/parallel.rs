@@ -0,0 +1,250 @@
+//! `parallel.rs`: S2V for AES-PMAC-SIV computed on multiple threads with
+//! rayon.
+//!
+//! PMAC encrypts each block of its input independently, after XORing it
+//! with an offset derived from the block's position, and combines the
+//! results with XOR. The blocks of a large input can therefore be split into
+//! ranges which are processed in parallel, with their partial sums combined
+//! afterward. Inputs smaller than `PARALLEL_THRESHOLD` are processed on the
+//! calling thread, where splitting them up would cost more than it saves.
+
+use crate::{
+    generic_array::{
+        typenum::{Unsigned, U16, U64},
+        GenericArray,
+    },
+    incremental::{dbl, xor},
+    Tag,
+};
+use aes_siv::siv::IV_SIZE;
+use block_cipher_trait::BlockCipher;
+use rayon::prelude::*;
+use zeroize::Zeroize;
+
+/// Inputs at least this large are split across threads
+const PARALLEL_THRESHOLD: usize = 64 * 1024;
+
+/// Number of bytes of input processed by each task (a whole number of blocks)
+const CHUNK_SIZE: usize = 16 * 1024;
+
+type Block = GenericArray<u8, U16>;
+
+/// Compute the S2V tag of the given headers and plaintext with PMAC keyed by
+/// `mac_key` (the first half of an AES-PMAC-SIV key).
+///
+/// The result is identical to the serial S2V computed by `siv::Siv`.
+pub(crate) fn s2v<C>(mac_key: &[u8], headers: &[&[u8]], plaintext: &[u8]) -> Tag
+where
+    C: BlockCipher<BlockSize = U16> + Sync,
+{
+    let pmac = Pmac::new(C::new_varkey(mac_key).expect("invalid MAC key"));
+    let mut state = pmac.mac(&[], &[0u8; IV_SIZE]);
+
+    for header in headers {
+        dbl(&mut state);
+        xor(&mut state, &pmac.mac_message(header));
+    }
+
+    let mut last = [0u8; IV_SIZE * 2];
+
+    let mut code = if plaintext.len() >= IV_SIZE {
+        // "xorend" only changes the last 16 bytes, so all of the whole blocks
+        // before them are authenticated unchanged
+        let prefix_len = (plaintext.len() - IV_SIZE) / IV_SIZE * IV_SIZE;
+        let (prefix, rest) = plaintext.split_at(prefix_len);
+        let tail = &mut last[..rest.len()];
+        tail.copy_from_slice(rest);
+        xor(&mut tail[rest.len() - IV_SIZE..], &state);
+        pmac.mac(prefix, tail)
+    } else {
+        // Messages shorter than a block are padded
+        dbl(&mut state);
+        last[..plaintext.len()].copy_from_slice(plaintext);
+        last[plaintext.len()] = 0x80;
+        xor(&mut last[..IV_SIZE], &state);
+        pmac.mac(&[], &last[..IV_SIZE])
+    };
+
+    let mut tag = [0u8; IV_SIZE];
+    tag.copy_from_slice(&code);
+
+    code.zeroize();
+    state.zeroize();
+    last.zeroize();
+
+    Tag::new(tag)
+}
+
+/// PMAC which can split its input across threads
+struct Pmac<C: BlockCipher<BlockSize = U16>> {
+    cipher: C,
+
+    /// `L(i)`, i.e. `L` multiplied by `x^i`, for each possible number of
+    /// trailing zeros of the block counter
+    l: GenericArray<Block, U64>,
+
+    /// `L` divided by `x`
+    l_inv: Block,
+}
+
+impl<C> Pmac<C>
+where
+    C: BlockCipher<BlockSize = U16> + Sync,
+{
+    fn new(cipher: C) -> Self {
+        let mut l = GenericArray::<Block, U64>::default();
+        cipher.encrypt_block(&mut l[0]);
+
+        for i in 1..l.len() {
+            l[i] = l[i - 1];
+            dbl(&mut l[i]);
+        }
+
+        let l_inv = inv_dbl(l[0]);
+        Self { cipher, l, l_inv }
+    }
+
+    /// Compute the PMAC of a complete message
+    fn mac_message(&self, message: &[u8]) -> Block {
+        let last_block = message.len().saturating_sub(1) / IV_SIZE * IV_SIZE;
+        let (prefix, tail) = message.split_at(last_block);
+        self.mac(prefix, tail)
+    }
+
+    /// Compute the PMAC of the message formed by `prefix` (a whole number of
+    /// blocks) followed by `tail` (at most two blocks, the last of which may
+    /// be partial, and which is only empty if the whole message is).
+    fn mac(&self, prefix: &[u8], mut tail: &[u8]) -> Block {
+        debug_assert_eq!(prefix.len() % IV_SIZE, 0);
+        debug_assert!(tail.len() <= IV_SIZE * 2);
+
+        let mut sum = if prefix.len() < PARALLEL_THRESHOLD {
+            self.sum(0, prefix)
+        } else {
+            prefix
+                .par_chunks(CHUNK_SIZE)
+                .enumerate()
+                .map(|(i, chunk)| self.sum(i * CHUNK_SIZE / IV_SIZE, chunk))
+                .reduce(Block::default, |mut a, b| {
+                    xor(&mut a, &b);
+                    a
+                })
+        };
+
+        if tail.len() > IV_SIZE {
+            let (block, rest) = tail.split_at(IV_SIZE);
+            xor(&mut sum, &self.sum(prefix.len() / IV_SIZE, block));
+            tail = rest;
+        }
+
+        // The last block isn't offset and encrypted: it's XORed into the sum
+        // along with `L / x` if it's whole, or padded if it isn't
+        let mut last = Block::default();
+
+        if tail.len() == IV_SIZE {
+            last.copy_from_slice(tail);
+            xor(&mut last, &self.l_inv);
+        } else {
+            last[..tail.len()].copy_from_slice(tail);
+            last[tail.len()] = 0x80;
+        }
+
+        xor(&mut sum, &last);
+        last.zeroize();
+
+        self.cipher.encrypt_block(&mut sum);
+        sum
+    }
+
+    /// Compute the XOR of the encrypted (offset) blocks of `data`, a whole
+    /// number of blocks which begins after the first `start` blocks of the
+    /// message.
+    fn sum(&self, start: usize, data: &[u8]) -> Block {
+        let mut offset = self.offset(start);
+        let mut counter = start;
+        let mut sum = Block::default();
+
+        let mut batch = GenericArray::<Block, C::ParBlocks>::default();
+        let mut chunks = data.chunks_exact(IV_SIZE * C::ParBlocks::to_usize());
+
+        for chunk in &mut chunks {
+            for (block, input) in batch.iter_mut().zip(chunk.chunks(IV_SIZE)) {
+                counter += 1;
+                xor(&mut offset, self.l_for(counter));
+                block.copy_from_slice(input);
+                xor(block, &offset);
+            }
+
+            self.cipher.encrypt_blocks(&mut batch);
+
+            for block in batch.iter() {
+                xor(&mut sum, block);
+            }
+        }
+
+        for input in chunks.remainder().chunks(IV_SIZE) {
+            counter += 1;
+            xor(&mut offset, self.l_for(counter));
+
+            let mut block = Block::clone_from_slice(input);
+            xor(&mut block, &offset);
+            self.cipher.encrypt_block(&mut block);
+            xor(&mut sum, &block);
+        }
+
+        for block in batch.iter_mut() {
+            block.zeroize();
+        }
+
+        offset.zeroize();
+        sum
+    }
+
+    /// Value XORed into the offset for the block with the given (1-based)
+    /// counter
+    fn l_for(&self, counter: usize) -> &Block {
+        &self.l[counter.trailing_zeros() as usize]
+    }
+
+    /// Offset after the first `blocks` blocks of the message.
+    ///
+    /// Each block's offset adds `L(ntz(i))`, so this is the XOR of `L(i)` for
+    /// each bit `i` set in the Gray code of `blocks`.
+    fn offset(&self, blocks: usize) -> Block {
+        let mut gray = blocks ^ (blocks >> 1);
+        let mut offset = Block::default();
+
+        for l in self.l.iter() {
+            if gray & 1 == 1 {
+                xor(&mut offset, l);
+            }
+
+            gray >>= 1;
+        }
+
+        offset
+    }
+}
+
+impl<C: BlockCipher<BlockSize = U16>> Drop for Pmac<C> {
+    fn drop(&mut self) {
+        for l in self.l.iter_mut() {
+            l.zeroize();
+        }
+
+        self.l_inv.zeroize();
+    }
+}
+
+/// Divide a block by x in GF(2^128) (the inverse of `dbl`)
+fn inv_dbl(mut block: Block) -> Block {
+    let carry = block[IV_SIZE - 1] & 1;
+    block[IV_SIZE - 1] ^= carry * 0x87;
+
+    for i in (1..IV_SIZE).rev() {
+        block[i] = block[i] >> 1 | block[i - 1] << 7;
+    }
+
+    block[0] = block[0] >> 1 | carry << 7;
+    block
+}
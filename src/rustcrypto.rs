@@ -0,0 +1,140 @@
+//! `rustcrypto.rs`: Implementations of the RustCrypto `aead` crate's traits,
+//! so the `*SivAead` types can be used by code which is generic over them
+//!
+//! Keys are the combined SIV key (`KeyInit`), nonces are `NONCE_SIZE` (16)
+//! bytes, and the synthetic IV is the `Tag`.
+//! `AeadInPlace::encrypt_in_place` (and so `aead::Aead::encrypt`) produces
+//! the same layout as this crate's `Aead::encrypt`, with the tag *before*
+//! the ciphertext rather than appended to it as with most RustCrypto AEADs.
+//!
+//! The `aead` traits take `&self`, whereas SIV's MAC state is mutated while
+//! computing a tag, so each call works on a copy of the key schedule.
+//! Reusing a `SivAead` through this crate's `Aead` trait is faster.
+//!
+//! Both `KeyInit` and this crate's `Aead` trait have a `new` method, so if
+//! both traits are in scope, disambiguate with `<T as KeyInit>::new`.
+
+use crate::{generic_array::ArrayLength, Aead, SivAead, Tag};
+use aead_crate::{
+    consts::{U0, U16},
+    AeadCore, AeadInPlace, Buffer, Key, KeyInit, KeySizeUser, Nonce,
+};
+use aes_siv::siv::IV_SIZE;
+use core::ops::Add;
+use crypto_mac::Mac;
+use stream_cipher::{NewStreamCipher, SyncStreamCipher};
+
+impl<C, M> KeySizeUser for SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8> + aead_crate::generic_array::ArrayLength<u8>,
+{
+    /// The combined SIV key (MAC key followed by CTR key): 32, 48, or 64
+    /// bytes for AES-128, AES-192, and AES-256 respectively
+    type KeySize = <C::KeySize as Add>::Output;
+}
+
+impl<C, M> KeyInit for SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8> + aead_crate::generic_array::ArrayLength<u8>,
+{
+    fn new(key: &Key<Self>) -> Self {
+        <Self as Aead>::new(key.as_slice())
+    }
+}
+
+impl<C, M> AeadCore for SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    type NonceSize = U16;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl<C, M> AeadInPlace for SivAead<C, M>
+where
+    C: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    C::KeySize: Add,
+    <C::KeySize as Add>::Output: ArrayLength<u8>,
+{
+    /// Encrypt the buffer in-place, inserting the tag before the ciphertext
+    fn encrypt_in_place(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> aead_crate::Result<()> {
+        let tag =
+            AeadInPlace::encrypt_in_place_detached(self, nonce, associated_data, buffer.as_mut())?;
+        buffer.extend_from_slice(&tag)?;
+        buffer.as_mut().rotate_right(IV_SIZE);
+        Ok(())
+    }
+
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead_crate::Result<aead_crate::Tag<Self>> {
+        let tag = SivAead::encrypt_in_place_detached(
+            &mut self.clone(),
+            nonce.as_slice(),
+            associated_data,
+            buffer,
+        );
+
+        Ok(aead_crate::Tag::<Self>::clone_from_slice(tag.as_ref()))
+    }
+
+    /// Decrypt the buffer in-place, reading the tag from before the
+    /// ciphertext
+    fn decrypt_in_place(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> aead_crate::Result<()> {
+        if buffer.len() < IV_SIZE {
+            return Err(aead_crate::Error);
+        }
+
+        let (tag, message) = buffer.as_mut().split_at_mut(IV_SIZE);
+        let tag = aead_crate::Tag::<Self>::clone_from_slice(tag);
+        AeadInPlace::decrypt_in_place_detached(self, nonce, associated_data, message, &tag)?;
+
+        buffer.as_mut().rotate_left(IV_SIZE);
+        buffer.truncate(buffer.len() - IV_SIZE);
+        Ok(())
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &aead_crate::Tag<Self>,
+    ) -> aead_crate::Result<()> {
+        let mut bytes = [0u8; IV_SIZE];
+        bytes.copy_from_slice(tag);
+
+        SivAead::decrypt_in_place_detached(
+            &mut self.clone(),
+            nonce.as_slice(),
+            associated_data,
+            buffer,
+            &Tag::new(bytes),
+        )
+        .map_err(|_| aead_crate::Error)
+    }
+}
@@ -1,30 +1,129 @@
 //! `stream.rs`: The STREAM online authenticated encryption construction.
 //! See <https://eprint.iacr.org/2015/189.pdf> for definition.
+//!
+//! ## Truncation
+//!
+//! Every message in a STREAM is encrypted under a nonce which encodes its
+//! position, and the final message is additionally flagged as the last block.
+//! This prevents reordering, and prevents an attacker from passing off a
+//! truncated STREAM as complete: only `decrypt_last*` can authenticate the
+//! final message, and `decrypt_next*` rejects it with
+//! `Error::UnexpectedLastBlock`.
+//!
+//! However, an attacker can still drop the final message (and any number of
+//! messages before it). Receivers must therefore treat a STREAM which ends
+//! without a successful call to `decrypt_last*` as truncated, and discard it.
+//!
+//! ## Counter binding
+//!
+//! With the `alloc` feature, `with_counter_binding` additionally appends the
+//! big-endian counter to the associated data of every message, so a message
+//! moved to a different position fails to authenticate even if the nonce
+//! mechanism were bypassed. Both sides of the STREAM must enable it, and it
+//! is off by default, since it changes every ciphertext. For messages with
+//! multiple associated data headers (`encrypt_next_headers*`), the counter
+//! is appended to the last header.
 
-use crate::{Aead, Aes128PmacSivAead, Aes128SivAead, Aes256PmacSivAead, Aes256SivAead, Error};
+use crate::{
+    aead::check_headers_len,
+    generic_array::{
+        typenum::{Unsigned, U16},
+        ArrayLength,
+    },
+    Aead, Aes128PmacSivAead, Aes128SivAead, Aes192PmacSivAead, Aes192SivAead, Aes256PmacSivAead,
+    Aes256SivAead, Error, SivAead,
+};
+use aes_siv::siv::IV_SIZE;
+use core::{fmt, marker::PhantomData, ops::Add};
+use crypto_mac::Mac;
+use stream_cipher::{NewStreamCipher, SyncStreamCipher};
+use zeroize::Zeroize;
 
 #[cfg(feature = "alloc")]
-use alloc::vec::Vec;
+use alloc::{borrow::Cow, collections::VecDeque, vec::Vec};
+#[cfg(feature = "alloc")]
+use zeroize::Zeroizing;
+
+#[cfg(feature = "alloc")]
+use core::iter;
+
+#[cfg(feature = "rand_core")]
+use rand_core::{CryptoRng, RngCore};
+
+#[cfg(all(feature = "alloc", feature = "rayon"))]
+use core::convert::TryFrom;
+#[cfg(all(feature = "alloc", feature = "rayon"))]
+use rayon::prelude::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Size of a nonce required by STREAM in bytes
 pub const NONCE_SIZE: usize = 8;
 
+/// Size of a nonce required by STREAM with a 64-bit counter in bytes
+pub const NONCE_SIZE_64: usize = 4;
+
+/// Size of a nonce required by STREAM with a 96-bit nonce prefix in bytes
+pub const NONCE_SIZE_96: usize = 12;
+
 /// Byte flag indicating this is the last block in the STREAM (otherwise 0)
 const LAST_BLOCK_FLAG: u8 = 1;
 
+/// Saved state of an in-progress STREAM, which can be used to resume it
+/// (e.g. after a crash) with byte-identical nonces to an uninterrupted run.
+///
+/// Contains only the nonce prefix and the position of the next message.
+/// Key material is never part of the state: the key must be persisted
+/// separately (and securely) and supplied again when restoring.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct StreamState<N = [u8; NONCE_SIZE], P = u32> {
+    /// Nonce prefix the STREAM was initialized with
+    pub nonce: N,
+
+    /// Position of the next message in the STREAM
+    pub position: P,
+}
+
 /// A STREAM encryptor with a 32-bit counter, generalized for any AEAD algorithm
 ///
 /// This corresponds to the ℰ stream encryptor object as defined in the paper
 /// Online Authenticated-Encryption and its Nonce-Reuse Misuse-Resistance
-pub struct Encryptor<A: Aead> {
+///
+/// Cloning an encryptor forks the STREAM: both copies continue from the
+/// same position with the same nonces. This is useful for snapshotting the
+/// state before encrypting a message, but only one copy should be used to
+/// encrypt further messages (encrypting different messages at the same
+/// position reveals whether they are equal).
+#[derive(Clone)]
+pub struct Encryptor<A: Aead, C: Counter = NonceEncoder32> {
     alg: A,
-    nonce: NonceEncoder32,
+    nonce: C,
+    bind_counter: bool,
 }
 
+/// A STREAM encryptor with a 64-bit counter, generalized for any AEAD algorithm
+///
+/// Uses a 32-bit (4-byte) nonce prefix, trading nonce space for a counter
+/// which won't overflow in practice even with very small chunk sizes.
+pub type Encryptor64<A> = Encryptor<A, NonceEncoder64>;
+
+/// A STREAM encryptor with a 96-bit nonce prefix and a 32-bit counter,
+/// generalized for any AEAD algorithm
+///
+/// Uses a 12-byte nonce prefix, which is large enough to be generated at
+/// random for every STREAM. See `PrefixedNonceEncoder` for other sizes.
+pub type Encryptor96<A> = Encryptor<A, NonceEncoder96>;
+
 /// AES-CMAC-SIV STREAM encryptor with 256-bit key size (128-bit security)
 /// and a 64-bit (8-byte) nonce.
 pub type Aes128SivEncryptor = Encryptor<Aes128SivAead>;
 
+/// AES-CMAC-SIV STREAM encryptor with 384-bit key size (192-bit security)
+/// and a 64-bit (8-byte) nonce.
+pub type Aes192SivEncryptor = Encryptor<Aes192SivAead>;
+
 /// AES-CMAC-SIV STREAM encryptor with 512-bit key size (256-bit security)
 /// and a 64-bit (8-byte) nonce.
 pub type Aes256SivEncryptor = Encryptor<Aes256SivAead>;
@@ -33,46 +132,580 @@ pub type Aes256SivEncryptor = Encryptor<Aes256SivAead>;
 /// and a 64-bit (8-byte) nonce.
 pub type Aes128PmacSivEncryptor = Encryptor<Aes128PmacSivAead>;
 
+/// AES-PMAC-SIV STREAM encryptor with 384-bit key size (192-bit security)
+/// and a 64-bit (8-byte) nonce.
+pub type Aes192PmacSivEncryptor = Encryptor<Aes192PmacSivAead>;
+
 /// AES-PMAC-SIV STREAM encryptor with 512-bit key size (256-bit security)
 /// and a 64-bit (8-byte) nonce.
 pub type Aes256PmacSivEncryptor = Encryptor<Aes256PmacSivAead>;
 
-impl<A: Aead> Encryptor<A> {
+/// AES-CMAC-SIV STREAM encryptor with 256-bit key size (128-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes128SivEncryptor64 = Encryptor64<Aes128SivAead>;
+
+/// AES-CMAC-SIV STREAM encryptor with 384-bit key size (192-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes192SivEncryptor64 = Encryptor64<Aes192SivAead>;
+
+/// AES-CMAC-SIV STREAM encryptor with 512-bit key size (256-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes256SivEncryptor64 = Encryptor64<Aes256SivAead>;
+
+/// AES-PMAC-SIV STREAM encryptor with 256-bit key size (128-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes128PmacSivEncryptor64 = Encryptor64<Aes128PmacSivAead>;
+
+/// AES-PMAC-SIV STREAM encryptor with 384-bit key size (192-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes192PmacSivEncryptor64 = Encryptor64<Aes192PmacSivAead>;
+
+/// AES-PMAC-SIV STREAM encryptor with 512-bit key size (256-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes256PmacSivEncryptor64 = Encryptor64<Aes256PmacSivAead>;
+
+impl<A: Aead, C: Counter> Encryptor<A, C> {
     /// Create a new STREAM encryptor, initialized with a given key and nonce.
     ///
     /// Panics if the key or nonce is the wrong size.
     pub fn new(key: &[u8], nonce: &[u8]) -> Self {
         Self {
             alg: A::new(key),
-            nonce: NonceEncoder32::new(nonce),
+            nonce: C::new(nonce),
+            bind_counter: false,
+        }
+    }
+
+    /// Create a new STREAM encryptor, returning `Error::InvalidKeySize` or
+    /// `Error::InvalidNonceSize` if the key or nonce is the wrong size.
+    pub fn try_new(key: &[u8], nonce: &[u8]) -> Result<Self, Error> {
+        let alg = A::try_new(key)?;
+        check_prefix_size::<C>(nonce)?;
+
+        Ok(Self {
+            alg,
+            nonce: C::new(nonce),
+            bind_counter: false,
+        })
+    }
+
+    /// Create a new STREAM encryptor with a random nonce generated from the
+    /// given RNG, returning the encryptor along with the nonce.
+    ///
+    /// The nonce must be transmitted to the receiver, who needs it to
+    /// construct the `Decryptor`.
+    ///
+    /// Panics if the key is the wrong size.
+    #[cfg(feature = "rand_core")]
+    pub fn generate(key: &[u8], rng: &mut (impl CryptoRng + RngCore)) -> (Self, C::Prefix) {
+        let mut nonce = C::Prefix::default();
+        rng.fill_bytes(nonce.as_mut());
+        (Self::new(key, nonce.as_ref()), nonce)
+    }
+
+    /// Create a new STREAM encryptor which starts at the given position rather
+    /// than zero, e.g. to begin encrypting at a known message without processing
+    /// the messages before it.
+    ///
+    /// Panics if the key or nonce is the wrong size.
+    pub fn with_counter(key: &[u8], nonce: &[u8], start: C::Position) -> Self {
+        Self {
+            alg: A::new(key),
+            nonce: C::with_position(nonce, start),
+            bind_counter: false,
+        }
+    }
+
+    /// Restore a STREAM encryptor from a previously saved state.
+    ///
+    /// The key is not part of the state and must be supplied separately.
+    ///
+    /// Panics if the key or nonce is the wrong size.
+    pub fn restore(key: &[u8], state: StreamState<C::Prefix, C::Position>) -> Self {
+        Self {
+            alg: A::new(key),
+            nonce: C::with_position(state.nonce.as_ref(), state.position),
+            bind_counter: false,
+        }
+    }
+
+    /// Start a new STREAM under the given nonce, reusing the already
+    /// initialized key schedule and resetting the position to zero.
+    ///
+    /// Panics if the nonce is the wrong size.
+    pub fn reset_nonce(&mut self, nonce: &[u8]) {
+        self.nonce = C::new(nonce);
+    }
+
+    /// Replace the key used for subsequent messages, keeping the current
+    /// nonce prefix and position in the STREAM.
+    ///
+    /// Both sides of the STREAM must rekey at the same position.
+    ///
+    /// Panics if the key is the wrong size.
+    pub fn rekey(&mut self, new_key: &[u8]) {
+        self.alg = A::new(new_key);
+    }
+
+    /// Authenticate the position of every message by appending the
+    /// big-endian counter to its associated data (see the module docs).
+    ///
+    /// The decryptor must enable counter binding too. It isn't part of the
+    /// saved state, so enable it again after `restore`.
+    #[cfg(feature = "alloc")]
+    pub fn with_counter_binding(mut self) -> Self {
+        self.bind_counter = true;
+        self
+    }
+
+    /// Position of the next message in the stream, i.e. the number of
+    /// messages encrypted so far
+    pub fn position(&self) -> C::Position {
+        self.nonce.position()
+    }
+
+    /// Save the current state of the STREAM so it can be restored later.
+    ///
+    /// The returned state does not include the key.
+    pub fn save_state(&self) -> StreamState<C::Prefix, C::Position> {
+        StreamState {
+            nonce: self.nonce.prefix(),
+            position: self.nonce.position(),
         }
     }
 
+    /// Nonce the next message will be encrypted under by `encrypt_next*`
+    pub fn next_nonce(&self) -> &[u8] {
+        self.nonce.as_slice()
+    }
+
+    /// Nonce the next message would be encrypted under by `encrypt_last*`,
+    /// computed without modifying the counter
+    pub fn final_nonce(&self) -> C::Nonce {
+        self.nonce.final_nonce()
+    }
+
     /// Encrypt the next message in the stream in-place
+    ///
+    /// Panics if the STREAM nonce counter would overflow.
     pub fn encrypt_next_in_place(&mut self, ad: &[u8], buffer: &mut [u8]) {
-        self.alg.encrypt_in_place(self.nonce.as_slice(), ad, buffer);
+        self.try_encrypt_next_in_place(ad, buffer)
+            .expect("STREAM nonce counter overflowed")
+    }
+
+    /// Encrypt the next message in the stream in-place, returning
+    /// `Error::CounterOverflow` instead of panicking if the STREAM nonce
+    /// counter would overflow. The buffer is left untouched in that case.
+    pub fn try_encrypt_next_in_place(&mut self, ad: &[u8], buffer: &mut [u8]) -> Result<(), Error> {
+        check_counter(&self.nonce)?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+        self.alg
+            .encrypt_in_place(self.nonce.as_slice(), &ad, buffer);
         self.nonce.increment();
+        Ok(())
     }
 
-    /// Encrypt the final message in-place, consuming the stream encryptor
+    /// Encrypt the final message in-place, consuming the stream encryptor.
+    ///
+    /// The final message may be empty (a buffer holding only space for the
+    /// tag), e.g. to finish a STREAM whose length turned out to be an exact
+    /// multiple of the chunk size.
     pub fn encrypt_last_in_place(mut self, ad: &[u8], buffer: &mut [u8]) {
-        self.alg.encrypt_in_place(&self.nonce.finish(), ad, buffer);
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+        self.alg
+            .encrypt_in_place(self.nonce.finish().as_ref(), &ad, buffer);
+    }
+
+    /// Encrypt the final message in-place as with `encrypt_last_in_place`,
+    /// returning a `FinishedEncryptor` recording that the STREAM is complete
+    pub fn finish_in_place(self, ad: &[u8], buffer: &mut [u8]) -> FinishedEncryptor<C::Position> {
+        let position = self.position();
+        self.encrypt_last_in_place(ad, buffer);
+        FinishedEncryptor { position }
     }
 
     /// Encrypt the next message in the stream, allocating and returning a
     /// `Vec<u8>` for the ciphertext
+    ///
+    /// Panics if the STREAM nonce counter would overflow.
     #[cfg(feature = "alloc")]
     pub fn encrypt_next(&mut self, ad: &[u8], plaintext: &[u8]) -> Vec<u8> {
-        let ciphertext = self.alg.encrypt(self.nonce.as_slice(), ad, plaintext);
+        self.try_encrypt_next(ad, plaintext)
+            .expect("STREAM nonce counter overflowed")
+    }
+
+    /// Encrypt the next message in the stream, allocating and returning a
+    /// `Vec<u8>` for the ciphertext, or `Error::CounterOverflow` if the
+    /// STREAM nonce counter would overflow.
+    #[cfg(feature = "alloc")]
+    pub fn try_encrypt_next(&mut self, ad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        check_counter(&self.nonce)?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+        let ciphertext = self.alg.encrypt(self.nonce.as_slice(), &ad, plaintext);
         self.nonce.increment();
-        ciphertext
+        Ok(ciphertext)
     }
 
     /// Encrypt the final message in the stream, allocating and returning a
     /// `Vec<u8>` for the ciphertext
     #[cfg(feature = "alloc")]
     pub fn encrypt_last(mut self, ad: &[u8], plaintext: &[u8]) -> Vec<u8> {
-        self.alg.encrypt(&self.nonce.finish(), ad, plaintext)
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+        self.alg
+            .encrypt(self.nonce.finish().as_ref(), &ad, plaintext)
+    }
+
+    /// Encrypt the final message as with `encrypt_last`, returning the
+    /// ciphertext along with a `FinishedEncryptor` recording that the STREAM
+    /// is complete
+    #[cfg(feature = "alloc")]
+    pub fn finish(self, ad: &[u8], plaintext: &[u8]) -> (Vec<u8>, FinishedEncryptor<C::Position>) {
+        let position = self.position();
+        (
+            self.encrypt_last(ad, plaintext),
+            FinishedEncryptor { position },
+        )
+    }
+
+    /// Encrypt the next message in the stream in-place with no associated
+    /// data
+    ///
+    /// Panics if the STREAM nonce counter would overflow.
+    pub fn encrypt_next_in_place_no_ad(&mut self, buffer: &mut [u8]) {
+        self.encrypt_next_in_place(&[], buffer)
+    }
+
+    /// Encrypt the final message in-place with no associated data, consuming
+    /// the stream encryptor
+    pub fn encrypt_last_in_place_no_ad(self, buffer: &mut [u8]) {
+        self.encrypt_last_in_place(&[], buffer)
+    }
+
+    /// Encrypt the next message in the stream with no associated data,
+    /// allocating and returning a `Vec<u8>` for the ciphertext
+    ///
+    /// Panics if the STREAM nonce counter would overflow.
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_next_no_ad(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        self.encrypt_next(&[], plaintext)
+    }
+
+    /// Encrypt the final message in the stream with no associated data,
+    /// allocating and returning a `Vec<u8>` for the ciphertext
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_last_no_ad(self, plaintext: &[u8]) -> Vec<u8> {
+        self.encrypt_last(&[], plaintext)
+    }
+
+    /// Total length of the ciphertexts produced by `encrypt_chunks` for a
+    /// plaintext of `message_len` bytes, i.e. the plaintext plus the tag of
+    /// each message (of which there is always at least one).
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn ciphertext_len(message_len: usize, chunk_size: usize) -> usize {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        let messages = if message_len == 0 {
+            1
+        } else {
+            (message_len - 1) / chunk_size + 1
+        };
+
+        message_len + messages * A::TAG_OVERHEAD
+    }
+
+    /// Encrypt the given plaintext as the remainder of the STREAM, split into
+    /// messages of `chunk_size` bytes (the last message may be shorter).
+    ///
+    /// Returns an iterator over the ciphertext of each message, the last of
+    /// which is encrypted as the final message in the STREAM. An empty
+    /// plaintext produces a single, empty final message.
+    ///
+    /// Panics if `chunk_size` is zero, or if the STREAM nonce counter
+    /// would overflow.
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_chunks<'a>(
+        self,
+        ad: &'a [u8],
+        plaintext: &'a [u8],
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Vec<u8>> + 'a
+    where
+        A: 'a,
+        C: 'a,
+    {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        let mut encryptor = Some(self);
+        let mut remaining = plaintext;
+
+        iter::from_fn(move || {
+            if remaining.len() > chunk_size {
+                let (chunk, rest) = remaining.split_at(chunk_size);
+                remaining = rest;
+                Some(encryptor.as_mut()?.encrypt_next(ad, chunk))
+            } else {
+                Some(encryptor.take()?.encrypt_last(ad, remaining))
+            }
+        })
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "rayon"))]
+impl<A: Aead, C: Counter> Encryptor<A, C> {
+    /// Encrypt the given plaintext as a complete STREAM on multiple threads
+    /// using rayon, split into messages of `chunk_size` bytes (the last
+    /// message may be shorter).
+    ///
+    /// Every message is encrypted under its own nonce, so they can be
+    /// encrypted independently. The result is identical to the messages
+    /// produced by `Encryptor::new(key, nonce).encrypt_chunks(...)`.
+    ///
+    /// Panics if the key or nonce is the wrong size, if `chunk_size` is
+    /// zero, or if the STREAM nonce counter would overflow.
+    pub fn encrypt_parallel(
+        key: &[u8],
+        nonce: &[u8],
+        ad: &[u8],
+        plaintext: &[u8],
+        chunk_size: usize,
+    ) -> Vec<Vec<u8>> {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        if plaintext.is_empty() {
+            return vec![Self::new(key, nonce).encrypt_last(ad, plaintext)];
+        }
+
+        let last = (plaintext.len() - 1) / chunk_size;
+
+        plaintext
+            .par_chunks(chunk_size)
+            .enumerate()
+            .map_init(
+                || A::new(key),
+                |alg, (i, chunk)| {
+                    let mut counter = C::new(nonce);
+                    let position = u32::try_from(i).expect("STREAM nonce counter overflowed");
+                    counter
+                        .advance(position)
+                        .expect("STREAM nonce counter overflowed");
+
+                    if i == last {
+                        alg.encrypt(counter.finish().as_ref(), ad, chunk)
+                    } else {
+                        check_counter(&counter).expect("STREAM nonce counter overflowed");
+                        alg.encrypt(counter.as_slice(), ad, chunk)
+                    }
+                },
+            )
+            .collect()
+    }
+}
+
+impl<CI, M, C> Encryptor<SivAead<CI, M>, C>
+where
+    CI: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    CI::KeySize: Add,
+    <CI::KeySize as Add>::Output: ArrayLength<u8>,
+    C: Counter,
+{
+    /// Encrypt the next message in the stream in-place, authenticating each
+    /// of the given associated data headers as a separate input to S2V (see
+    /// `SivAead::encrypt_headers_in_place`).
+    ///
+    /// With counter binding, the counter is appended to the last header (or
+    /// is the only header if none are given), so a single header is
+    /// equivalent to the `ad` of `encrypt_next_in_place`.
+    ///
+    /// Panics if the STREAM nonce counter would overflow, or if more than
+    /// `siv::MAX_HEADERS - 1` headers are given.
+    pub fn encrypt_next_headers_in_place(&mut self, headers: &[&[u8]], buffer: &mut [u8]) {
+        check_counter(&self.nonce).expect("STREAM nonce counter overflowed");
+        let alg = &mut self.alg;
+        let nonce = self.nonce.as_slice();
+
+        with_message_headers(&self.nonce, self.bind_counter, headers, |headers| {
+            alg.encrypt_headers_in_place(nonce, headers, buffer)
+        });
+
+        self.nonce.increment();
+    }
+
+    /// Encrypt the final message in-place with multiple associated data
+    /// headers (see `encrypt_next_headers_in_place`), consuming the stream
+    /// encryptor.
+    pub fn encrypt_last_headers_in_place(mut self, headers: &[&[u8]], buffer: &mut [u8]) {
+        let alg = &mut self.alg;
+        let nonce = self.nonce.final_nonce();
+
+        with_message_headers(&self.nonce, self.bind_counter, headers, |headers| {
+            alg.encrypt_headers_in_place(nonce.as_ref(), headers, buffer)
+        });
+    }
+
+    /// Encrypt the next message in the stream with multiple associated data
+    /// headers (see `encrypt_next_headers_in_place`), allocating and
+    /// returning a `Vec<u8>` for the ciphertext
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_next_headers(&mut self, headers: &[&[u8]], plaintext: &[u8]) -> Vec<u8> {
+        let mut buffer = vec![0; IV_SIZE + plaintext.len()];
+        buffer[IV_SIZE..].copy_from_slice(plaintext);
+        self.encrypt_next_headers_in_place(headers, &mut buffer);
+        buffer
+    }
+
+    /// Encrypt the final message with multiple associated data headers (see
+    /// `encrypt_next_headers_in_place`), allocating and returning a
+    /// `Vec<u8>` for the ciphertext
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_last_headers(self, headers: &[&[u8]], plaintext: &[u8]) -> Vec<u8> {
+        let mut buffer = vec![0; IV_SIZE + plaintext.len()];
+        buffer[IV_SIZE..].copy_from_slice(plaintext);
+        self.encrypt_last_headers_in_place(headers, &mut buffer);
+        buffer
+    }
+}
+
+impl<A: Aead, C: Counter> fmt::Debug for Encryptor<A, C>
+where
+    C::Position: fmt::Debug,
+{
+    /// Print the current position, but never the key or nonce
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encryptor")
+            .field("position", &self.nonce.position())
+            .finish()
+    }
+}
+
+impl<A: Aead> Encryptor<A, NonceEncoder32> {
+    /// Number of further messages which can be encrypted with `encrypt_next`
+    /// before the STREAM nonce counter would overflow.
+    ///
+    /// The final message doesn't use a counter value of its own, so it can
+    /// always be encrypted with `encrypt_last`, even when this is zero.
+    pub fn remaining(&self) -> u32 {
+        NonceEncoder32::MAX_POSITION - self.position()
+    }
+}
+
+impl<A: Aead> Encryptor<A, NonceEncoder64> {
+    /// Number of further messages which can be encrypted with `encrypt_next`
+    /// before the STREAM nonce counter would overflow.
+    ///
+    /// The final message doesn't use a counter value of its own, so it can
+    /// always be encrypted with `encrypt_last`, even when this is zero.
+    pub fn remaining(&self) -> u64 {
+        NonceEncoder64::MAX_POSITION - self.position()
+    }
+}
+
+impl<A: Aead, P> Encryptor<A, PrefixedNonceEncoder<P>>
+where
+    P: AsRef<[u8]> + AsMut<[u8]> + Copy + Default,
+{
+    /// Number of further messages which can be encrypted with `encrypt_next`
+    /// before the STREAM nonce counter would overflow.
+    ///
+    /// The final message doesn't use a counter value of its own, so it can
+    /// always be encrypted with `encrypt_last`, even when this is zero.
+    pub fn remaining(&self) -> u32 {
+        PrefixedNonceEncoder::<P>::MAX_POSITION - self.position()
+    }
+}
+
+/// A STREAM encryptor which has encrypted the final message, returned by
+/// `Encryptor::finish` and `Encryptor::finish_in_place`.
+///
+/// An `Encryptor` can only encrypt further messages until it's finished,
+/// after which only this remains, so encrypting a message after the final
+/// one is a compile-time error. Functions which produce a STREAM can return
+/// it to show that the STREAM they wrote is complete (it's `#[must_use]` so
+/// that result isn't silently dropped).
+#[must_use = "a finished STREAM should be acknowledged with `close`"]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FinishedEncryptor<P = u32> {
+    position: P,
+}
+
+impl<P: Copy> FinishedEncryptor<P> {
+    /// Position of the final message in the STREAM, i.e. the number of
+    /// messages before it
+    pub fn position(&self) -> P {
+        self.position
+    }
+
+    /// Acknowledge that the STREAM is complete
+    pub fn close(self) {}
+}
+
+/// Remembers the nonces of recently created STREAM encryptors, and refuses
+/// to create another encryptor with any of them.
+///
+/// This is a best-effort safety net against the most dangerous misuse of
+/// STREAM (reusing a nonce with the same key), not a guarantee: only the
+/// most recent `capacity` nonces are remembered, and nothing is persisted,
+/// so reuse across runs of a program (or across trackers) goes undetected.
+/// Nonces are tracked without regard to the key, so use one tracker per key.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct NonceTracker {
+    used: VecDeque<Vec<u8>>,
+    capacity: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl NonceTracker {
+    /// Create a tracker which remembers (at most) the given number of nonces
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            used: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Create a new STREAM encryptor with the given key and nonce, returning
+    /// `Error::NonceReused` if the nonce is one this tracker remembers.
+    ///
+    /// Returns `Error::InvalidKeySize` or `Error::InvalidNonceSize` if the
+    /// key or nonce is the wrong size (in which case the nonce isn't
+    /// remembered).
+    pub fn new_encryptor<A: Aead, C: Counter>(
+        &mut self,
+        key: &[u8],
+        nonce: &[u8],
+    ) -> Result<Encryptor<A, C>, Error> {
+        if self.contains(nonce) {
+            return Err(Error::NonceReused);
+        }
+
+        let encryptor = Encryptor::try_new(key, nonce)?;
+
+        if self.capacity > 0 {
+            if self.used.len() == self.capacity {
+                self.used.pop_front();
+            }
+
+            self.used.push_back(Vec::from(nonce));
+        }
+
+        Ok(encryptor)
+    }
+
+    /// Has the given nonce been used recently (i.e. is it remembered)?
+    pub fn contains(&self, nonce: &[u8]) -> bool {
+        self.used.iter().any(|used| used[..] == *nonce)
+    }
+
+    /// Number of nonces currently remembered
+    pub fn len(&self) -> usize {
+        self.used.len()
+    }
+
+    /// Are no nonces remembered?
+    pub fn is_empty(&self) -> bool {
+        self.used.is_empty()
     }
 }
 
@@ -80,15 +713,33 @@ impl<A: Aead> Encryptor<A> {
 ///
 /// This corresponds to the 𝒟 stream decryptor object as defined in the paper
 /// Online Authenticated-Encryption and its Nonce-Reuse Misuse-Resistance
-pub struct Decryptor<A: Aead> {
+#[derive(Clone)]
+pub struct Decryptor<A: Aead, C: Counter = NonceEncoder32> {
     alg: A,
-    nonce: NonceEncoder32,
+    nonce: C,
+    bind_counter: bool,
+    finished: bool,
 }
 
+/// A STREAM decryptor with a 64-bit counter, generalized for any AEAD algorithm
+///
+/// Counterpart of [`Encryptor64`], using a 32-bit (4-byte) nonce prefix.
+pub type Decryptor64<A> = Decryptor<A, NonceEncoder64>;
+
+/// A STREAM decryptor with a 96-bit nonce prefix and a 32-bit counter,
+/// generalized for any AEAD algorithm
+///
+/// Counterpart of [`Encryptor96`], using a 96-bit (12-byte) nonce prefix.
+pub type Decryptor96<A> = Decryptor<A, NonceEncoder96>;
+
 /// AES-CMAC-SIV STREAM decryptor with 256-bit key size (128-bit security)
 /// and a 64-bit (8-byte) nonce.
 pub type Aes128SivDecryptor = Decryptor<Aes128SivAead>;
 
+/// AES-CMAC-SIV STREAM decryptor with 384-bit key size (192-bit security)
+/// and a 64-bit (8-byte) nonce.
+pub type Aes192SivDecryptor = Decryptor<Aes192SivAead>;
+
 /// AES-CMAC-SIV STREAM decryptor with 512-bit key size (256-bit security)
 /// and a 64-bit (8-byte) nonce.
 pub type Aes256SivDecryptor = Decryptor<Aes256SivAead>;
@@ -97,76 +748,805 @@ pub type Aes256SivDecryptor = Decryptor<Aes256SivAead>;
 /// and a 64-bit (8-byte) nonce.
 pub type Aes128PmacSivDecryptor = Decryptor<Aes128PmacSivAead>;
 
+/// AES-PMAC-SIV STREAM decryptor with 384-bit key size (192-bit security)
+/// and a 64-bit (8-byte) nonce.
+pub type Aes192PmacSivDecryptor = Decryptor<Aes192PmacSivAead>;
+
 /// AES-PMAC-SIV STREAM decryptor with 512-bit key size (256-bit security)
 /// and a 64-bit (8-byte) nonce.
 pub type Aes256PmacSivDecryptor = Decryptor<Aes256PmacSivAead>;
 
-impl<A: Aead> Decryptor<A> {
+/// AES-CMAC-SIV STREAM decryptor with 256-bit key size (128-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes128SivDecryptor64 = Decryptor64<Aes128SivAead>;
+
+/// AES-CMAC-SIV STREAM decryptor with 384-bit key size (192-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes192SivDecryptor64 = Decryptor64<Aes192SivAead>;
+
+/// AES-CMAC-SIV STREAM decryptor with 512-bit key size (256-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes256SivDecryptor64 = Decryptor64<Aes256SivAead>;
+
+/// AES-PMAC-SIV STREAM decryptor with 256-bit key size (128-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes128PmacSivDecryptor64 = Decryptor64<Aes128PmacSivAead>;
+
+/// AES-PMAC-SIV STREAM decryptor with 384-bit key size (192-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes192PmacSivDecryptor64 = Decryptor64<Aes192PmacSivAead>;
+
+/// AES-PMAC-SIV STREAM decryptor with 512-bit key size (256-bit security),
+/// a 32-bit (4-byte) nonce, and a 64-bit counter.
+pub type Aes256PmacSivDecryptor64 = Decryptor64<Aes256PmacSivAead>;
+
+impl<A: Aead, C: Counter> Decryptor<A, C> {
     /// Create a new STREAM decryptor, initialized with a given key and nonce.
     ///
     /// Panics if the key or nonce is the wrong size.
     pub fn new(key: &[u8], nonce: &[u8]) -> Self {
         Self {
             alg: A::new(key),
-            nonce: NonceEncoder32::new(nonce),
+            nonce: C::new(nonce),
+            bind_counter: false,
+            finished: false,
+        }
+    }
+
+    /// Create a new STREAM decryptor, returning `Error::InvalidKeySize` or
+    /// `Error::InvalidNonceSize` if the key or nonce is the wrong size.
+    pub fn try_new(key: &[u8], nonce: &[u8]) -> Result<Self, Error> {
+        let alg = A::try_new(key)?;
+        check_prefix_size::<C>(nonce)?;
+
+        Ok(Self {
+            alg,
+            nonce: C::new(nonce),
+            bind_counter: false,
+            finished: false,
+        })
+    }
+
+    /// Create a new STREAM decryptor which starts at the given position rather
+    /// than zero, e.g. to begin decrypting at a known message without processing
+    /// the messages before it.
+    ///
+    /// Panics if the key or nonce is the wrong size.
+    pub fn with_counter(key: &[u8], nonce: &[u8], start: C::Position) -> Self {
+        Self {
+            alg: A::new(key),
+            nonce: C::with_position(nonce, start),
+            bind_counter: false,
+            finished: false,
+        }
+    }
+
+    /// Restore a STREAM decryptor from a previously saved state.
+    ///
+    /// The key is not part of the state and must be supplied separately.
+    ///
+    /// Panics if the key or nonce is the wrong size.
+    pub fn restore(key: &[u8], state: StreamState<C::Prefix, C::Position>) -> Self {
+        Self {
+            alg: A::new(key),
+            nonce: C::with_position(state.nonce.as_ref(), state.position),
+            bind_counter: false,
+            finished: false,
+        }
+    }
+
+    /// Replace the key used for subsequent messages, keeping the current
+    /// nonce prefix and position in the STREAM.
+    ///
+    /// Both sides of the STREAM must rekey at the same position.
+    ///
+    /// Panics if the key is the wrong size.
+    pub fn rekey(&mut self, new_key: &[u8]) {
+        self.alg = A::new(new_key);
+    }
+
+    /// Expect the big-endian counter to be appended to the associated data
+    /// of every message, as produced by `Encryptor::with_counter_binding`.
+    ///
+    /// Counter binding isn't part of the saved state, so enable it again
+    /// after `restore`.
+    #[cfg(feature = "alloc")]
+    pub fn with_counter_binding(mut self) -> Self {
+        self.bind_counter = true;
+        self
+    }
+
+    /// Position of the next message in the stream, i.e. the number of
+    /// messages decrypted so far
+    pub fn position(&self) -> C::Position {
+        self.nonce.position()
+    }
+
+    /// Skip the next `n` messages without decrypting them, e.g. to seek
+    /// forward in a STREAM of fixed-size messages.
+    ///
+    /// Returns `Error::CounterOverflow` (without skipping any messages) if
+    /// this would overflow the STREAM nonce counter.
+    pub fn skip(&mut self, n: u32) -> Result<(), Error> {
+        self.nonce.advance(n)
+    }
+
+    /// Save the current state of the STREAM so it can be restored later.
+    ///
+    /// The returned state does not include the key.
+    pub fn save_state(&self) -> StreamState<C::Prefix, C::Position> {
+        StreamState {
+            nonce: self.nonce.prefix(),
+            position: self.nonce.position(),
         }
     }
 
     /// Decrypt the next message in the stream in-place
+    ///
+    /// Returns `Error::UnexpectedLastBlock` if the message is the last one in
+    /// the STREAM, in which case the buffer still contains the ciphertext and
+    /// it can be decrypted with `decrypt_last_in_place`. If the message fails
+    /// to authenticate at all, the buffer is zeroized.
+    ///
+    /// Returns `Error::CounterOverflow` if the STREAM nonce counter would
+    /// overflow.
     pub fn decrypt_next_in_place<'a>(
         &mut self,
         ad: &[u8],
         buffer: &'a mut [u8],
     ) -> Result<&'a [u8], Error> {
-        let result = self
+        self.check_finished()?;
+        check_counter(&self.nonce)?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+
+        if self
             .alg
-            .decrypt_in_place(self.nonce.as_slice(), ad, buffer)?;
+            .decrypt_in_place_restoring(self.nonce.as_slice(), &ad, buffer)
+            .is_err()
+        {
+            return Err(self.check_last_in_place(&ad, buffer));
+        }
+
         self.nonce.increment();
-        Ok(result)
+        Ok(&buffer[A::TagSize::to_usize()..])
     }
 
-    /// Decrypt the final message in-place, consuming the stream decryptor
-    pub fn decrypt_last_in_place<'a>(
-        mut self,
+    /// Decrypt the next message in the stream in-place, automatically
+    /// detecting whether it's the last message in the STREAM.
+    ///
+    /// Returns a boolean which is `true` if the message was the last one,
+    /// along with the decrypted plaintext. Once this returns `true` the
+    /// STREAM is complete, and every later call to decrypt or verify a
+    /// message (including the last one again) returns
+    /// `Error::UnexpectedLastBlock`.
+    ///
+    /// Returns `Error::AuthenticationFailed` if the message authenticates as
+    /// neither the next nor the last message, in which case the buffer is
+    /// zeroized.
+    pub fn decrypt_auto_in_place<'a>(
+        &mut self,
         ad: &[u8],
         buffer: &'a mut [u8],
-    ) -> Result<&'a [u8], Error> {
-        self.alg.decrypt_in_place(&self.nonce.finish(), ad, buffer)
-    }
+    ) -> Result<(bool, &'a [u8]), Error> {
+        self.check_finished()?;
+        let tag_size = A::TagSize::to_usize();
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
 
-    /// Decrypt the next message in the stream, allocating and returning a
-    /// `Vec<u8>` for the plaintext
-    #[cfg(feature = "alloc")]
-    pub fn decrypt_next(&mut self, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
-        let plaintext = self.alg.decrypt(self.nonce.as_slice(), ad, ciphertext)?;
-        self.nonce.increment();
-        Ok(plaintext)
-    }
+        if check_counter(&self.nonce).is_ok()
+            && self
+                .alg
+                .decrypt_in_place_restoring(self.nonce.as_slice(), &ad, buffer)
+                .is_ok()
+        {
+            self.nonce.increment();
+            return Ok((false, &buffer[tag_size..]));
+        }
+
+        self.alg
+            .decrypt_in_place(self.nonce.final_nonce().as_ref(), &ad, buffer)?;
+
+        self.finished = true;
+        Ok((true, &buffer[tag_size..]))
+    }
+
+    /// Check that the next message in the stream authenticates, without
+    /// decrypting it into a buffer, and advance to the following message.
+    ///
+    /// Returns `Error::UnexpectedLastBlock` if the message is the last one in
+    /// the STREAM, in which case it can be checked with `verify_last`.
+    ///
+    /// Returns `Error::CounterOverflow` if the STREAM nonce counter would
+    /// overflow.
+    pub fn verify_next(&mut self, ad: &[u8], ciphertext: &[u8]) -> Result<(), Error> {
+        self.check_finished()?;
+        check_counter(&self.nonce)?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+
+        if self
+            .alg
+            .verify(self.nonce.as_slice(), &ad, ciphertext)
+            .is_err()
+        {
+            let last_nonce = self.nonce.final_nonce();
+
+            return match self.alg.verify(last_nonce.as_ref(), &ad, ciphertext) {
+                Ok(()) => Err(Error::UnexpectedLastBlock),
+                Err(e) => Err(e),
+            };
+        }
+
+        self.nonce.increment();
+        Ok(())
+    }
+
+    /// Check that the final message in the stream authenticates, without
+    /// decrypting it into a buffer, consuming the stream decryptor
+    pub fn verify_last(mut self, ad: &[u8], ciphertext: &[u8]) -> Result<(), Error> {
+        self.check_finished()?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+        self.alg
+            .verify(self.nonce.finish().as_ref(), &ad, ciphertext)
+    }
+
+    /// Check that the given (associated data, ciphertext) pairs are the
+    /// remaining messages of the STREAM, in order, without keeping any
+    /// plaintext, consuming the stream decryptor.
+    ///
+    /// Each message is checked as with `verify_next`, and the last one must
+    /// be the final message in the STREAM, so truncated, reordered, or
+    /// extended STREAMs are all rejected. Returns
+    /// `Error::AuthenticationFailed` if there are no messages, or
+    /// `Error::UnexpectedLastBlock` if more messages follow the final one.
+    pub fn verify_stream<'a, I>(mut self, messages: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (&'a [u8], &'a [u8])>,
+    {
+        let mut messages = messages.into_iter().peekable();
+
+        while let Some((ad, ciphertext)) = messages.next() {
+            if messages.peek().is_none() {
+                return self.verify_last(ad, ciphertext);
+            }
+
+            self.verify_next(ad, ciphertext)?;
+        }
+
+        Err(Error::AuthenticationFailed)
+    }
+
+    /// Decrypt the final message in-place, consuming the stream decryptor
+    pub fn decrypt_last_in_place<'a>(
+        mut self,
+        ad: &[u8],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.check_finished()?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+        self.alg
+            .decrypt_in_place(self.nonce.finish().as_ref(), &ad, buffer)
+    }
+
+    /// Decrypt the next message in the stream, allocating and returning a
+    /// `Vec<u8>` for the plaintext
+    ///
+    /// Returns `Error::UnexpectedLastBlock` if the message is the last one in
+    /// the STREAM, in which case it can be decrypted with `decrypt_last`.
+    ///
+    /// Returns `Error::CounterOverflow` if the STREAM nonce counter would
+    /// overflow.
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_next(&mut self, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        self.check_finished()?;
+        check_counter(&self.nonce)?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+
+        let plaintext = self
+            .alg
+            .decrypt(self.nonce.as_slice(), &ad, ciphertext)
+            .map_err(|_| self.check_last(&ad, ciphertext))?;
+
+        self.nonce.increment();
+        Ok(plaintext)
+    }
+
+    /// Decrypt the next message in the stream as with `decrypt_next`,
+    /// returning the plaintext in a `Zeroizing` wrapper which wipes it
+    /// when dropped
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_next_zeroizing(
+        &mut self,
+        ad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Error> {
+        self.decrypt_next(ad, ciphertext).map(Zeroizing::new)
+    }
+
+    /// Decrypt the next message in the stream, automatically detecting
+    /// whether it's the last message in the STREAM, allocating and returning
+    /// a `Vec<u8>` for the plaintext.
+    ///
+    /// See `decrypt_auto_in_place` for more information.
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_auto(&mut self, ad: &[u8], ciphertext: &[u8]) -> Result<(bool, Vec<u8>), Error> {
+        self.check_finished()?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+
+        if check_counter(&self.nonce).is_ok() {
+            if let Ok(plaintext) = self.alg.decrypt(self.nonce.as_slice(), &ad, ciphertext) {
+                self.nonce.increment();
+                return Ok((false, plaintext));
+            }
+        }
+
+        let plaintext = self
+            .alg
+            .decrypt(self.nonce.final_nonce().as_ref(), &ad, ciphertext)?;
+
+        self.finished = true;
+        Ok((true, plaintext))
+    }
 
     /// Decrypt the next message in the stream, allocating and returning a
     /// `Vec<u8>` for the plaintext
     #[cfg(feature = "alloc")]
     pub fn decrypt_last(mut self, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
-        self.alg.decrypt(&self.nonce.finish(), ad, ciphertext)
+        self.check_finished()?;
+        let ad = message_ad(&self.nonce, self.bind_counter, ad);
+        self.alg
+            .decrypt(self.nonce.finish().as_ref(), &ad, ciphertext)
+    }
+
+    /// Decrypt the next message in the stream in-place with no associated
+    /// data (see `decrypt_next_in_place`)
+    pub fn decrypt_next_in_place_no_ad<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.decrypt_next_in_place(&[], buffer)
+    }
+
+    /// Decrypt the final message in-place with no associated data, consuming
+    /// the stream decryptor
+    pub fn decrypt_last_in_place_no_ad(self, buffer: &mut [u8]) -> Result<&[u8], Error> {
+        self.decrypt_last_in_place(&[], buffer)
+    }
+
+    /// Decrypt the next message in the stream with no associated data,
+    /// allocating and returning a `Vec<u8>` for the plaintext
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_next_no_ad(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        self.decrypt_next(&[], ciphertext)
+    }
+
+    /// Decrypt the final message in the stream with no associated data,
+    /// allocating and returning a `Vec<u8>` for the plaintext
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_last_no_ad(self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        self.decrypt_last(&[], ciphertext)
+    }
+
+    /// Return `Error::UnexpectedLastBlock` if `decrypt_auto` has already
+    /// returned the last message in the STREAM
+    fn check_finished(&self) -> Result<(), Error> {
+        if self.finished {
+            Err(Error::UnexpectedLastBlock)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Determine why a message failed to authenticate as the next message
+    /// in the STREAM, restoring the original ciphertext in the buffer if it
+    /// is the last message, and zeroizing the buffer otherwise.
+    fn check_last_in_place(&mut self, ad: &[u8], buffer: &mut [u8]) -> Error {
+        let last_nonce = self.nonce.final_nonce();
+
+        if self
+            .alg
+            .decrypt_in_place(last_nonce.as_ref(), ad, buffer)
+            .is_ok()
+        {
+            // SIV is deterministic, so re-encrypting restores the ciphertext
+            self.alg.encrypt_in_place(last_nonce.as_ref(), ad, buffer);
+            Error::UnexpectedLastBlock
+        } else {
+            Error::AuthenticationFailed
+        }
+    }
+
+    /// Determine why a message failed to authenticate as the next message
+    /// in the STREAM.
+    #[cfg(feature = "alloc")]
+    fn check_last(&mut self, ad: &[u8], ciphertext: &[u8]) -> Error {
+        let last_nonce = self.nonce.final_nonce();
+
+        match self.alg.decrypt(last_nonce.as_ref(), ad, ciphertext) {
+            Ok(_) => Error::UnexpectedLastBlock,
+            Err(e) => e,
+        }
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "rayon"))]
+impl<A: Aead, C: Counter> Decryptor<A, C> {
+    /// Decrypt the given messages as a complete STREAM on multiple threads
+    /// using rayon, returning the concatenated plaintext.
+    ///
+    /// The messages must be given in order, starting from the first one,
+    /// and the last of them must be the final message in the STREAM.
+    /// Returns `Error::AuthenticationFailed` if any of them fails to
+    /// authenticate at its position (including if there are no messages),
+    /// in which case no plaintext is returned.
+    ///
+    /// Panics if the key or nonce is the wrong size, or if the STREAM nonce
+    /// counter would overflow.
+    pub fn decrypt_parallel(
+        key: &[u8],
+        nonce: &[u8],
+        ad: &[u8],
+        messages: &[&[u8]],
+    ) -> Result<Vec<u8>, Error> {
+        if messages.is_empty() {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let last = messages.len() - 1;
+
+        let mut plaintexts: Vec<Result<Vec<u8>, Error>> = messages
+            .par_iter()
+            .enumerate()
+            .map_init(
+                || A::new(key),
+                |alg, (i, message)| {
+                    let mut counter = C::new(nonce);
+                    let position = u32::try_from(i).expect("STREAM nonce counter overflowed");
+                    counter
+                        .advance(position)
+                        .expect("STREAM nonce counter overflowed");
+
+                    if i == last {
+                        alg.decrypt(counter.finish().as_ref(), ad, message)
+                    } else {
+                        check_counter(&counter).expect("STREAM nonce counter overflowed");
+                        alg.decrypt(counter.as_slice(), ad, message)
+                    }
+                },
+            )
+            .collect();
+
+        if let Some(&Err(e)) = plaintexts.iter().find(|result| result.is_err()) {
+            for plaintext in plaintexts.iter_mut().flatten() {
+                plaintext.zeroize();
+            }
+
+            return Err(e);
+        }
+
+        let len = plaintexts.iter().flatten().map(Vec::len).sum();
+        let mut output = Vec::with_capacity(len);
+
+        for mut plaintext in plaintexts.into_iter().flatten() {
+            output.extend_from_slice(&plaintext);
+            plaintext.zeroize();
+        }
+
+        Ok(output)
+    }
+}
+
+impl<CI, M, C> Decryptor<SivAead<CI, M>, C>
+where
+    CI: NewStreamCipher<NonceSize = U16> + SyncStreamCipher,
+    M: Mac<OutputSize = U16>,
+    CI::KeySize: Add,
+    <CI::KeySize as Add>::Output: ArrayLength<u8>,
+    C: Counter,
+{
+    /// Decrypt the next message in the stream in-place, authenticating it
+    /// against the same associated data headers it was encrypted with (see
+    /// `Encryptor::encrypt_next_headers_in_place`).
+    ///
+    /// Errors are as with `decrypt_next_in_place`: in particular, the buffer
+    /// still contains the ciphertext after `Error::UnexpectedLastBlock`, and
+    /// after `Error::AuthenticationFailed` for too many headers (see
+    /// `SivAead::decrypt_headers_in_place_restoring`).
+    pub fn decrypt_next_headers_in_place<'a>(
+        &mut self,
+        headers: &[&[u8]],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.check_finished()?;
+        check_counter(&self.nonce)?;
+        let alg = &mut self.alg;
+        let nonce = self.nonce.as_slice();
+        let last_nonce = self.nonce.final_nonce();
+
+        with_message_headers(&self.nonce, self.bind_counter, headers, |headers| {
+            // Fail before the last block retry, which would zeroize the buffer
+            check_headers_len(headers.len())?;
+
+            if alg
+                .decrypt_headers_in_place_restoring(nonce, headers, buffer)
+                .is_ok()
+            {
+                return Ok(());
+            }
+
+            if alg
+                .decrypt_headers_in_place(last_nonce.as_ref(), headers, buffer)
+                .is_ok()
+            {
+                // SIV is deterministic, so re-encrypting restores the ciphertext
+                alg.encrypt_headers_in_place(last_nonce.as_ref(), headers, buffer);
+                Err(Error::UnexpectedLastBlock)
+            } else {
+                Err(Error::AuthenticationFailed)
+            }
+        })?;
+
+        self.nonce.increment();
+        Ok(&buffer[IV_SIZE..])
+    }
+
+    /// Decrypt the final message in-place with multiple associated data
+    /// headers (see `decrypt_next_headers_in_place`), consuming the stream
+    /// decryptor
+    pub fn decrypt_last_headers_in_place<'a>(
+        mut self,
+        headers: &[&[u8]],
+        buffer: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.check_finished()?;
+        let alg = &mut self.alg;
+        let nonce = self.nonce.final_nonce();
+
+        with_message_headers(&self.nonce, self.bind_counter, headers, |headers| {
+            alg.decrypt_headers_in_place(nonce.as_ref(), headers, buffer)
+                .map(|_| ())
+        })?;
+
+        Ok(&buffer[IV_SIZE..])
+    }
+
+    /// Decrypt the next message in the stream with multiple associated data
+    /// headers (see `decrypt_next_headers_in_place`), allocating and
+    /// returning a `Vec<u8>` for the plaintext
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_next_headers(
+        &mut self,
+        headers: &[&[u8]],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::from(ciphertext);
+        self.decrypt_next_headers_in_place(headers, &mut buffer)?;
+        buffer.drain(..IV_SIZE);
+        Ok(buffer)
+    }
+
+    /// Decrypt the final message with multiple associated data headers (see
+    /// `decrypt_next_headers_in_place`), allocating and returning a
+    /// `Vec<u8>` for the plaintext
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_last_headers(
+        self,
+        headers: &[&[u8]],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::from(ciphertext);
+        self.decrypt_last_headers_in_place(headers, &mut buffer)?;
+        buffer.drain(..IV_SIZE);
+        Ok(buffer)
+    }
+}
+
+impl<A: Aead, C: Counter> fmt::Debug for Decryptor<A, C>
+where
+    C::Position: fmt::Debug,
+{
+    /// Print the current position, but never the key or nonce
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Decryptor")
+            .field("position", &self.nonce.position())
+            .finish()
+    }
+}
+
+/// Counters which encode the current position in a STREAM as a nonce.
+///
+/// Implement this trait to use a custom nonce layout (e.g. to match an
+/// existing wire format) while reusing the `Encryptor` and `Decryptor`.
+pub trait Counter: Sized {
+    /// Nonce type returned by `finish`, with the last block flag set
+    type Nonce: AsRef<[u8]>;
+
+    /// Integer type of the counter
+    type Position: Copy + PartialEq;
+
+    /// Maximum counter value. Only the last message may be encrypted at
+    /// this position, as incrementing the counter past it would overflow.
+    const MAX_POSITION: Self::Position;
+
+    /// Nonce prefix type, i.e. the nonce the STREAM was initialized with
+    type Prefix: AsRef<[u8]> + AsMut<[u8]> + Copy + Default;
+
+    /// Create a new counter, initialized with the given nonce prefix.
+    ///
+    /// Panics if the nonce prefix is the wrong size.
+    fn new(prefix: &[u8]) -> Self;
+
+    /// Create a counter initialized with the given nonce prefix, starting
+    /// at the given position in the STREAM.
+    ///
+    /// Panics if the nonce prefix is the wrong size.
+    fn with_position(prefix: &[u8], position: Self::Position) -> Self;
+
+    /// Nonce prefix this counter was initialized with
+    fn prefix(&self) -> Self::Prefix;
+
+    /// Increment the nonce value in-place
+    ///
+    /// Panics if the counter overflows, i.e. is already at `MAX_POSITION`.
+    fn increment(&mut self);
+
+    /// Advance the counter by `n` positions, as if by calling `increment`
+    /// `n` times, returning `Error::CounterOverflow` (and leaving the counter
+    /// unchanged) if this would go past `MAX_POSITION`.
+    ///
+    /// The provided implementation calls `increment` repeatedly. The
+    /// built-in counters advance in constant time.
+    fn advance(&mut self, n: u32) -> Result<(), Error> {
+        let start = self.position();
+
+        for _ in 0..n {
+            if self.position() == Self::MAX_POSITION {
+                *self = Self::with_position(self.prefix().as_ref(), start);
+                return Err(Error::CounterOverflow);
+            }
+
+            self.increment();
+        }
+
+        Ok(())
+    }
+
+    /// Borrow the current value as a slice
+    fn as_slice(&self) -> &[u8];
+
+    /// Current counter value, i.e. the position of the next message
+    fn position(&self) -> Self::Position;
+
+    /// Encoding of the current position appended to the associated data
+    /// when counter binding is enabled.
+    ///
+    /// Defaults to the current nonce, which encodes the position. The
+    /// built-in counters return just the big-endian counter.
+    fn position_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+
+    /// Compute the nonce value for the last message at the current position,
+    /// without modifying the counter.
+    fn final_nonce(&self) -> Self::Nonce;
+
+    /// Compute the final nonce value, consuming self and returning the final
+    /// nonce value.
+    fn finish(self) -> Self::Nonce {
+        self.final_nonce()
+    }
+}
+
+/// Ensure the given nonce prefix is the right size for the counter
+fn check_prefix_size<C: Counter>(prefix: &[u8]) -> Result<(), Error> {
+    let expected = C::Prefix::default().as_ref().len();
+
+    if prefix.len() != expected {
+        return Err(Error::InvalidNonceSize {
+            expected,
+            got: prefix.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Associated data to authenticate a message with: `ad` itself, or with
+/// counter binding enabled, `ad` followed by the counter's position bytes
+#[cfg(feature = "alloc")]
+fn message_ad<'a, C: Counter>(counter: &C, bind_counter: bool, ad: &'a [u8]) -> Cow<'a, [u8]> {
+    if !bind_counter {
+        return Cow::Borrowed(ad);
+    }
+
+    let position = counter.position_bytes();
+    let mut bound = Vec::with_capacity(ad.len() + position.len());
+    bound.extend_from_slice(ad);
+    bound.extend_from_slice(position);
+    Cow::Owned(bound)
+}
+
+/// Counter binding requires `alloc`, so the associated data is used as-is
+#[cfg(not(feature = "alloc"))]
+fn message_ad<'a, C: Counter>(_counter: &C, _bind_counter: bool, ad: &'a [u8]) -> &'a [u8] {
+    ad
+}
+
+/// Call `f` with the associated data headers to authenticate a message
+/// with: `headers` themselves, or with counter binding enabled, `headers`
+/// with the counter's position bytes appended to the last of them
+#[cfg(feature = "alloc")]
+fn with_message_headers<C: Counter, R>(
+    counter: &C,
+    bind_counter: bool,
+    headers: &[&[u8]],
+    f: impl FnOnce(&[&[u8]]) -> R,
+) -> R {
+    if !bind_counter {
+        return f(headers);
+    }
+
+    let (last, init): (&[u8], &[&[u8]]) = match headers.split_last() {
+        Some((last, init)) => (last, init),
+        None => (&[], &[]),
+    };
+    let bound = message_ad(counter, true, last);
+    let mut bound_headers = Vec::with_capacity(headers.len().max(1));
+    bound_headers.extend_from_slice(init);
+    bound_headers.push(&*bound);
+    f(&bound_headers)
+}
+
+/// Counter binding requires `alloc`, so the headers are used as-is
+#[cfg(not(feature = "alloc"))]
+fn with_message_headers<C: Counter, R>(
+    _counter: &C,
+    _bind_counter: bool,
+    headers: &[&[u8]],
+    f: impl FnOnce(&[&[u8]]) -> R,
+) -> R {
+    f(headers)
+}
+
+/// Ensure the counter can be incremented after processing a message
+fn check_counter<C: Counter>(counter: &C) -> Result<(), Error> {
+    if counter.position() == C::MAX_POSITION {
+        Err(Error::CounterOverflow)
+    } else {
+        Ok(())
     }
 }
 
 /// STREAM nonce including space for 32-bit counter and 1-byte last block flag
-type StreamNonce = [u8; NONCE_SIZE + 4 + 1];
+pub type StreamNonce = [u8; NONCE_SIZE + 4 + 1];
 
 /// Computes STREAM nonces based on the current position in the STREAM.
 ///
 /// Accepts a 64-bit nonce and uses a 32-bit counter internally.
 ///
 /// Panics if the nonce size is incorrect, 32-bit counter overflows
-struct NonceEncoder32 {
+#[derive(Clone)]
+pub struct NonceEncoder32 {
     value: StreamNonce,
     counter: u32,
 }
 
-impl NonceEncoder32 {
-    /// Create a new nonce encoder object
+impl Drop for NonceEncoder32 {
+    fn drop(&mut self) {
+        self.value.zeroize();
+        self.counter.zeroize();
+    }
+}
+
+impl Counter for NonceEncoder32 {
+    type Nonce = StreamNonce;
+    type Position = u32;
+    const MAX_POSITION: u32 = !0;
+    type Prefix = [u8; NONCE_SIZE];
+
     fn new(prefix: &[u8]) -> Self {
+        Self::with_position(prefix, 0)
+    }
+
+    fn with_position(prefix: &[u8], position: u32) -> Self {
         if prefix.len() != NONCE_SIZE {
             panic!(
                 "incorrect nonce size (expected {}, got {})",
@@ -177,15 +1557,21 @@ impl NonceEncoder32 {
 
         let mut result = Self {
             value: Default::default(),
-            counter: 0,
+            counter: position,
         };
 
         result.value[..NONCE_SIZE].copy_from_slice(prefix);
+        result.value[NONCE_SIZE..(NONCE_SIZE + 4)].copy_from_slice(&position.to_be_bytes());
         result
     }
 
-    /// Increment the nonce value in-place
-    pub fn increment(&mut self) {
+    fn prefix(&self) -> Self::Prefix {
+        let mut prefix = Self::Prefix::default();
+        prefix.copy_from_slice(&self.value[..NONCE_SIZE]);
+        prefix
+    }
+
+    fn increment(&mut self) {
         self.counter = self
             .counter
             .checked_add(1)
@@ -194,15 +1580,277 @@ impl NonceEncoder32 {
         self.value[NONCE_SIZE..(NONCE_SIZE + 4)].copy_from_slice(&self.counter.to_be_bytes());
     }
 
-    /// Borrow the current value as a slice
-    pub fn as_slice(&self) -> &[u8] {
+    fn advance(&mut self, n: u32) -> Result<(), Error> {
+        self.counter = self.counter.checked_add(n).ok_or(Error::CounterOverflow)?;
+
+        self.value[NONCE_SIZE..(NONCE_SIZE + 4)].copy_from_slice(&self.counter.to_be_bytes());
+        Ok(())
+    }
+
+    fn as_slice(&self) -> &[u8] {
         &self.value
     }
 
-    /// Compute the final nonce value, consuming self and returning the final
-    /// nonce value.
-    pub fn finish(mut self) -> StreamNonce {
-        *self.value.iter_mut().last().unwrap() = LAST_BLOCK_FLAG;
-        self.value
+    fn position(&self) -> Self::Position {
+        self.counter
+    }
+
+    fn position_bytes(&self) -> &[u8] {
+        &self.value[NONCE_SIZE..(NONCE_SIZE + 4)]
+    }
+
+    fn final_nonce(&self) -> StreamNonce {
+        let mut value = self.value;
+        *value.iter_mut().last().unwrap() = LAST_BLOCK_FLAG;
+        value
+    }
+}
+
+/// STREAM nonce including space for 64-bit counter and 1-byte last block flag
+pub type StreamNonce64 = [u8; NONCE_SIZE_64 + 8 + 1];
+
+/// Computes STREAM nonces based on the current position in the STREAM.
+///
+/// Accepts a 32-bit nonce and uses a 64-bit counter internally.
+///
+/// Panics if the nonce size is incorrect, 64-bit counter overflows
+#[derive(Clone)]
+pub struct NonceEncoder64 {
+    value: StreamNonce64,
+    counter: u64,
+}
+
+impl Drop for NonceEncoder64 {
+    fn drop(&mut self) {
+        self.value.zeroize();
+        self.counter.zeroize();
+    }
+}
+
+impl Counter for NonceEncoder64 {
+    type Nonce = StreamNonce64;
+    type Position = u64;
+    const MAX_POSITION: u64 = !0;
+    type Prefix = [u8; NONCE_SIZE_64];
+
+    fn new(prefix: &[u8]) -> Self {
+        Self::with_position(prefix, 0)
+    }
+
+    fn with_position(prefix: &[u8], position: u64) -> Self {
+        if prefix.len() != NONCE_SIZE_64 {
+            panic!(
+                "incorrect nonce size (expected {}, got {})",
+                NONCE_SIZE_64,
+                prefix.len()
+            );
+        }
+
+        let mut result = Self {
+            value: Default::default(),
+            counter: position,
+        };
+
+        result.value[..NONCE_SIZE_64].copy_from_slice(prefix);
+        result.value[NONCE_SIZE_64..(NONCE_SIZE_64 + 8)].copy_from_slice(&position.to_be_bytes());
+        result
+    }
+
+    fn prefix(&self) -> Self::Prefix {
+        let mut prefix = Self::Prefix::default();
+        prefix.copy_from_slice(&self.value[..NONCE_SIZE_64]);
+        prefix
+    }
+
+    fn increment(&mut self) {
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .expect("STREAM nonce counter overflowed");
+
+        self.value[NONCE_SIZE_64..(NONCE_SIZE_64 + 8)].copy_from_slice(&self.counter.to_be_bytes());
+    }
+
+    fn advance(&mut self, n: u32) -> Result<(), Error> {
+        self.counter = self
+            .counter
+            .checked_add(u64::from(n))
+            .ok_or(Error::CounterOverflow)?;
+
+        self.value[NONCE_SIZE_64..(NONCE_SIZE_64 + 8)].copy_from_slice(&self.counter.to_be_bytes());
+        Ok(())
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.value
+    }
+
+    fn position(&self) -> Self::Position {
+        self.counter
+    }
+
+    fn position_bytes(&self) -> &[u8] {
+        &self.value[NONCE_SIZE_64..(NONCE_SIZE_64 + 8)]
+    }
+
+    fn final_nonce(&self) -> StreamNonce64 {
+        let mut value = self.value;
+        *value.iter_mut().last().unwrap() = LAST_BLOCK_FLAG;
+        value
+    }
+}
+
+/// Largest nonce prefix supported by `PrefixedNonceEncoder` in bytes
+pub const MAX_PREFIX_SIZE: usize = 16;
+
+/// STREAM nonce computed by a `PrefixedNonceEncoder`, with space for the
+/// largest supported prefix, a 32-bit counter, and a 1-byte last block flag
+#[derive(Copy, Clone)]
+pub struct PrefixedNonce {
+    value: [u8; MAX_PREFIX_SIZE + 4 + 1],
+    len: usize,
+}
+
+impl AsRef<[u8]> for PrefixedNonce {
+    fn as_ref(&self) -> &[u8] {
+        &self.value[..self.len]
+    }
+}
+
+/// Computes STREAM nonces based on the current position in the STREAM.
+///
+/// Accepts a nonce prefix of any size up to `MAX_PREFIX_SIZE` bytes, which
+/// is given by the prefix type `P` (e.g. `[u8; 12]`), and uses a 32-bit
+/// counter internally. A wider prefix makes collisions between randomly
+/// generated nonces less likely, in exchange for longer nonces.
+///
+/// Panics if the nonce size is incorrect, 32-bit counter overflows
+pub struct PrefixedNonceEncoder<P = [u8; NONCE_SIZE_96]> {
+    value: PrefixedNonce,
+    counter: u32,
+    prefix: PhantomData<P>,
+}
+
+/// Computes STREAM nonces from a 96-bit nonce prefix and a 32-bit counter,
+/// i.e. a 128-bit nonce (plus the last block flag)
+pub type NonceEncoder96 = PrefixedNonceEncoder<[u8; NONCE_SIZE_96]>;
+
+impl<P> PrefixedNonceEncoder<P>
+where
+    P: AsRef<[u8]> + AsMut<[u8]> + Copy + Default,
+{
+    /// Size of the nonce prefix in bytes
+    fn prefix_len() -> usize {
+        P::default().as_ref().len()
+    }
+
+    /// Counter bytes within the nonce
+    fn counter_range() -> core::ops::Range<usize> {
+        Self::prefix_len()..(Self::prefix_len() + 4)
+    }
+}
+
+impl<P> Clone for PrefixedNonceEncoder<P> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value,
+            counter: self.counter,
+            prefix: PhantomData,
+        }
+    }
+}
+
+impl<P> Drop for PrefixedNonceEncoder<P> {
+    fn drop(&mut self) {
+        self.value.value.zeroize();
+        self.counter.zeroize();
+    }
+}
+
+impl<P> Counter for PrefixedNonceEncoder<P>
+where
+    P: AsRef<[u8]> + AsMut<[u8]> + Copy + Default,
+{
+    type Nonce = PrefixedNonce;
+    type Position = u32;
+    const MAX_POSITION: u32 = !0;
+    type Prefix = P;
+
+    fn new(prefix: &[u8]) -> Self {
+        Self::with_position(prefix, 0)
+    }
+
+    fn with_position(prefix: &[u8], position: u32) -> Self {
+        let prefix_len = Self::prefix_len();
+
+        assert!(
+            prefix_len <= MAX_PREFIX_SIZE,
+            "nonce prefix too large (maximum {}, got {})",
+            MAX_PREFIX_SIZE,
+            prefix_len
+        );
+
+        if prefix.len() != prefix_len {
+            panic!(
+                "incorrect nonce size (expected {}, got {})",
+                prefix_len,
+                prefix.len()
+            );
+        }
+
+        let mut result = Self {
+            value: PrefixedNonce {
+                value: Default::default(),
+                len: prefix_len + 4 + 1,
+            },
+            counter: position,
+            prefix: PhantomData,
+        };
+
+        result.value.value[..prefix_len].copy_from_slice(prefix);
+        result.value.value[Self::counter_range()].copy_from_slice(&position.to_be_bytes());
+        result
+    }
+
+    fn prefix(&self) -> P {
+        let mut prefix = P::default();
+        prefix
+            .as_mut()
+            .copy_from_slice(&self.value.value[..Self::prefix_len()]);
+        prefix
+    }
+
+    fn increment(&mut self) {
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .expect("STREAM nonce counter overflowed");
+
+        self.value.value[Self::counter_range()].copy_from_slice(&self.counter.to_be_bytes());
+    }
+
+    fn advance(&mut self, n: u32) -> Result<(), Error> {
+        self.counter = self.counter.checked_add(n).ok_or(Error::CounterOverflow)?;
+
+        self.value.value[Self::counter_range()].copy_from_slice(&self.counter.to_be_bytes());
+        Ok(())
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        self.value.as_ref()
+    }
+
+    fn position(&self) -> Self::Position {
+        self.counter
+    }
+
+    fn position_bytes(&self) -> &[u8] {
+        &self.value.value[Self::counter_range()]
+    }
+
+    fn final_nonce(&self) -> PrefixedNonce {
+        let mut value = self.value;
+        value.value[value.len - 1] = LAST_BLOCK_FLAG;
+        value
     }
 }
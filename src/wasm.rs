@@ -0,0 +1,141 @@
+//! `wasm.rs`: WebAssembly bindings (via `wasm-bindgen`) which mirror the
+//! `AEAD` API of the JavaScript Miscreant library
+//!
+//! ```js
+//! const aead = AEAD.importKey(key, "AES-PMAC-SIV");
+//! const ciphertext = aead.seal(plaintext, nonce, [header1, header2]);
+//! const plaintext = aead.open(ciphertext, nonce, [header1, header2]);
+//! aead.free();
+//! ```
+//!
+//! Unlike the JavaScript library these methods are synchronous (there's no
+//! WebCrypto call to wait for), so they return values rather than promises.
+//! Errors (e.g. an invalid key size or a ciphertext which fails to
+//! authenticate) are thrown as JavaScript exceptions.
+//!
+//! The associated data may be given as a single `Uint8Array` (as in the
+//! JavaScript library), or as an array of them, each of which is
+//! authenticated as a separate S2V header. If it's omitted, an empty
+//! `Uint8Array` is used.
+
+// The code generated by `wasm_bindgen` uses newer features than the crate's
+// MSRV, but the `wasm` feature requires a newer compiler anyway
+#![allow(clippy::incompatible_msrv)]
+
+use crate::{Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead, AssociatedData};
+use crate::{Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead};
+use js_sys::{Array, Uint8Array};
+use std::vec::Vec;
+use wasm_bindgen::{prelude::*, JsCast};
+
+/// AES-SIV or AES-PMAC-SIV instance exported to JavaScript as `AEAD`
+#[wasm_bindgen(js_name = AEAD)]
+pub struct WasmAead {
+    inner: Inner,
+}
+
+/// Algorithm and key size selected when the key was imported
+#[allow(clippy::large_enum_variant)]
+enum Inner {
+    Aes128(Aes128SivAead),
+    Aes192(Aes192SivAead),
+    Aes256(Aes256SivAead),
+    Aes128Pmac(Aes128PmacSivAead),
+    Aes192Pmac(Aes192PmacSivAead),
+    Aes256Pmac(Aes256PmacSivAead),
+}
+
+/// Call the same method on whichever algorithm an `Inner` holds
+macro_rules! dispatch {
+    ($inner:expr, $aead:ident => $body:expr) => {
+        match $inner {
+            Inner::Aes128($aead) => $body,
+            Inner::Aes192($aead) => $body,
+            Inner::Aes256($aead) => $body,
+            Inner::Aes128Pmac($aead) => $body,
+            Inner::Aes192Pmac($aead) => $body,
+            Inner::Aes256Pmac($aead) => $body,
+        }
+    };
+}
+
+#[wasm_bindgen(js_class = AEAD)]
+impl WasmAead {
+    /// Create a new instance from the given key for the given algorithm
+    /// (`"AES-SIV"` or `"AES-PMAC-SIV"`). The key size (32, 48, or 64
+    /// bytes) selects AES-128, AES-192, or AES-256.
+    #[wasm_bindgen(js_name = importKey)]
+    pub fn import_key(key_data: &[u8], alg: &str) -> Result<WasmAead, JsError> {
+        let inner = match (alg, key_data.len()) {
+            ("AES-SIV", 48) => Inner::Aes192(Aes192SivAead::try_new(key_data)?),
+            ("AES-SIV", 64) => Inner::Aes256(Aes256SivAead::try_new(key_data)?),
+            ("AES-SIV", _) => Inner::Aes128(Aes128SivAead::try_new(key_data)?),
+            ("AES-PMAC-SIV", 48) => Inner::Aes192Pmac(Aes192PmacSivAead::try_new(key_data)?),
+            ("AES-PMAC-SIV", 64) => Inner::Aes256Pmac(Aes256PmacSivAead::try_new(key_data)?),
+            ("AES-PMAC-SIV", _) => Inner::Aes128Pmac(Aes128PmacSivAead::try_new(key_data)?),
+            _ => return Err(JsError::new(&format!("unsupported algorithm: {}", alg))),
+        };
+
+        Ok(Self { inner })
+    }
+
+    /// Encrypt and authenticate the given plaintext, returning the SIV tag
+    /// followed by the ciphertext
+    pub fn seal(
+        &mut self,
+        plaintext: &[u8],
+        nonce: &[u8],
+        associated_data: JsValue,
+    ) -> Result<Vec<u8>, JsError> {
+        let headers = headers(associated_data)?;
+        let headers: Vec<&[u8]> = headers.iter().map(Vec::as_slice).collect();
+
+        Ok(dispatch!(&mut self.inner, aead => aead.encrypt_headers(nonce, &headers, plaintext)))
+    }
+
+    /// Decrypt and authenticate the given ciphertext, throwing an exception
+    /// if it isn't authentic
+    pub fn open(
+        &mut self,
+        ciphertext: &[u8],
+        nonce: &[u8],
+        associated_data: JsValue,
+    ) -> Result<Vec<u8>, JsError> {
+        let headers = headers(associated_data)?;
+        let headers: Vec<&[u8]> = headers.iter().map(Vec::as_slice).collect();
+
+        Ok(dispatch!(&mut self.inner, aead => aead.decrypt_headers(nonce, &headers, ciphertext))?)
+    }
+}
+
+/// Copy the associated data passed from JavaScript into a list of headers
+fn headers(associated_data: JsValue) -> Result<Vec<Vec<u8>>, JsError> {
+    if associated_data.is_undefined() {
+        return Ok(vec![Vec::new()]);
+    }
+
+    if let Some(header) = associated_data.dyn_ref::<Uint8Array>() {
+        return Ok(vec![header.to_vec()]);
+    }
+
+    let items = associated_data
+        .dyn_into::<Array>()
+        .map_err(|_| invalid_associated_data())?;
+
+    if items.length() as usize > AssociatedData::MAX_ITEMS {
+        return Err(JsError::new("too many associated data items"));
+    }
+
+    items
+        .iter()
+        .map(|item| {
+            item.dyn_into::<Uint8Array>()
+                .map(|header| header.to_vec())
+                .map_err(|_| invalid_associated_data())
+        })
+        .collect()
+}
+
+fn invalid_associated_data() -> JsError {
+    JsError::new("associated data must be a Uint8Array or an array of them")
+}
@@ -1,7 +1,12 @@
 mod aead_vectors;
 
 use self::aead_vectors::AesSivAeadExample;
-use miscreant::{Aead, Aes128PmacSivAead, Aes128SivAead, Aes256PmacSivAead, Aes256SivAead};
+use core::{convert::TryFrom, mem};
+use miscreant::{
+    reencrypt, Aead, Aes128PmacSivAead, Aes128SivAead, Aes192PmacSivAead, Aes192SivAead,
+    Aes256PmacSivAead, Aes256SivAead, AssociatedData, Error, Key, Nonce, Tag, TagPosition,
+    Zeroizing, MAX_MESSAGE_LEN, MIN_TRUNCATED_TAG_SIZE,
+};
 
 #[test]
 fn aes_siv_aead_examples_encrypt() {
@@ -15,6 +20,11 @@ fn aes_siv_aead_examples_encrypt() {
                     &example.ad,
                     &example.plaintext,
                 ),
+                48 => Aes192SivAead::new(&example.key).encrypt(
+                    &example.nonce,
+                    &example.ad,
+                    &example.plaintext,
+                ),
                 64 => Aes256SivAead::new(&example.key).encrypt(
                     &example.nonce,
                     &example.ad,
@@ -28,6 +38,11 @@ fn aes_siv_aead_examples_encrypt() {
                     &example.ad,
                     &example.plaintext,
                 ),
+                48 => Aes192PmacSivAead::new(&example.key).encrypt(
+                    &example.nonce,
+                    &example.ad,
+                    &example.plaintext,
+                ),
                 64 => Aes256PmacSivAead::new(&example.key).encrypt(
                     &example.nonce,
                     &example.ad,
@@ -54,6 +69,11 @@ fn aes_siv_aead_examples_decrypt() {
                     &example.ad,
                     &example.ciphertext,
                 ),
+                48 => Aes192SivAead::new(&example.key).decrypt(
+                    &example.nonce,
+                    &example.ad,
+                    &example.ciphertext,
+                ),
                 64 => Aes256SivAead::new(&example.key).decrypt(
                     &example.nonce,
                     &example.ad,
@@ -67,6 +87,11 @@ fn aes_siv_aead_examples_decrypt() {
                     &example.ad,
                     &example.ciphertext,
                 ),
+                48 => Aes192PmacSivAead::new(&example.key).decrypt(
+                    &example.nonce,
+                    &example.ad,
+                    &example.ciphertext,
+                ),
                 64 => Aes256PmacSivAead::new(&example.key).decrypt(
                     &example.nonce,
                     &example.ad,
@@ -81,3 +106,1222 @@ fn aes_siv_aead_examples_decrypt() {
         assert_eq!(plaintext, example.plaintext);
     }
 }
+
+#[test]
+fn aes_siv_aead_debug_redacts_key() {
+    let aead = Aes128SivAead::new(&[0x42; 32]);
+    assert_eq!(format!("{:?}", aead), "SivAead { .. }");
+}
+
+#[test]
+fn aes_siv_aead_multiple_headers() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let nonce = [0x24; 16];
+
+    let ciphertext = aead.encrypt_headers(&nonce, &[b"header a", b"header b"], b"plaintext");
+    assert_eq!(
+        aead.decrypt_headers(&nonce, &[b"header a", b"header b"], &ciphertext)
+            .unwrap(),
+        b"plaintext"
+    );
+
+    // Each header is a distinct S2V input, unlike their concatenation
+    let concatenated = aead.encrypt(&nonce, b"header aheader b", b"plaintext");
+    assert_ne!(ciphertext[..16], concatenated[..16]);
+    assert!(aead
+        .decrypt_headers(&nonce, &[b"header aheader b"], &ciphertext)
+        .is_err());
+
+    // A single header is equivalent to `encrypt`
+    assert_eq!(
+        aead.encrypt_headers(&nonce, &[b"header"], b"plaintext"),
+        aead.encrypt(&nonce, b"header", b"plaintext")
+    );
+}
+
+#[test]
+fn aes_siv_aead_detached() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let nonce = [0x24; 16];
+
+    let mut buffer = *b"plaintext";
+    let tag = aead.encrypt_in_place_detached(&nonce, b"ad", &mut buffer);
+
+    // The detached tag and ciphertext match the attached encoding
+    let attached = aead.encrypt(&nonce, b"ad", b"plaintext");
+    assert_eq!(tag.as_ref(), &attached[..16]);
+    assert_eq!(buffer, attached[16..]);
+
+    aead.decrypt_in_place_detached(&nonce, b"ad", &mut buffer, &tag)
+        .unwrap();
+    assert_eq!(&buffer, b"plaintext");
+
+    let ciphertext = attached[16..].to_vec();
+    let mut buffer = ciphertext.clone();
+    let mut bad_tag = tag.to_bytes();
+    bad_tag[0] ^= 1;
+    assert_eq!(
+        aead.decrypt_in_place_detached(&nonce, b"ad", &mut buffer, &Tag::new(bad_tag)),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(buffer, [0u8; 9]);
+}
+
+#[test]
+fn aes_siv_aead_failure_zeroizes_buffer() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let nonce = [0x24; 16];
+
+    let mut ciphertext = *b"a message of some length";
+    let tag = aead.encrypt_in_place_detached(&nonce, b"ad", &mut ciphertext);
+    ciphertext[3] ^= 1;
+
+    let mut buffer = ciphertext;
+    assert_eq!(
+        aead.decrypt_in_place_detached(&nonce, b"ad", &mut buffer, &tag),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(buffer, [0u8; 24]);
+
+    // Wrong associated data also zeroizes the buffer
+    ciphertext[3] ^= 1;
+    let mut buffer = ciphertext;
+    assert!(aead
+        .decrypt_in_place_detached(&nonce, b"other", &mut buffer, &tag)
+        .is_err());
+    assert_eq!(buffer, [0u8; 24]);
+
+    // The attached forms zeroize the tag along with the message
+    let sealed = aead.encrypt(&nonce, b"ad", b"plaintext");
+
+    let mut buffer = sealed.clone();
+    assert!(aead
+        .decrypt_in_place(&nonce, b"other", &mut buffer)
+        .is_err());
+    assert_eq!(buffer, [0u8; 25]);
+
+    let mut buffer = aead.seal_deterministic(b"ad", b"plaintext");
+    assert!(aead
+        .open_deterministic_in_place(b"other", &mut buffer)
+        .is_err());
+    assert_eq!(buffer, [0u8; 25]);
+
+    let mut buffer =
+        aead.encrypt_with_tag_position(&nonce, b"ad", b"plaintext", TagPosition::Suffix);
+    assert!(aead
+        .decrypt_in_place_with_tag_position(&nonce, b"other", &mut buffer, TagPosition::Suffix)
+        .is_err());
+    assert_eq!(buffer, [0u8; 25]);
+
+    // Unless the ciphertext is explicitly restored instead
+    let mut buffer = sealed.clone();
+    assert_eq!(
+        aead.decrypt_in_place_restoring(&nonce, b"other", &mut buffer),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(buffer, sealed);
+    assert_eq!(
+        aead.decrypt_in_place_restoring(&nonce, b"ad", &mut buffer)
+            .unwrap(),
+        b"plaintext"
+    );
+}
+
+#[test]
+fn aes_siv_aead_to_slice() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let nonce = [0x24; 16];
+
+    let mut ciphertext = [0u8; 64];
+    let len = aead
+        .encrypt_to_slice(&nonce, b"ad", b"plaintext", &mut ciphertext)
+        .unwrap();
+    assert_eq!(len, 25);
+    assert_eq!(
+        &ciphertext[..len],
+        &aead.encrypt(&nonce, b"ad", b"plaintext")[..]
+    );
+    assert_eq!(
+        aead.encrypt_to_slice(&nonce, b"ad", b"plaintext", &mut [0u8; 24]),
+        Err(Error::BufferTooSmall)
+    );
+
+    let mut plaintext = [0u8; 9];
+    assert_eq!(
+        aead.decrypt_to_slice(&nonce, b"ad", &ciphertext[..len], &mut plaintext),
+        Ok(9)
+    );
+    assert_eq!(&plaintext, b"plaintext");
+    assert_eq!(
+        aead.decrypt_to_slice(&nonce, b"ad", &ciphertext[..len], &mut [0u8; 8]),
+        Err(Error::BufferTooSmall)
+    );
+
+    // The whole output buffer is zeroized on failure, not just the part
+    // which would have held the plaintext
+    let mut plaintext = [0xffu8; 16];
+    assert_eq!(
+        aead.decrypt_to_slice(&nonce, b"other", &ciphertext[..len], &mut plaintext),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(plaintext, [0u8; 16]);
+
+    let mut plaintext = [0xffu8; 16];
+    assert_eq!(
+        aead.decrypt_to_slice(&nonce, b"ad", &ciphertext[..8], &mut plaintext),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(plaintext, [0u8; 16]);
+}
+
+#[test]
+fn aes_siv_aead_try_new() {
+    assert!(Aes128SivAead::try_new(&[0x42; 32]).is_ok());
+    assert!(Aes256SivAead::try_new(&[0x42; 64]).is_ok());
+    assert_eq!(
+        Aes128SivAead::try_new(&[0x42; 16]).unwrap_err(),
+        Error::InvalidKeySize {
+            expected: 32,
+            got: 16
+        }
+    );
+    assert_eq!(
+        Aes256PmacSivAead::try_new(&[0x42; 32]).unwrap_err(),
+        Error::InvalidKeySize {
+            expected: 64,
+            got: 32
+        }
+    );
+}
+
+#[test]
+fn aes_siv_aead_try_from() {
+    let key = [0x42u8; 32];
+    let mut aead = Aes128SivAead::try_from(&key[..]).unwrap();
+    assert_eq!(
+        aead.encrypt(b"nonce", b"ad", b"plaintext"),
+        Aes128SivAead::new(&key).encrypt(b"nonce", b"ad", b"plaintext")
+    );
+
+    assert!(Aes192SivAead::try_from(&[0x42; 48][..]).is_ok());
+    assert!(Aes256PmacSivAead::try_from(&[0x42; 64][..]).is_ok());
+    assert_eq!(
+        Aes256SivAead::try_from(&key[..]).unwrap_err(),
+        Error::InvalidKeySize {
+            expected: 64,
+            got: 32
+        }
+    );
+}
+
+#[test]
+fn aes_siv_aead_clone() {
+    let mut aead = Aes256PmacSivAead::new(&[0x42; 64]);
+    let expected = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let mut clone = aead.clone();
+            std::thread::spawn(move || clone.encrypt(b"nonce", b"ad", b"plaintext"))
+        })
+        .collect();
+
+    // Dropping (and so zeroizing) the original must not affect its clones
+    drop(aead);
+
+    for worker in workers {
+        assert_eq!(worker.join().unwrap(), expected);
+    }
+}
+
+#[test]
+fn aes_siv_aead_truncated_tag() {
+    let mut aead = Aes128PmacSivAead::new(&[0x42; 32]);
+
+    for tag_len in MIN_TRUNCATED_TAG_SIZE..=16 {
+        let mut buffer = *b"plaintext";
+        let tag = aead
+            .encrypt_in_place_detached_truncated(b"nonce", b"ad", &mut buffer, tag_len)
+            .unwrap();
+        assert!(tag.as_ref()[tag_len..].iter().all(|&b| b == 0));
+
+        let tag = &tag.as_ref()[..tag_len];
+        let ciphertext = buffer;
+        aead.decrypt_in_place_detached_truncated(b"nonce", b"ad", &mut buffer, tag)
+            .unwrap();
+        assert_eq!(&buffer, b"plaintext");
+
+        let mut tampered = ciphertext;
+        tampered[0] ^= 1;
+        assert_eq!(
+            aead.decrypt_in_place_detached_truncated(b"nonce", b"ad", &mut tampered, tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(tampered, [0u8; 9]);
+
+        let mut buffer = ciphertext;
+        let mut forged = tag.to_vec();
+        forged[tag_len - 1] ^= 1;
+        assert_eq!(
+            aead.decrypt_in_place_detached_truncated(b"nonce", b"ad", &mut buffer, &forged),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    // An untruncated tag is an ordinary SIV tag
+    let mut buffer = *b"plaintext";
+    let tag = aead
+        .encrypt_in_place_detached_truncated(b"nonce", b"ad", &mut buffer, 16)
+        .unwrap();
+    let mut expected = *b"plaintext";
+    assert_eq!(
+        tag.as_ref(),
+        aead.encrypt_in_place_detached(b"nonce", b"ad", &mut expected)
+            .as_ref()
+    );
+    assert_eq!(buffer, expected);
+
+    for &tag_len in &[0, MIN_TRUNCATED_TAG_SIZE - 1, 17] {
+        let expected = if tag_len > 16 {
+            16
+        } else {
+            MIN_TRUNCATED_TAG_SIZE
+        };
+        let err = Error::InvalidTagSize {
+            expected,
+            got: tag_len,
+        };
+
+        assert_eq!(
+            aead.encrypt_in_place_detached_truncated(b"nonce", b"ad", &mut buffer, tag_len)
+                .err(),
+            Some(err)
+        );
+        assert_eq!(
+            aead.decrypt_in_place_detached_truncated(
+                b"nonce",
+                b"ad",
+                &mut buffer,
+                &[0; 17][..tag_len]
+            ),
+            Err(err)
+        );
+    }
+}
+
+#[test]
+fn aes_siv_aead_verify() {
+    let mut aead = Aes128PmacSivAead::new(&[0x42; 32]);
+
+    for &len in &[0, 15, 16, 17, 255, 256, 257, 1000] {
+        let plaintext = vec![0x24; len];
+        let mut ciphertext = aead.encrypt(b"nonce", b"ad", &plaintext);
+        assert_eq!(aead.verify(b"nonce", b"ad", &ciphertext), Ok(()));
+        assert_eq!(
+            aead.verify(b"nonce", b"other", &ciphertext),
+            Err(Error::AuthenticationFailed)
+        );
+
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 1;
+        assert_eq!(
+            aead.verify(b"nonce", b"ad", &ciphertext),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    assert_eq!(
+        aead.verify(b"nonce", b"ad", &[0u8; 15]),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[test]
+fn aes_siv_aead_associated_data_builder() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+
+    let mut ad = AssociatedData::new();
+    ad.push(b"ab");
+    ad.push(b"c");
+    assert_eq!(ad.len(), 2);
+
+    let ciphertext = aead.encrypt_with_ad(b"nonce", &ad, b"plaintext");
+    assert_eq!(
+        ciphertext,
+        aead.encrypt_headers(b"nonce", &[b"ab", b"c"], b"plaintext")
+    );
+    assert_eq!(
+        aead.decrypt_with_ad(b"nonce", &ad, &ciphertext).unwrap(),
+        b"plaintext"
+    );
+
+    // Fields are not concatenated, so they can't be confused
+    let mut other = AssociatedData::new();
+    other.push(b"a");
+    other.push(b"bc");
+    assert_eq!(
+        aead.decrypt_with_ad(b"nonce", &other, &ciphertext),
+        Err(Error::AuthenticationFailed)
+    );
+
+    let mut buffer = [0u8; 25];
+    buffer[16..].copy_from_slice(b"plaintext");
+    aead.encrypt_in_place_with_ad(b"nonce", &ad, &mut buffer);
+    assert_eq!(&buffer[..], &ciphertext[..]);
+    assert_eq!(
+        aead.decrypt_in_place_with_ad(b"nonce", &ad, &mut buffer)
+            .unwrap(),
+        b"plaintext"
+    );
+}
+
+#[test]
+#[should_panic(expected = "too many associated data items")]
+fn aes_siv_aead_associated_data_too_many_items() {
+    let mut ad = AssociatedData::new();
+
+    for _ in 0..=AssociatedData::MAX_ITEMS {
+        ad.push(b"");
+    }
+}
+
+#[test]
+fn aes_siv_aead_typed_key_and_nonce() {
+    let key = Key::<Aes128SivAead>::try_from(&[0x42; 32][..]).unwrap();
+    let nonce = Nonce::try_from(&[0x24; 16][..]).unwrap();
+    let mut aead = Aes128SivAead::from_key(&key);
+    let ciphertext = aead.encrypt(nonce.as_ref(), b"", b"plaintext");
+
+    let mut expected = Aes128SivAead::new(&[0x42; 32]);
+    assert_eq!(ciphertext, expected.encrypt(&[0x24; 16], b"", b"plaintext"));
+    assert_eq!(format!("{:?}", key), "Key { .. }");
+
+    let tag = Tag::try_from(&ciphertext[..16]).unwrap();
+    assert_eq!(tag.as_ref(), &ciphertext[..16]);
+
+    assert_eq!(
+        Key::<Aes256SivAead>::try_from(&[0x42; 32][..]).unwrap_err(),
+        Error::InvalidKeySize {
+            expected: 64,
+            got: 32
+        }
+    );
+    assert_eq!(
+        Nonce::try_from(&[0x24; 12][..]).unwrap_err(),
+        Error::InvalidNonceSize {
+            expected: 16,
+            got: 12
+        }
+    );
+    assert_eq!(
+        Tag::try_from(&ciphertext[..]).unwrap_err(),
+        Error::InvalidTagSize {
+            expected: 16,
+            got: ciphertext.len()
+        }
+    );
+}
+
+#[test]
+fn aes_siv_aead_error_is_copy() {
+    let err = Aes128SivAead::new(&[0x42; 32])
+        .decrypt(b"", b"", &[0u8; 16])
+        .unwrap_err();
+    let copy = err;
+
+    // `Error` is non-exhaustive, so matches need a wildcard arm
+    match copy {
+        Error::AuthenticationFailed => assert_eq!(err, copy),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn aes_siv_aead_error_display() {
+    assert_eq!(
+        Error::AuthenticationFailed.to_string(),
+        "authentication failed"
+    );
+    assert_eq!(
+        Error::InvalidKeySize {
+            expected: 32,
+            got: 16
+        }
+        .to_string(),
+        "invalid key size (expected 32 bytes, got 16)"
+    );
+}
+
+#[test]
+fn aes_siv_aead_sizes() {
+    assert_eq!(Aes128SivAead::KEY_SIZE, 32);
+    assert_eq!(Aes256SivAead::KEY_SIZE, 64);
+    assert_eq!(Aes128PmacSivAead::KEY_SIZE, 32);
+    assert_eq!(Aes192SivAead::KEY_SIZE, 48);
+    assert_eq!(Aes192PmacSivAead::KEY_SIZE, 48);
+    assert_eq!(Aes256PmacSivAead::KEY_SIZE, 64);
+    assert_eq!(Aes128SivAead::TAG_SIZE, 16);
+    assert_eq!(Aes256PmacSivAead::TAG_SIZE, 16);
+
+    // The constants can be used to size arrays
+    let key = [0x42u8; Aes256SivAead::KEY_SIZE];
+    let mut buffer = [0u8; Aes256SivAead::TAG_SIZE];
+    Aes256SivAead::new(&key).encrypt_in_place(b"", b"", &mut buffer);
+}
+
+#[test]
+fn aes_siv_aead_zeroize_on_drop() {
+    use miscreant::backend::{Aes128, Aes192, Aes256};
+
+    // Both halves of the key are expanded by these ciphers (the MAC key
+    // once, and the CTR key for each message), which zeroize their key
+    // schedules when dropped
+    assert!(mem::needs_drop::<Aes128>());
+    assert!(mem::needs_drop::<Aes192>());
+    assert!(mem::needs_drop::<Aes256>());
+}
+
+#[test]
+fn aes_siv_aead_sealed_message() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+
+    for plaintext in &[&b"plaintext"[..], b""] {
+        let message = aead.seal_message(b"nonce", b"ad", plaintext);
+        assert_eq!(message.nonce, b"nonce");
+        assert_eq!(message.ciphertext.len(), 16 + plaintext.len());
+        assert_eq!(aead.open_message(b"ad", &message).unwrap(), *plaintext);
+        assert_eq!(
+            aead.open_message(b"other", &message),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+}
+
+#[cfg(feature = "secrecy")]
+#[test]
+fn aes_siv_aead_try_new_secret() {
+    use secrecy::Secret;
+
+    let key = Secret::new(vec![0x42u8; 32]);
+    let ciphertext =
+        Aes128SivAead::try_new_secret(&key)
+            .unwrap()
+            .encrypt(b"nonce", b"ad", b"plaintext");
+    assert_eq!(
+        ciphertext,
+        Aes128SivAead::new(&[0x42; 32]).encrypt(b"nonce", b"ad", b"plaintext")
+    );
+
+    assert_eq!(
+        Aes128SivAead::try_new_secret(&Secret::new([0u8; 16])).unwrap_err(),
+        Error::InvalidKeySize {
+            expected: 32,
+            got: 16
+        }
+    );
+}
+
+#[cfg(feature = "hkdf")]
+#[test]
+fn aes_siv_aead_from_master_key() {
+    let master_key = [0x42u8; 32];
+
+    let mut aead = Aes256SivAead::from_master_key(&master_key, b"file 1");
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    // The same master key and context always derive the same key
+    assert_eq!(
+        Aes256SivAead::from_master_key(&master_key, b"file 1")
+            .decrypt(b"nonce", b"ad", &ciphertext)
+            .unwrap(),
+        b"plaintext"
+    );
+
+    // Other contexts derive different keys
+    assert_eq!(
+        Aes256SivAead::from_master_key(&master_key, b"file 2").decrypt(
+            b"nonce",
+            b"ad",
+            &ciphertext
+        ),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // The derived key isn't simply the master key
+    assert_ne!(
+        Aes128SivAead::from_master_key(&master_key, b"").encrypt(b"nonce", b"ad", b"plaintext"),
+        Aes128SivAead::new(&master_key).encrypt(b"nonce", b"ad", b"plaintext")
+    );
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn aes_siv_aead_generate_key() {
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let key = Aes256PmacSivAead::generate_key(&mut rng);
+    assert_eq!(key.as_slice().len(), Aes256PmacSivAead::KEY_SIZE);
+    assert_ne!(
+        key.as_slice(),
+        Aes256PmacSivAead::generate_key(&mut rng).as_slice()
+    );
+
+    let mut aead = Aes256PmacSivAead::from_key(&key);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+    assert_eq!(
+        aead.decrypt(b"nonce", b"ad", &ciphertext).unwrap(),
+        b"plaintext"
+    );
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn aes_siv_aead_random_nonce() {
+    use miscreant::NONCE_SIZE;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let message = aead.encrypt_with_random_nonce(&mut rng, b"ad", b"plaintext");
+    assert_eq!(message.len(), NONCE_SIZE + Aes128SivAead::ciphertext_len(9));
+
+    // The blob is the nonce followed by an ordinary ciphertext
+    let (nonce, ciphertext) = message.split_at(NONCE_SIZE);
+    assert_eq!(ciphertext, &aead.encrypt(nonce, b"ad", b"plaintext")[..]);
+    assert_ne!(
+        message,
+        aead.encrypt_with_random_nonce(&mut rng, b"ad", b"plaintext")
+    );
+
+    assert_eq!(
+        aead.decrypt_with_prepended_nonce(b"ad", &message).unwrap(),
+        b"plaintext"
+    );
+    assert_eq!(
+        aead.decrypt_with_prepended_nonce(b"other ad", &message),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        aead.decrypt_with_prepended_nonce(b"ad", &message[..NONCE_SIZE + 15]),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn aes_siv_aead_generate_key_with_os_rng() {
+    let key = Aes128SivAead::generate_key_with_os_rng();
+    assert_eq!(key.as_slice().len(), Aes128SivAead::KEY_SIZE);
+    assert_ne!(
+        key.as_slice(),
+        Aes128SivAead::generate_key_with_os_rng().as_slice()
+    );
+}
+
+#[test]
+fn aes_siv_aead_decrypt_zeroizing() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    let plaintext: Zeroizing<Vec<u8>> = aead
+        .decrypt_zeroizing(b"nonce", b"ad", &ciphertext)
+        .unwrap();
+    assert_eq!(&plaintext[..], b"plaintext");
+    assert_eq!(
+        aead.decrypt_zeroizing(b"nonce", b"other ad", &ciphertext)
+            .unwrap_err(),
+        Error::AuthenticationFailed
+    );
+}
+
+#[test]
+fn aes_siv_aead_in_place_vec() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let mut buffer = b"plaintext".to_vec();
+
+    aead.encrypt_in_place_vec(b"nonce", b"ad", &mut buffer);
+    assert_eq!(buffer, aead.encrypt(b"nonce", b"ad", b"plaintext"));
+
+    let mut tampered = buffer.clone();
+    aead.decrypt_in_place_vec(b"nonce", b"ad", &mut buffer)
+        .unwrap();
+    assert_eq!(buffer, b"plaintext");
+
+    tampered[0] ^= 1;
+    assert_eq!(
+        aead.decrypt_in_place_vec(b"nonce", b"ad", &mut tampered),
+        Err(Error::AuthenticationFailed)
+    );
+    assert!(tampered.iter().all(|&b| b == 0));
+
+    let mut short = vec![0u8; 15];
+    assert!(aead
+        .decrypt_in_place_vec(b"nonce", b"ad", &mut short)
+        .is_err());
+}
+
+#[test]
+fn aes_siv_aead_reencrypt() {
+    let mut old = Aes128SivAead::new(&[0x42; 32]);
+    let mut new = Aes128SivAead::new(&[0x43; 32]);
+    let ciphertext = old.encrypt(b"nonce", b"ad", b"plaintext");
+
+    let reencrypted = reencrypt(&mut old, &mut new, b"nonce", b"ad", &ciphertext).unwrap();
+    assert_eq!(
+        new.decrypt(b"nonce", b"ad", &reencrypted).unwrap(),
+        b"plaintext"
+    );
+    assert_eq!(
+        old.decrypt(b"nonce", b"ad", &reencrypted),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // Rotating to a different algorithm works the same way
+    let mut pmac = Aes256PmacSivAead::new(&[0x44; 64]);
+    let reencrypted = reencrypt(&mut new, &mut pmac, b"nonce", b"ad", &reencrypted).unwrap();
+    assert_eq!(
+        pmac.decrypt(b"nonce", b"ad", &reencrypted).unwrap(),
+        b"plaintext"
+    );
+
+    assert_eq!(
+        reencrypt(&mut old, &mut new, b"nonce", b"other ad", &ciphertext),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[cfg(feature = "committing")]
+#[test]
+fn aes_siv_aead_committing() {
+    use miscreant::KEY_COMMITMENT_SIZE;
+
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let message = aead.encrypt_committing(b"nonce", b"ad", b"plaintext");
+    assert_eq!(
+        message.len(),
+        KEY_COMMITMENT_SIZE + Aes128SivAead::ciphertext_len(9)
+    );
+    assert_eq!(
+        &message[KEY_COMMITMENT_SIZE..],
+        &aead.encrypt(b"nonce", b"ad", b"plaintext")[..]
+    );
+    assert_eq!(
+        aead.decrypt_committing(b"nonce", b"ad", &message).unwrap(),
+        b"plaintext"
+    );
+
+    // Decrypting under another key fails on the commitment, even if the
+    // rest of the message would authenticate under that key
+    let mut other = Aes128SivAead::new(&[0x43; 32]);
+    let mut forged = other.encrypt_committing(b"nonce", b"ad", b"plaintext");
+    forged[..KEY_COMMITMENT_SIZE].copy_from_slice(&message[..KEY_COMMITMENT_SIZE]);
+    assert_eq!(
+        other.decrypt_committing(b"nonce", b"ad", &forged),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        other.decrypt_committing(b"nonce", b"ad", &message),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // The commitment also covers the nonce
+    assert_eq!(
+        aead.decrypt_committing(b"other nonce", b"ad", &message),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        aead.decrypt_committing(b"nonce", b"ad", &message[..KEY_COMMITMENT_SIZE + 15]),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[test]
+fn aes_siv_aead_single_allocation() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let plaintext = [0x24u8; 100];
+
+    // The results are allocated at exactly their final size
+    let ciphertext = aead.encrypt(b"nonce", b"ad", &plaintext);
+    assert_eq!(
+        ciphertext.len(),
+        Aes128SivAead::ciphertext_len(plaintext.len())
+    );
+    assert_eq!(ciphertext.capacity(), ciphertext.len());
+
+    let decrypted = aead.decrypt(b"nonce", b"ad", &ciphertext).unwrap();
+    assert_eq!(decrypted, &plaintext[..]);
+    assert_eq!(decrypted.capacity(), plaintext.len());
+
+    // The restructured decryption still authenticates the whole message
+    for i in 0..ciphertext.len() {
+        let mut tampered = ciphertext.clone();
+        tampered[i] ^= 0x80;
+        assert_eq!(
+            aead.decrypt(b"nonce", b"ad", &tampered),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    assert_eq!(
+        aead.decrypt(b"nonce", b"ad", &ciphertext[..15]),
+        Err(Error::AuthenticationFailed)
+    );
+    let empty = aead.encrypt(b"nonce", b"ad", b"");
+    assert!(aead.decrypt(b"nonce", b"ad", &empty).unwrap().is_empty());
+}
+
+#[test]
+fn aes_siv_aead_decrypt_into() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let mut out = Vec::with_capacity(64);
+
+    for plaintext in &[&b"a longer plaintext message"[..], b"plaintext", b""] {
+        let ciphertext = aead.encrypt(b"nonce", b"ad", plaintext);
+        aead.decrypt_into(b"nonce", b"ad", &ciphertext, &mut out)
+            .unwrap();
+        assert_eq!(out, *plaintext);
+        assert_eq!(out.capacity(), 64);
+    }
+
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+    assert_eq!(
+        aead.decrypt_into(b"nonce", b"other", &ciphertext, &mut out),
+        Err(Error::AuthenticationFailed)
+    );
+    assert!(out.is_empty());
+}
+
+#[test]
+fn aes_siv_aead_tag_position() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let prefixed = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    assert_eq!(
+        aead.encrypt_with_tag_position(b"nonce", b"ad", b"plaintext", TagPosition::Prefix),
+        prefixed
+    );
+
+    // The suffix layout contains the same tag and ciphertext, swapped
+    let suffixed =
+        aead.encrypt_with_tag_position(b"nonce", b"ad", b"plaintext", TagPosition::Suffix);
+    assert_eq!(&suffixed[..9], &prefixed[16..]);
+    assert_eq!(&suffixed[9..], &prefixed[..16]);
+
+    assert_eq!(
+        aead.decrypt_with_tag_position(b"nonce", b"ad", &suffixed, TagPosition::Suffix)
+            .unwrap(),
+        b"plaintext"
+    );
+
+    // Each layout is only accepted by the matching decryption
+    assert_eq!(
+        aead.decrypt_with_tag_position(b"nonce", b"ad", &suffixed, TagPosition::Prefix),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        aead.decrypt_with_tag_position(b"nonce", b"ad", &prefixed, TagPosition::Suffix),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        aead.decrypt_with_tag_position(b"nonce", b"ad", &suffixed[..15], TagPosition::Suffix),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn aes_siv_aead_sealed_message_serde() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+
+    for plaintext in &[&b"plaintext"[..], b""] {
+        let message = aead.seal_message(b"nonce", b"ad", plaintext);
+        let json = serde_json::to_string(&message).unwrap();
+        let decoded: miscreant::SealedMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(aead.open_message(b"ad", &decoded).unwrap(), *plaintext);
+    }
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn aes_siv_aead_base64_and_hex() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    let encoded = aead.encrypt_base64(b"nonce", b"ad", b"plaintext");
+    assert!(!encoded.contains(|c| c == '+' || c == '/' || c == '='));
+    assert_eq!(
+        aead.decrypt_base64(b"nonce", b"ad", &encoded).unwrap(),
+        b"plaintext"
+    );
+    assert_eq!(
+        aead.decrypt_base64(b"nonce", b"ad", "not base64!"),
+        Err(Error::Encoding)
+    );
+
+    let encoded = aead.encrypt_hex(b"nonce", b"ad", b"plaintext");
+    assert_eq!(encoded.len(), ciphertext.len() * 2);
+    assert_eq!(
+        aead.decrypt_hex(b"nonce", b"ad", &encoded).unwrap(),
+        b"plaintext"
+    );
+    assert_eq!(
+        aead.decrypt_hex(b"nonce", b"ad", "zz"),
+        Err(Error::Encoding)
+    );
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn aes_siv_aead_heapless() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    let mut buffer = heapless::Vec::<u8, 32>::from_slice(b"plaintext").unwrap();
+    aead.encrypt_heapless(b"nonce", b"ad", &mut buffer).unwrap();
+    assert_eq!(&buffer[..], &ciphertext[..]);
+
+    aead.decrypt_heapless(b"nonce", b"ad", &mut buffer).unwrap();
+    assert_eq!(&buffer[..], b"plaintext");
+
+    // Buffers without capacity for the tag are rejected without panicking
+    let mut buffer = heapless::Vec::<u8, 16>::from_slice(b"plaintext").unwrap();
+    assert_eq!(
+        aead.encrypt_heapless(b"nonce", b"ad", &mut buffer),
+        Err(Error::BufferTooSmall)
+    );
+    assert_eq!(&buffer[..], b"plaintext");
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn aes_siv_aead_bytes() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    let mut buffer = bytes::BytesMut::with_capacity(25);
+    buffer.extend_from_slice(b"plaintext");
+    aead.encrypt_bytes(b"nonce", b"ad", &mut buffer);
+    assert_eq!(&buffer[..], &ciphertext[..]);
+
+    aead.decrypt_bytes(b"nonce", b"ad", &mut buffer).unwrap();
+    assert_eq!(&buffer[..], b"plaintext");
+
+    let mut buffer = bytes::BytesMut::from(&ciphertext[..]);
+    assert_eq!(
+        aead.decrypt_bytes(b"nonce", b"other", &mut buffer),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(&buffer[..], &[0u8; 25][..]);
+}
+
+#[test]
+fn aes_siv_aead_tag_ct_eq() {
+    use subtle::ConstantTimeEq;
+
+    let tag = Tag::new([0x42; 16]);
+    assert!(bool::from(tag.ct_eq(&Tag::new([0x42; 16]))));
+    assert_eq!(tag, Tag::new([0x42; 16]));
+
+    for &i in &[0, 15] {
+        let mut bytes = [0x42; 16];
+        bytes[i] ^= 1;
+        assert!(!bool::from(tag.ct_eq(&Tag::new(bytes))));
+        assert_ne!(tag, Tag::new(bytes));
+    }
+}
+
+#[test]
+fn aes_siv_aead_reject_tag_first_or_last_byte() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    // Tags which are wrong in their first or last byte take the same
+    // rejection path, through each of the ways of checking a tag
+    for &i in &[0, 15] {
+        let mut forged = ciphertext.clone();
+        forged[i] ^= 1;
+
+        let mut buffer = forged.clone();
+        assert_eq!(
+            aead.decrypt_in_place(b"nonce", b"ad", &mut buffer),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(buffer, [0u8; 25]);
+
+        assert_eq!(
+            aead.verify(b"nonce", b"ad", &forged),
+            Err(Error::AuthenticationFailed)
+        );
+
+        let tag = Tag::try_from(&forged[..16]).unwrap();
+        let mut buffer = forged[16..].to_vec();
+        assert_eq!(
+            aead.decrypt_in_place_detached(b"nonce", b"ad", &mut buffer, &tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(buffer, [0u8; 9]);
+    }
+}
+
+#[test]
+fn aes_siv_aead_no_ad() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let ciphertext = aead.encrypt(b"nonce", b"", b"plaintext");
+    assert_eq!(aead.encrypt_no_ad(b"nonce", b"plaintext"), ciphertext);
+    assert_eq!(
+        aead.decrypt_no_ad(b"nonce", &ciphertext).unwrap(),
+        b"plaintext"
+    );
+
+    let mut buffer = [0u8; 25];
+    buffer[16..].copy_from_slice(b"plaintext");
+    aead.encrypt_in_place_no_ad(b"nonce", &mut buffer);
+    assert_eq!(&buffer[..], &ciphertext[..]);
+    assert_eq!(
+        aead.decrypt_in_place_no_ad(b"nonce", &mut buffer).unwrap(),
+        b"plaintext"
+    );
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn aes_siv_aead_subkeys() {
+    let key: Vec<u8> = (0..64).collect();
+    let aead = Aes256SivAead::new(&key);
+    let (mac_key, ctr_key) = aead.subkeys();
+    assert_eq!(mac_key, &key[..32]);
+    assert_eq!(ctr_key, &key[32..]);
+}
+
+#[test]
+fn aes_siv_aead_wrap_key() {
+    let mut kek = Aes256SivAead::new(&[0x42; 64]);
+    let key_to_wrap: Vec<u8> = (0..32).collect();
+
+    let wrapped = kek.wrap_key(&key_to_wrap);
+    assert_eq!(wrapped.len(), 48);
+    assert_eq!(wrapped, kek.seal_deterministic(b"", &key_to_wrap));
+    assert_eq!(kek.unwrap_key(&wrapped).unwrap(), key_to_wrap);
+
+    let mut tampered = wrapped.clone();
+    tampered[47] ^= 1;
+    assert_eq!(kek.unwrap_key(&tampered), Err(Error::AuthenticationFailed));
+
+    let mut other_kek = Aes256SivAead::new(&[0x43; 64]);
+    assert_eq!(
+        other_kek.unwrap_key(&wrapped),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[test]
+fn aes_siv_aead_ciphertext_len() {
+    assert_eq!(Aes128SivAead::TAG_OVERHEAD, 16);
+    assert_eq!(Aes256SivAead::ciphertext_len(0), 16);
+
+    let mut aead = Aes256SivAead::new(&[0x42; 64]);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", &[0u8; 100]);
+    assert_eq!(ciphertext.len(), Aes256SivAead::ciphertext_len(100));
+}
+
+#[test]
+fn aes_siv_aead_vectored() {
+    let mut aead = Aes128PmacSivAead::new(&[0x42; 32]);
+    let plaintext: Vec<u8> = (0..100).collect();
+    let expected = aead.encrypt(b"nonce", b"ad", &plaintext);
+
+    for &split in &[0, 1, 15, 16, 17, 50, 84, 99, 100] {
+        let (header, body) = plaintext.split_at(split);
+        assert_eq!(
+            aead.encrypt_vectored(b"nonce", b"ad", &[header, &[], body]),
+            expected
+        );
+
+        let mut first = vec![0u8; split];
+        let mut second = vec![0u8; 110 - split];
+        assert_eq!(
+            aead.decrypt_vectored(b"nonce", b"ad", &expected, &mut [&mut first, &mut second]),
+            Ok(100)
+        );
+        assert_eq!(&first[..], header);
+        assert_eq!(&second[..100 - split], body);
+    }
+
+    assert_eq!(
+        aead.encrypt_vectored(b"nonce", b"ad", &[]),
+        aead.encrypt(b"nonce", b"ad", b"")
+    );
+
+    let mut buffer = [0u8; 99];
+    assert_eq!(
+        aead.decrypt_vectored(b"nonce", b"ad", &expected, &mut [&mut buffer]),
+        Err(Error::BufferTooSmall)
+    );
+
+    let mut first = [0u8; 40];
+    let mut second = [0u8; 60];
+    assert_eq!(
+        aead.decrypt_vectored(
+            b"nonce",
+            b"other",
+            &expected,
+            &mut [&mut first, &mut second]
+        ),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(first, [0u8; 40]);
+    assert_eq!(second, [0u8; 60]);
+}
+
+#[test]
+fn aes_siv_aead_max_message_len() {
+    // 2^31 blocks, within which RFC 5297 guarantees the CTR counter can't wrap
+    assert_eq!(MAX_MESSAGE_LEN, (1 << 31) * 16);
+    assert_eq!(Error::MessageTooLong.to_string(), "message too long");
+
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let plaintext = [0x24; 100];
+    let ciphertext = aead.encrypt(b"nonce", b"ad", &plaintext);
+
+    assert_eq!(
+        aead.try_encrypt(b"nonce", b"ad", &plaintext),
+        Ok(ciphertext.clone())
+    );
+    assert_eq!(
+        aead.decrypt(b"nonce", b"ad", &ciphertext),
+        Ok(plaintext.to_vec())
+    );
+}
+
+#[test]
+fn aes_siv_aead_new_from_subkeys() {
+    let key: Vec<u8> = (0..64).collect();
+    let expected = Aes256SivAead::new(&key).encrypt(b"nonce", b"ad", b"plaintext");
+
+    let mut aead = Aes256SivAead::new_from_subkeys(&key[..32], &key[32..]).unwrap();
+    assert_eq!(aead.encrypt(b"nonce", b"ad", b"plaintext"), expected);
+
+    let mut aead = Aes128PmacSivAead::new_from_subkeys(&key[..16], &key[16..32]).unwrap();
+    assert_eq!(
+        aead.encrypt(b"nonce", b"ad", b"plaintext"),
+        Aes128PmacSivAead::new(&key[..32]).encrypt(b"nonce", b"ad", b"plaintext")
+    );
+
+    assert_eq!(
+        Aes256SivAead::new_from_subkeys(&key[..16], &key[32..]).err(),
+        Some(Error::InvalidKeySize {
+            expected: 32,
+            got: 16
+        })
+    );
+    assert_eq!(
+        Aes256SivAead::new_from_subkeys(&key[..32], &key[..]).err(),
+        Some(Error::InvalidKeySize {
+            expected: 32,
+            got: 64
+        })
+    );
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn aes_siv_aead_keystream() {
+    for &key_size in &[32, 64] {
+        let key = vec![0x42; key_size];
+        let plaintext = [0x24; 100];
+        let ciphertext = match key_size {
+            32 => Aes128SivAead::new(&key).encrypt(b"nonce", b"ad", &plaintext),
+            _ => Aes256PmacSivAead::new(&key).encrypt(b"nonce", b"ad", &plaintext),
+        };
+        let (iv, body) = ciphertext.split_at(16);
+
+        let mut keystream = [0xff; 100];
+        match key_size {
+            32 => Aes128SivAead::new(&key).keystream(iv, &mut keystream),
+            _ => Aes256PmacSivAead::new(&key).keystream(iv, &mut keystream),
+        }
+
+        for ((k, p), c) in keystream.iter().zip(&plaintext[..]).zip(body) {
+            assert_eq!(k ^ p, *c);
+        }
+    }
+}
+
+#[test]
+fn aes_siv_aead_decrypt_cow() {
+    use std::borrow::Cow;
+
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+
+    let mut buffer = ciphertext.clone();
+    match aead.decrypt_cow(b"nonce", b"ad", &mut buffer).unwrap() {
+        Cow::Borrowed(plaintext) => assert_eq!(plaintext, b"plaintext"),
+        Cow::Owned(_) => panic!("expected the plaintext to be borrowed"),
+    }
+
+    let mut buffer = ciphertext;
+    assert_eq!(
+        aead.decrypt_cow(b"nonce", b"other", &mut buffer),
+        Err(Error::AuthenticationFailed)
+    );
+    assert!(buffer.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn aes_siv_aead_header_partitions() {
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let partitions: &[&[&[u8]]] = &[
+        &[b"abc"],
+        &[b"a", b"bc"],
+        &[b"ab", b"c"],
+        &[b"a", b"b", b"c"],
+        &[b"", b"abc"],
+        &[b"abc", b""],
+        &[b"a", b"", b"bc"],
+        &[],
+        &[b""],
+        &[b"", b""],
+    ];
+
+    let tags: Vec<Vec<u8>> = partitions
+        .iter()
+        .map(|headers| aead.encrypt_headers(b"nonce", headers, b"plaintext")[..16].to_vec())
+        .collect();
+
+    // Every way of splitting the same bytes into headers gives a distinct tag
+    for (i, a) in tags.iter().enumerate() {
+        for b in &tags[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+
+    let ciphertext = aead.encrypt_headers(b"nonce", partitions[1], b"plaintext");
+    for (i, headers) in partitions.iter().enumerate().filter(|&(i, _)| i != 1) {
+        assert_eq!(
+            aead.decrypt_headers(b"nonce", headers, &ciphertext),
+            Err(Error::AuthenticationFailed),
+            "partition {} authenticated",
+            i
+        );
+    }
+}
+
+#[test]
+fn aes_siv_aead_restoring_too_many_headers() {
+    use miscreant::siv::MAX_HEADERS;
+
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+    let headers = vec![&b"ad"[..]; MAX_HEADERS];
+
+    // Rejected before the buffer is decrypted, leaving the ciphertext intact
+    let mut buffer = ciphertext.clone();
+    assert_eq!(
+        aead.decrypt_headers_in_place_restoring(b"nonce", &headers, &mut buffer),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(buffer, ciphertext);
+}
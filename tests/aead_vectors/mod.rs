@@ -1,4 +1,3 @@
-pub use serde_json::Value as JsonValue;
 use std::{fs::File, io::Read, path::Path};
 use subtle_encoding::hex;
 
@@ -17,12 +16,12 @@ pub struct AesSivAeadExample {
 impl AesSivAeadExample {
     /// Load examples from aes_siv_aead.tjson
     pub fn load_all() -> Vec<Self> {
-        Self::load_from_file(Path::new("vectors/aes_siv_aead.tjson"))
+        Self::load_from_file(Path::new("tests/vectors/aes_siv_aead.tjson"))
     }
 
     /// Load examples from a file at the given path
     pub fn load_from_file(path: &Path) -> Vec<Self> {
-        let mut file = File::open(&path).expect("valid aes_siv_aead.tjson");
+        let mut file = File::open(path).expect("valid aes_siv_aead.tjson");
         let mut tjson_string = String::new();
 
         file.read_to_string(&mut tjson_string)
@@ -36,7 +35,7 @@ impl AesSivAeadExample {
             .expect("aes_siv_aead.tjson examples array");
 
         examples
-            .into_iter()
+            .iter()
             .map(|ex| Self {
                 alg: ex["alg:s"].as_str().expect("algorithm name").to_owned(),
                 key: hex::decode(ex["key:d16"].as_str().expect("encoded example").as_bytes())
@@ -0,0 +1,77 @@
+use miscreant::{new_dyn, Aead, Aes128SivAead, Aes256PmacSivAead, Algorithm, DynAead, Error};
+
+#[test]
+fn algorithm_from_str() {
+    for &(name, alg, key_size) in &[
+        ("AES-128-SIV", Algorithm::Aes128Siv, 32),
+        ("AES-192-SIV", Algorithm::Aes192Siv, 48),
+        ("AES-256-SIV", Algorithm::Aes256Siv, 64),
+        ("AES-128-PMAC-SIV", Algorithm::Aes128PmacSiv, 32),
+        ("AES-192-PMAC-SIV", Algorithm::Aes192PmacSiv, 48),
+        ("AES-256-PMAC-SIV", Algorithm::Aes256PmacSiv, 64),
+    ] {
+        assert_eq!(name.parse::<Algorithm>().unwrap(), alg);
+        assert_eq!(alg.to_string(), name);
+        assert_eq!(alg.key_size(), key_size);
+    }
+
+    assert_eq!(
+        "aes-256-pmac-siv".parse::<Algorithm>().unwrap(),
+        Algorithm::Aes256PmacSiv
+    );
+    assert_eq!(
+        "AES-256-GCM".parse::<Algorithm>(),
+        Err(Error::UnknownAlgorithm)
+    );
+    assert_eq!("".parse::<Algorithm>(), Err(Error::UnknownAlgorithm));
+}
+
+#[test]
+fn algorithm_new_dyn() {
+    let key = [0x42; 64];
+    let mut aeads: Vec<Box<dyn DynAead + Send + Sync>> = vec![
+        new_dyn("AES-128-SIV".parse().unwrap(), &key[..32]).unwrap(),
+        new_dyn("AES-256-PMAC-SIV".parse().unwrap(), &key).unwrap(),
+    ];
+
+    let expected = [
+        Aead::encrypt(
+            &mut Aes128SivAead::new(&key[..32]),
+            b"nonce",
+            b"ad",
+            b"plaintext",
+        ),
+        Aead::encrypt(
+            &mut Aes256PmacSivAead::new(&key),
+            b"nonce",
+            b"ad",
+            b"plaintext",
+        ),
+    ];
+
+    for (aead, expected) in aeads.iter_mut().zip(&expected) {
+        let ciphertext = aead.encrypt(b"nonce", b"ad", b"plaintext");
+        assert_eq!(&ciphertext, expected);
+        aead.verify(b"nonce", b"ad", &ciphertext).unwrap();
+        assert_eq!(
+            aead.decrypt(b"nonce", b"ad", &ciphertext).unwrap(),
+            b"plaintext"
+        );
+        assert_eq!(
+            aead.decrypt(b"nonce", b"other ad", &ciphertext),
+            Err(Error::AuthenticationFailed)
+        );
+
+        let mut buffer = ciphertext.clone();
+        aead.encrypt_in_place(b"nonce", b"ad", &mut buffer);
+        assert_ne!(buffer, ciphertext);
+    }
+
+    assert_eq!(
+        new_dyn(Algorithm::Aes256Siv, &key[..32]).err(),
+        Some(Error::InvalidKeySize {
+            expected: 64,
+            got: 32
+        })
+    );
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "async")]
+
+use futures::{
+    executor::block_on,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    task::{Context, Poll},
+};
+use miscreant::{
+    async_io::{AsyncStreamReader, AsyncStreamWriter},
+    io::{StreamReader, StreamWriter},
+    stream::{Aes128SivDecryptor, Aes128SivEncryptor},
+};
+use std::{
+    io::{self, Read, Write},
+    pin::Pin,
+};
+
+const KEY: [u8; 32] = [0x42; 32];
+const NONCE: [u8; 8] = [0x24; 8];
+
+/// Transfers data in small pieces, returning `Pending` on every other poll
+struct Trickle<T> {
+    inner: T,
+    max_len: usize,
+    pending: bool,
+}
+
+impl<T> Trickle<T> {
+    fn new(inner: T, max_len: usize) -> Self {
+        Self {
+            inner,
+            max_len,
+            pending: false,
+        }
+    }
+
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> bool {
+        self.pending = !self.pending;
+
+        if self.pending {
+            cx.waker().wake_by_ref();
+        }
+
+        self.pending
+    }
+}
+
+impl AsyncRead for Trickle<&[u8]> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        if this.poll_pending(cx) {
+            return Poll::Pending;
+        }
+
+        let len = buf.len().min(this.max_len);
+        Poll::Ready(Read::read(&mut this.inner, &mut buf[..len]))
+    }
+}
+
+impl AsyncWrite for Trickle<Vec<u8>> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        if this.poll_pending(cx) {
+            return Poll::Pending;
+        }
+
+        let len = data.len().min(this.max_len);
+        Poll::Ready(Write::write(&mut this.inner, &data[..len]))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn plaintext() -> Vec<u8> {
+    (0..1000).map(|i| i as u8).collect()
+}
+
+#[test]
+fn async_stream_writer_matches_sync() {
+    let plaintext = plaintext();
+
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut writer = AsyncStreamWriter::with_chunk_size(encryptor, Trickle::new(vec![], 7), 64);
+    block_on(async {
+        for piece in plaintext.chunks(13) {
+            writer.write_all(piece).await.unwrap();
+        }
+        writer.close().await.unwrap();
+    });
+    let ciphertext = writer.into_inner().inner;
+
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut expected = StreamWriter::with_chunk_size(encryptor, vec![], 64);
+    expected.write_all(&plaintext).unwrap();
+    assert_eq!(ciphertext, expected.finish().unwrap());
+}
+
+#[test]
+fn async_stream_reader_round_trip() {
+    let plaintext = plaintext();
+
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut writer = StreamWriter::with_chunk_size(encryptor, vec![], 64);
+    writer.write_all(&plaintext).unwrap();
+    let ciphertext = writer.finish().unwrap();
+
+    let decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    let mut reader =
+        AsyncStreamReader::with_chunk_size(decryptor, Trickle::new(&ciphertext[..], 5), 64);
+    let mut decrypted = vec![];
+    block_on(reader.read_to_end(&mut decrypted)).unwrap();
+    assert_eq!(decrypted, plaintext);
+
+    // The synchronous reader accepts the same STREAM
+    let decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    let mut decrypted = vec![];
+    StreamReader::with_chunk_size(decryptor, &ciphertext[..], 64)
+        .read_to_end(&mut decrypted)
+        .unwrap();
+    assert_eq!(decrypted, plaintext);
+}
+
+#[test]
+fn async_stream_reader_truncated() {
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut writer = StreamWriter::with_chunk_size(encryptor, vec![], 64);
+    writer.write_all(&plaintext()).unwrap();
+    let ciphertext = writer.finish().unwrap();
+
+    let decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    let truncated = Trickle::new(&ciphertext[..ciphertext.len() - 1], 64);
+    let mut reader = AsyncStreamReader::with_chunk_size(decryptor, truncated, 64);
+    let err = block_on(reader.read_to_end(&mut vec![])).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn async_stream_reader_oversized_message() {
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut writer = StreamWriter::with_chunk_size(encryptor, vec![], 64);
+    writer.write_all(&plaintext()).unwrap();
+    let ciphertext = writer.finish().unwrap();
+
+    // Messages larger than the configured chunk size are rejected
+    let decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    let mut reader = AsyncStreamReader::with_chunk_size(decryptor, &ciphertext[..], 32);
+    let err = block_on(reader.read_to_end(&mut vec![])).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
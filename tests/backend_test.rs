@@ -0,0 +1,157 @@
+//! The hardware accelerated AES backends must produce byte-identical output
+//! to the software implementation
+
+mod aead_vectors;
+
+use self::aead_vectors::AesSivAeadExample;
+use block_cipher_trait::BlockCipher;
+use cmac::Cmac;
+use ctr::Ctr128;
+use miscreant::{
+    active_backend, backend,
+    generic_array::{
+        typenum::{U16, U8},
+        GenericArray,
+    },
+    Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead, Backend, SivAead,
+};
+use pmac_crate::Pmac;
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha8Rng,
+};
+
+macro_rules! check_cipher {
+    ($fast:ty, $soft:ty, $key_len:expr) => {{
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+        for _ in 0..16 {
+            let mut key = [0u8; $key_len];
+            rng.fill_bytes(&mut key);
+            let fast = <$fast>::new(GenericArray::from_slice(&key));
+            let soft = <$soft>::new(GenericArray::from_slice(&key));
+            assert_eq!(fast.backend(), Aes128SivAead::backend());
+
+            let mut block: GenericArray<u8, U16> = GenericArray::default();
+            rng.fill_bytes(&mut block);
+            let mut expected = block.clone();
+
+            fast.encrypt_block(&mut block);
+            soft.encrypt_block(&mut expected);
+            assert_eq!(block, expected);
+
+            fast.decrypt_block(&mut block);
+            soft.decrypt_block(&mut expected);
+            assert_eq!(block, expected);
+
+            let mut blocks: GenericArray<GenericArray<u8, U16>, U8> = GenericArray::default();
+
+            for block in blocks.iter_mut() {
+                rng.fill_bytes(block);
+            }
+
+            let mut expected = blocks.clone();
+            fast.encrypt_blocks(&mut blocks);
+            soft.encrypt_blocks(&mut expected);
+            assert_eq!(blocks, expected);
+        }
+    }};
+}
+
+#[test]
+fn backend_ciphers_match_software() {
+    check_cipher!(backend::Aes128, aes::Aes128, 16);
+    check_cipher!(backend::Aes192, aes::Aes192, 24);
+    check_cipher!(backend::Aes256, aes::Aes256, 32);
+}
+
+#[test]
+fn backend_detection() {
+    let backend = Aes128SivAead::backend();
+    assert!(match backend {
+        Backend::Soft | Backend::AesNi | Backend::Armv8 => true,
+        _ => false,
+    });
+    assert_eq!(Aes256SivAead::backend(), backend);
+}
+
+#[test]
+fn backend_active() {
+    let backend = active_backend();
+    assert_eq!(backend, Aes128SivAead::backend());
+    assert_eq!(backend, active_backend());
+
+    #[cfg(feature = "force-soft")]
+    assert_eq!(backend, Backend::Soft);
+
+    #[cfg(all(target_arch = "x86_64", not(feature = "force-soft")))]
+    {
+        let aesni = is_x86_feature_detected!("aes") && is_x86_feature_detected!("sse2");
+        assert_eq!(backend == Backend::AesNi, aesni);
+    }
+
+    #[cfg(all(target_arch = "aarch64", not(feature = "force-soft")))]
+    {
+        let armv8 = cfg!(feature = "armv8") && std::arch::is_aarch64_feature_detected!("aes");
+        assert_eq!(backend == Backend::Armv8, armv8);
+    }
+
+    assert_eq!(Backend::Soft.to_string(), "software");
+    assert_eq!(Backend::AesNi.to_string(), "AES-NI");
+}
+
+/// AES-SIV using the software AES implementation
+type SoftSivAead<C> = SivAead<Ctr128<C>, Cmac<C>>;
+
+/// AES-PMAC-SIV using the software AES implementation
+type SoftPmacSivAead<C> = SivAead<Ctr128<C>, Pmac<C>>;
+
+fn check_example<A: Aead, S: Aead>(example: &AesSivAeadExample) {
+    let ciphertext = A::new(&example.key).encrypt(&example.nonce, &example.ad, &example.plaintext);
+    assert_eq!(ciphertext, example.ciphertext);
+    assert_eq!(
+        ciphertext,
+        S::new(&example.key).encrypt(&example.nonce, &example.ad, &example.plaintext)
+    );
+}
+
+#[test]
+fn backend_examples_match_software() {
+    for example in AesSivAeadExample::load_all() {
+        match (example.alg.as_ref(), example.key.len()) {
+            ("AES-SIV", 32) => check_example::<Aes128SivAead, SoftSivAead<aes::Aes128>>(&example),
+            ("AES-SIV", 48) => check_example::<Aes192SivAead, SoftSivAead<aes::Aes192>>(&example),
+            ("AES-SIV", 64) => check_example::<Aes256SivAead, SoftSivAead<aes::Aes256>>(&example),
+            ("AES-PMAC-SIV", 32) => check_example::<
+                miscreant::Aes128PmacSivAead,
+                SoftPmacSivAead<aes::Aes128>,
+            >(&example),
+            ("AES-PMAC-SIV", 48) => check_example::<
+                miscreant::Aes192PmacSivAead,
+                SoftPmacSivAead<aes::Aes192>,
+            >(&example),
+            ("AES-PMAC-SIV", 64) => check_example::<
+                miscreant::Aes256PmacSivAead,
+                SoftPmacSivAead<aes::Aes256>,
+            >(&example),
+            (alg, len) => panic!("unexpected example: {} with {}-byte key", alg, len),
+        }
+    }
+}
+
+#[test]
+fn backend_pmac_matches_software() {
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+
+    // Lengths spanning several batches of parallel blocks, aligned or not
+    for &len in &[0, 1, 16, 127, 128, 129, 255, 256, 1000, 4096, 4097] {
+        let mut key = [0u8; 32];
+        let mut plaintext = vec![0u8; len];
+        rng.fill_bytes(&mut key);
+        rng.fill_bytes(&mut plaintext);
+
+        let ciphertext = miscreant::Aes128PmacSivAead::new(&key).encrypt(b"nonce", b"", &plaintext);
+        let expected = SoftPmacSivAead::<aes::Aes128>::new(&key).encrypt(b"nonce", b"", &plaintext);
+        assert_eq!(ciphertext, expected, "len: {}", len);
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "alloc")]
+
+use miscreant::{compat, Aead, Aes128PmacSivAead, Aes192SivAead, Aes256SivAead, Error};
+
+#[test]
+fn compat_seal_open() {
+    let headers: &[&[u8]] = &[b"header 1", b"header 2"];
+
+    for &len in &[32, 48, 64] {
+        let key = vec![0x42; len];
+        let sealed = compat::seal(&key, b"plaintext", b"nonce", headers).unwrap();
+        assert_eq!(
+            compat::open(&key, &sealed, b"nonce", headers).unwrap(),
+            b"plaintext"
+        );
+        assert_eq!(
+            compat::open(&key, &sealed, b"nonce", &[b"header 1"]),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    // The key size selects the algorithm
+    assert_eq!(
+        compat::seal(&[0x42; 48], b"plaintext", b"nonce", headers).unwrap(),
+        Aes192SivAead::new(&[0x42; 48]).encrypt_headers(b"nonce", headers, b"plaintext")
+    );
+    assert_eq!(
+        compat::seal(&[0x42; 64], b"plaintext", b"nonce", &[b"ad"]).unwrap(),
+        Aes256SivAead::new(&[0x42; 64]).encrypt(b"nonce", b"ad", b"plaintext")
+    );
+
+    assert_eq!(
+        compat::seal(&[0x42; 16], b"plaintext", b"nonce", &[]),
+        Err(Error::InvalidKeySize {
+            expected: 32,
+            got: 16
+        })
+    );
+}
+
+#[cfg(feature = "pmac")]
+#[test]
+fn compat_seal_open_pmac() {
+    let key = [0x42; 32];
+    let sealed = compat::seal_pmac(&key, b"plaintext", b"nonce", &[b"ad"]).unwrap();
+    assert_eq!(
+        sealed,
+        Aes128PmacSivAead::new(&key).encrypt(b"nonce", b"ad", b"plaintext")
+    );
+    assert_eq!(
+        compat::open_pmac(&key, &sealed, b"nonce", &[b"ad"]).unwrap(),
+        b"plaintext"
+    );
+    assert!(compat::open(&key, &sealed, b"nonce", &[b"ad"]).is_err());
+}
@@ -0,0 +1,70 @@
+use miscreant::{Aead, Aes128SivAead, Aes256PmacSivAead, Error};
+
+#[test]
+fn domain_round_trip() {
+    let aead = Aes128SivAead::new(&[0x42; 32]);
+    let mut domain = aead.with_domain(b"context A");
+    assert_eq!(domain.label(), b"context A");
+
+    let ciphertext = domain.encrypt(b"nonce", b"ad", b"plaintext");
+    assert_eq!(
+        domain.decrypt(b"nonce", b"ad", &ciphertext).unwrap(),
+        b"plaintext"
+    );
+
+    let mut buffer = ciphertext.clone();
+    assert_eq!(
+        domain
+            .clone()
+            .decrypt_in_place(b"nonce", b"ad", &mut buffer)
+            .unwrap(),
+        b"plaintext"
+    );
+
+    // The label is the first associated data header
+    let mut aead = aead;
+    assert_eq!(
+        aead.encrypt_headers(b"nonce", &[b"context A", b"ad"], b"plaintext"),
+        ciphertext
+    );
+
+    let mut buffer = [0u8; 25];
+    buffer[16..].copy_from_slice(b"plaintext");
+    domain.encrypt_in_place(b"nonce", b"ad", &mut buffer);
+    assert_eq!(&buffer[..], &ciphertext[..]);
+}
+
+#[test]
+fn domain_separation() {
+    let aead = Aes256PmacSivAead::new(&[0x42; 64]);
+    let mut a = aead.with_domain(b"context A");
+    let mut b = aead.with_domain(b"context B");
+    let mut unlabelled = aead.clone();
+
+    let from_a = a.encrypt(b"nonce", b"ad", b"plaintext");
+    let from_b = b.encrypt(b"nonce", b"ad", b"plaintext");
+    let plain = unlabelled.encrypt(b"nonce", b"ad", b"plaintext");
+
+    assert_eq!(
+        b.decrypt(b"nonce", b"ad", &from_a),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        a.decrypt(b"nonce", b"ad", &from_b),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        a.decrypt(b"nonce", b"ad", &plain),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        unlabelled.decrypt(b"nonce", b"ad", &from_a),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // Moving the label into the associated data doesn't help either
+    assert_eq!(
+        unlabelled.decrypt(b"nonce", b"context Aad", &from_a),
+        Err(Error::AuthenticationFailed)
+    );
+}
@@ -9,6 +9,14 @@
 #include <string.h>
 #include "miscreant.h"
 
+// Like assert, but never compiled out (the checked expressions have side effects)
+#define CHECK(expr) do { \
+    if (!(expr)) { \
+        fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #expr); \
+        abort(); \
+    } \
+} while (0)
+
 void test_aead_aes128siv() {
     // AES-SIV Nonce-based Authenticated Encryption Example #1
     const uint8_t key[] = "\x7F\x7E\x7D\x7C\x7B\x7A\x79\x78\x77\x76\x75\x74\x73\x72\x71\x70\x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4A\x4B\x4C\x4D\x4E\x4F";
@@ -177,11 +185,194 @@ void test_aead_aes256pmacsiv() {
     assert(memcmp(buf, pt, sizeof(pt) - 1) == 0);
 }
 
+void test_aes128siv_instance() {
+    // AES-SIV Nonce-based Authenticated Encryption Example #1
+    const uint8_t key[] = "\x7F\x7E\x7D\x7C\x7B\x7A\x79\x78\x77\x76\x75\x74\x73\x72\x71\x70\x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4A\x4B\x4C\x4D\x4E\x4F";
+    const uint8_t nonce[] = "\x09\xF9\x11\x02\x9D\x74\xE3\x5B\xD8\x41\x56\xC5\x63\x56\x88\xC0";
+    const uint8_t ad[] = "\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xAA\xBB\xCC\xDD\xEE\xFF\xDE\xAD\xDA\xDA\xDE\xAD\xDA\xDA\xFF\xEE\xDD\xCC\xBB\xAA\x99\x88\x77\x66\x55\x44\x33\x22\x11\x00";
+    const uint8_t pt[] = "\x74\x68\x69\x73\x20\x69\x73\x20\x73\x6F\x6D\x65\x20\x70\x6C\x61\x69\x6E\x74\x65\x78\x74\x20\x74\x6F\x20\x65\x6E\x63\x72\x79\x70\x74\x20\x75\x73\x69\x6E\x67\x20\x53\x49\x56\x2D\x41\x45\x53";
+    const uint8_t ct[] = "\x85\x82\x5E\x22\xE9\x0C\xF2\xDD\xDA\x2C\x54\x8D\xC7\xC1\xB6\x31\x0D\xCD\xAC\xA0\xCE\xBF\x9D\xC6\xCB\x90\x58\x3F\x5B\xF1\x50\x6E\x02\xCD\x48\x83\x2B\x00\xE4\xE5\x98\xB2\xB2\x2A\x53\xE6\x19\x9D\x4D\xF0\xC1\x66\x6A\x35\xA0\x43\x3B\x25\x0D\xC1\x34\xD7\x76";
+
+    miscreant_aes128siv *aead = NULL;
+    uint8_t buf[sizeof(ct)] = {0};
+    size_t buflen;
+
+    // Keys are validated
+    CHECK(miscreant_aes128siv_new(&aead, key, 16) == MISCREANT_ERR_INVALID_KEY_SIZE);
+    CHECK(aead == NULL);
+    CHECK(miscreant_aes128siv_new(&aead, key, sizeof(key) - 1) == MISCREANT_OK);
+
+    // The instance is reused for several messages
+    for (int i = 0; i < 3; i++) {
+        buflen = sizeof(buf);
+        CHECK(miscreant_aes128siv_encrypt(
+            aead, buf, &buflen,
+            pt, sizeof(pt) - 1,
+            nonce, sizeof(nonce) - 1,
+            ad, sizeof(ad) - 1
+        ) == MISCREANT_OK);
+        CHECK(buflen == sizeof(ct) - 1);
+        CHECK(memcmp(buf, ct, sizeof(ct) - 1) == 0);
+
+        buflen = sizeof(buf);
+        CHECK(miscreant_aes128siv_decrypt(
+            aead, buf, &buflen,
+            ct, sizeof(ct) - 1,
+            nonce, sizeof(nonce) - 1,
+            ad, sizeof(ad) - 1
+        ) == MISCREANT_OK);
+        CHECK(buflen == sizeof(pt) - 1);
+        CHECK(memcmp(buf, pt, sizeof(pt) - 1) == 0);
+    }
+
+    // Too small output buffers report the required size
+    buflen = 10;
+    CHECK(miscreant_aes128siv_encrypt(
+        aead, buf, &buflen,
+        pt, sizeof(pt) - 1,
+        nonce, sizeof(nonce) - 1,
+        ad, sizeof(ad) - 1
+    ) == MISCREANT_ERR_BUFFER_TOO_SMALL);
+    CHECK(buflen == sizeof(ct) - 1);
+
+    // Tampered ciphertexts are rejected
+    uint8_t tampered[sizeof(ct)];
+    memcpy(tampered, ct, sizeof(ct));
+    tampered[20] ^= 1;
+    buflen = sizeof(buf);
+    CHECK(miscreant_aes128siv_decrypt(
+        aead, buf, &buflen,
+        tampered, sizeof(tampered) - 1,
+        nonce, sizeof(nonce) - 1,
+        ad, sizeof(ad) - 1
+    ) == MISCREANT_ERR_AUTHENTICATION_FAILED);
+
+    // Messages longer than MAX_MESSAGE_LEN are rejected before being read
+    buflen = sizeof(buf);
+    CHECK(miscreant_aes128siv_encrypt(
+        aead, buf, &buflen,
+        pt, SIZE_MAX - 8,
+        nonce, sizeof(nonce) - 1,
+        ad, sizeof(ad) - 1
+    ) == MISCREANT_ERR_MESSAGE_TOO_LONG);
+
+    // Null pointers are rejected rather than dereferenced
+    buflen = sizeof(buf);
+    CHECK(miscreant_aes128siv_encrypt(
+        aead, buf, &buflen,
+        NULL, 5,
+        nonce, sizeof(nonce) - 1,
+        ad, sizeof(ad) - 1
+    ) == MISCREANT_ERR_NULL_POINTER);
+
+    miscreant_aes128siv_free(aead);
+    miscreant_aes128siv_free(NULL);
+}
+
+void test_aes256siv_stream() {
+    uint8_t key[64];
+    const uint8_t nonce[] = "\x01\x02\x03\x04\x05\x06\x07\x08";
+    const char *messages[] = {"first message", "", "last message"};
+    uint8_t ct[3][32];
+    size_t ctlen[3];
+    uint8_t buf[32];
+    size_t buflen;
+
+    memset(key, 0x42, sizeof(key));
+
+    miscreant_aes256siv_stream_encryptor *encryptor = NULL;
+    CHECK(miscreant_aes256siv_stream_encryptor_new(
+        &encryptor, key, sizeof(key), nonce, 4
+    ) == MISCREANT_ERR_INVALID_NONCE_SIZE);
+    CHECK(miscreant_aes256siv_stream_encryptor_new(
+        &encryptor, key, sizeof(key), nonce, sizeof(nonce) - 1
+    ) == MISCREANT_OK);
+
+    // Overlong messages are rejected without finishing the encryptor
+    buflen = sizeof(buf);
+    CHECK(miscreant_aes256siv_stream_encrypt_last(
+        encryptor, buf, &buflen,
+        (const uint8_t *)messages[0], SIZE_MAX - 8,
+        NULL, 0
+    ) == MISCREANT_ERR_MESSAGE_TOO_LONG);
+    CHECK(miscreant_aes256siv_stream_encrypt_next(
+        encryptor, buf, &buflen,
+        (const uint8_t *)messages[0], SIZE_MAX - 8,
+        NULL, 0
+    ) == MISCREANT_ERR_MESSAGE_TOO_LONG);
+
+    for (int i = 0; i < 3; i++) {
+        ctlen[i] = sizeof(ct[i]);
+
+        if (i < 2) {
+            CHECK(miscreant_aes256siv_stream_encrypt_next(
+                encryptor, ct[i], &ctlen[i],
+                (const uint8_t *)messages[i], strlen(messages[i]),
+                NULL, 0
+            ) == MISCREANT_OK);
+        } else {
+            CHECK(miscreant_aes256siv_stream_encrypt_last(
+                encryptor, ct[i], &ctlen[i],
+                (const uint8_t *)messages[i], strlen(messages[i]),
+                NULL, 0
+            ) == MISCREANT_OK);
+        }
+
+        CHECK(ctlen[i] == strlen(messages[i]) + 16);
+    }
+
+    // The encryptor is finished after the last message
+    buflen = sizeof(buf);
+    CHECK(miscreant_aes256siv_stream_encrypt_next(
+        encryptor, buf, &buflen, NULL, 0, NULL, 0
+    ) == MISCREANT_ERR_FINISHED);
+    miscreant_aes256siv_stream_encryptor_free(encryptor);
+
+    miscreant_aes256siv_stream_decryptor *decryptor = NULL;
+    CHECK(miscreant_aes256siv_stream_decryptor_new(
+        &decryptor, key, sizeof(key), nonce, sizeof(nonce) - 1
+    ) == MISCREANT_OK);
+
+    // A too small output buffer doesn't use up the message, so it can be
+    // retried with the required size
+    buflen = 4;
+    CHECK(miscreant_aes256siv_stream_decrypt_next(
+        decryptor, buf, &buflen, ct[0], ctlen[0], NULL, 0
+    ) == MISCREANT_ERR_BUFFER_TOO_SMALL);
+    CHECK(buflen == strlen(messages[0]));
+
+    for (int i = 0; i < 2; i++) {
+        buflen = i == 0 ? strlen(messages[0]) : sizeof(buf);
+        CHECK(miscreant_aes256siv_stream_decrypt_next(
+            decryptor, buf, &buflen, ct[i], ctlen[i], NULL, 0
+        ) == MISCREANT_OK);
+        CHECK(buflen == strlen(messages[i]));
+        CHECK(memcmp(buf, messages[i], buflen) == 0);
+    }
+
+    // The last message is detected, and must be decrypted as such
+    buflen = sizeof(buf);
+    CHECK(miscreant_aes256siv_stream_decrypt_next(
+        decryptor, buf, &buflen, ct[2], ctlen[2], NULL, 0
+    ) == MISCREANT_ERR_UNEXPECTED_LAST_BLOCK);
+
+    buflen = sizeof(buf);
+    CHECK(miscreant_aes256siv_stream_decrypt_last(
+        decryptor, buf, &buflen, ct[2], ctlen[2], NULL, 0
+    ) == MISCREANT_OK);
+    CHECK(buflen == strlen(messages[2]));
+    CHECK(memcmp(buf, messages[2], buflen) == 0);
+
+    miscreant_aes256siv_stream_decryptor_free(decryptor);
+}
+
 int main(int argc, char **argv) {
     test_aead_aes128siv();
     test_aead_aes256siv();
     test_aead_aes128pmacsiv();
     test_aead_aes256pmacsiv();
+    test_aes128siv_instance();
+    test_aes256siv_stream();
 
     printf("%s: success\n", argv[0]);
     return 0;
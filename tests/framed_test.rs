@@ -0,0 +1,74 @@
+use miscreant::{
+    decrypt_framed, Aead, Aes128PmacSivAead, Aes128SivAead, Aes256SivAead, Error, FramedAead,
+    FRAME_VERSION,
+};
+
+const KEY: [u8; 32] = [0x42; 32];
+
+#[test]
+fn framed_roundtrip() {
+    let framed = Aes128SivAead::new(&KEY).encrypt_framed(b"nonce", b"ad", b"plaintext");
+
+    assert_eq!(framed[0], FRAME_VERSION);
+    assert_eq!(framed[1], Aes128SivAead::ALGORITHM_ID);
+    assert_eq!(&framed[2..8], b"\x05nonce");
+    assert_eq!(
+        &framed[8..],
+        &Aes128SivAead::new(&KEY).encrypt(b"nonce", b"ad", b"plaintext")[..]
+    );
+
+    assert_eq!(decrypt_framed(&KEY, b"ad", &framed).unwrap(), b"plaintext");
+}
+
+#[test]
+fn framed_selects_algorithm() {
+    let cmac = Aes128SivAead::new(&KEY).encrypt_framed(b"nonce", b"", b"plaintext");
+    let pmac = Aes128PmacSivAead::new(&KEY).encrypt_framed(b"nonce", b"", b"plaintext");
+    assert_ne!(cmac[1], pmac[1]);
+
+    assert_eq!(decrypt_framed(&KEY, b"", &cmac).unwrap(), b"plaintext");
+    assert_eq!(decrypt_framed(&KEY, b"", &pmac).unwrap(), b"plaintext");
+
+    // Relabelling a message as another algorithm doesn't decrypt it
+    let mut relabelled = cmac.clone();
+    relabelled[1] = Aes128PmacSivAead::ALGORITHM_ID;
+    assert_eq!(
+        decrypt_framed(&KEY, b"", &relabelled),
+        Err(Error::AuthenticationFailed)
+    );
+
+    relabelled[1] = Aes256SivAead::ALGORITHM_ID;
+    assert_eq!(
+        decrypt_framed(&KEY, b"", &relabelled),
+        Err(Error::InvalidKeySize {
+            expected: 64,
+            got: 32
+        })
+    );
+}
+
+#[test]
+fn framed_unsupported() {
+    let mut framed = Aes128SivAead::new(&KEY).encrypt_framed(b"nonce", b"", b"plaintext");
+
+    framed[1] = 0xff;
+    assert_eq!(
+        decrypt_framed(&KEY, b"", &framed),
+        Err(Error::UnsupportedAlgorithm { id: 0xff })
+    );
+
+    framed[0] = 0;
+    assert_eq!(
+        decrypt_framed(&KEY, b"", &framed),
+        Err(Error::UnsupportedVersion { version: 0 })
+    );
+}
+
+#[test]
+fn framed_truncated() {
+    let framed = Aes128SivAead::new(&KEY).encrypt_framed(b"nonce", b"", b"");
+
+    for len in 0..framed.len() {
+        assert!(decrypt_framed(&KEY, b"", &framed[..len]).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use miscreant::{
+    incremental::{Aes128PmacSivEncryptor, Aes128SivEncryptor, Aes256SivEncryptor},
+    Aead, Aes128PmacSivAead, Aes128SivAead, Aes256SivAead, Error, Tag,
+};
+use std::{convert::TryFrom, io};
+
+const KEY: [u8; 32] = [0x42; 32];
+
+fn plaintext(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+#[test]
+fn incremental_matches_aead() {
+    for &len in &[0, 1, 15, 16, 17, 31, 32, 33, 100, 5000] {
+        let plaintext = plaintext(len);
+        let expected = Aes128SivAead::new(&KEY).encrypt(b"nonce", b"ad", &plaintext);
+
+        // Split the plaintext at various points, including within the final block
+        for &piece in &[1, 7, 16, 4096] {
+            let mut encryptor = Aes128SivEncryptor::new(&KEY);
+            encryptor.update_ad(b"ad");
+            encryptor.update_ad(b"nonce");
+
+            for chunk in plaintext.chunks(piece) {
+                encryptor.update_plaintext(chunk);
+            }
+
+            let mut ciphertext = vec![];
+            encryptor
+                .finalize_into(&plaintext[..], &mut ciphertext)
+                .unwrap();
+            assert_eq!(ciphertext, expected, "len: {}, piece: {}", len, piece);
+        }
+    }
+}
+
+#[test]
+fn incremental_pmac_and_aes_256() {
+    let plaintext = plaintext(1000);
+
+    let mut encryptor = Aes128PmacSivEncryptor::new(&KEY);
+    encryptor.update_ad(b"ad");
+    encryptor.update_ad(b"nonce");
+    encryptor.update_plaintext(&plaintext);
+    let mut ciphertext = vec![];
+    encryptor
+        .finalize_into(&plaintext[..], &mut ciphertext)
+        .unwrap();
+    assert_eq!(
+        ciphertext,
+        Aes128PmacSivAead::new(&KEY).encrypt(b"nonce", b"ad", &plaintext)
+    );
+
+    let mut encryptor = Aes256SivEncryptor::new(&[0x42; 64]);
+    encryptor.update_ad(b"ad");
+    encryptor.update_ad(b"nonce");
+    encryptor.update_plaintext(&plaintext);
+    let mut ciphertext = vec![];
+    encryptor
+        .finalize_into(&plaintext[..], &mut ciphertext)
+        .unwrap();
+    assert_eq!(
+        ciphertext,
+        Aes256SivAead::new(&[0x42; 64]).encrypt(b"nonce", b"ad", &plaintext)
+    );
+}
+
+#[test]
+fn incremental_plaintext_changed() {
+    let mut encryptor = Aes128SivEncryptor::new(&KEY);
+    encryptor.update_plaintext(&plaintext(100));
+
+    let err = encryptor
+        .finalize_into(&plaintext(101)[..], &mut vec![])
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn incremental_two_pass_decrypt() {
+    let plaintext = plaintext(1000);
+    let ciphertext = Aes128SivAead::new(&KEY).encrypt(b"nonce", b"ad", &plaintext);
+    let tag = Tag::try_from(&ciphertext[..16]).unwrap();
+
+    // First pass: decrypt with the keystream
+    let mut encryptor = Aes128SivEncryptor::new(&KEY);
+    let mut decrypted = ciphertext[16..].to_vec();
+    let mut keystream = encryptor.keystream(tag);
+
+    for chunk in decrypted.chunks_mut(100) {
+        keystream.apply_keystream(chunk);
+    }
+
+    assert_eq!(keystream.tag().as_ref(), tag.as_ref());
+
+    // Second pass: authenticate the decrypted plaintext
+    encryptor.update_ad(b"ad");
+    encryptor.update_ad(b"nonce");
+    encryptor.update_plaintext(&decrypted);
+    encryptor.verify(&tag).unwrap();
+    assert_eq!(decrypted, plaintext);
+}
+
+#[test]
+#[should_panic(expected = "associated data must be given before the plaintext")]
+fn incremental_ad_after_plaintext() {
+    let mut encryptor = Aes128SivEncryptor::new(&KEY);
+    encryptor.update_plaintext(b"plaintext");
+    encryptor.update_ad(b"ad");
+}
+
+#[test]
+fn incremental_verify_rejects_wrong_tag() {
+    let mut encryptor = Aes128SivEncryptor::new(&KEY);
+    encryptor.update_plaintext(b"plaintext");
+    assert_eq!(
+        encryptor.verify(&Tag::new([0u8; 16])),
+        Err(Error::AuthenticationFailed)
+    );
+}
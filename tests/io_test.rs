@@ -0,0 +1,238 @@
+#![cfg(feature = "std")]
+
+use miscreant::{
+    io::{StreamReader, StreamWriter},
+    stream::{Aes128SivDecryptor, Aes128SivEncryptor},
+    Error,
+};
+use std::{
+    cell::Cell,
+    io::{self, Read, Write},
+};
+
+const KEY: [u8; 32] = [0x42; 32];
+const NONCE: [u8; 8] = [0x24; 8];
+
+/// Split a length-prefixed STREAM into its messages
+fn split_messages(mut stream: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages = vec![];
+
+    while !stream.is_empty() {
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&stream[..4]);
+        let len = u32::from_be_bytes(len) as usize;
+        messages.push(stream[4..4 + len].to_vec());
+        stream = &stream[4 + len..];
+    }
+
+    messages
+}
+
+#[test]
+fn stream_writer_chunks() {
+    let plaintext: Vec<u8> = (0..100u8).collect();
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut writer = StreamWriter::with_chunk_size(encryptor, vec![], 32);
+    io::copy(&mut &plaintext[..], &mut writer).unwrap();
+
+    let messages = split_messages(&writer.finish().unwrap());
+    let lengths: Vec<_> = messages.iter().map(Vec::len).collect();
+    assert_eq!(lengths, [48, 48, 48, 20]);
+
+    let mut decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    let mut decrypted = vec![];
+
+    for message in &messages[..3] {
+        decrypted.extend(decryptor.decrypt_next(b"", message).unwrap());
+    }
+
+    decrypted.extend(decryptor.decrypt_last(b"", &messages[3]).unwrap());
+    assert_eq!(decrypted, plaintext);
+}
+
+#[test]
+fn stream_writer_empty() {
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let messages = split_messages(&StreamWriter::new(encryptor, vec![]).finish().unwrap());
+    assert_eq!(messages.len(), 1);
+
+    let decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    assert!(decryptor
+        .decrypt_last(b"", &messages[0])
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn stream_writer_exact_multiple() {
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut writer = StreamWriter::with_chunk_size(encryptor, vec![], 16);
+    writer.write_all(&[0u8; 32]).unwrap();
+
+    // Full messages are encrypted as soon as they fill, leaving an empty final message
+    let messages = split_messages(&writer.finish().unwrap());
+    let lengths: Vec<_> = messages.iter().map(Vec::len).collect();
+    assert_eq!(lengths, [32, 32, 16]);
+}
+
+/// Writer which accepts at most 7 bytes at a time, and fails every other
+/// call with `io::ErrorKind::WouldBlock` until failures are disabled
+struct FlakyWriter {
+    data: Vec<u8>,
+    calls: usize,
+    fail: Cell<bool>,
+}
+
+impl Write for FlakyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.calls += 1;
+
+        if self.fail.get() && self.calls % 2 == 0 {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        let len = buf.len().min(7);
+        self.data.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn stream_writer_resumes_after_error() {
+    let plaintext: Vec<u8> = (0..100).collect();
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let inner = FlakyWriter {
+        data: vec![],
+        calls: 0,
+        fail: Cell::new(true),
+    };
+    let mut writer = StreamWriter::with_chunk_size(encryptor, inner, 32);
+    let mut remaining = &plaintext[..];
+
+    while !remaining.is_empty() {
+        match writer.write(remaining) {
+            Ok(n) => remaining = &remaining[n..],
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::WouldBlock),
+        }
+    }
+
+    while writer.flush().is_err() {}
+    writer.get_ref().fail.set(false);
+
+    // Each message is encrypted and written exactly once
+    let ciphertext = writer.finish().unwrap().data;
+    assert_eq!(ciphertext, encrypt_stream(&plaintext, 32));
+}
+
+/// Encrypt the given plaintext as a STREAM using the writer adapter
+fn encrypt_stream(plaintext: &[u8], chunk_size: usize) -> Vec<u8> {
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut writer = StreamWriter::with_chunk_size(encryptor, vec![], chunk_size);
+    writer.write_all(plaintext).unwrap();
+    writer.finish().unwrap()
+}
+
+#[test]
+fn stream_reader_round_trip() {
+    let plaintext: Vec<u8> = (0..3 * 1024 * 1024 + 1234).map(|i| i as u8).collect();
+    let encryptor = Aes128SivEncryptor::new(&KEY, &NONCE);
+    let mut writer = StreamWriter::new(encryptor, vec![]);
+    io::copy(&mut &plaintext[..], &mut writer).unwrap();
+    let ciphertext = writer.finish().unwrap();
+
+    let decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    let mut reader = StreamReader::new(decryptor, &ciphertext[..]);
+    let mut decrypted = vec![];
+    reader.read_to_end(&mut decrypted).unwrap();
+    assert!(decrypted == plaintext);
+}
+
+#[test]
+fn stream_reader_truncated() {
+    let ciphertext = encrypt_stream(&[0u8; 100], 32);
+
+    // Drop the last message entirely
+    let messages = split_messages(&ciphertext);
+    let truncated = &ciphertext[..ciphertext.len() - 4 - messages[3].len()];
+
+    let decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    let mut reader = StreamReader::with_chunk_size(decryptor, truncated, 32);
+    let err = reader.read_to_end(&mut vec![]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn stream_reader_tampered() {
+    let mut ciphertext = encrypt_stream(&[0u8; 100], 32);
+    ciphertext[60] ^= 1;
+
+    let decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+    let mut reader = StreamReader::with_chunk_size(decryptor, &ciphertext[..], 32);
+    let err = reader.read_to_end(&mut vec![]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // The underlying error can be recovered from the `io::Error`
+    let inner = err.get_ref().unwrap().downcast_ref::<Error>().unwrap();
+    assert_eq!(*inner, Error::AuthenticationFailed);
+    assert_eq!(err.to_string(), "authentication failed");
+}
+
+#[test]
+fn error_into_io_error_kind() {
+    for &(err, kind) in &[
+        (Error::AuthenticationFailed, io::ErrorKind::InvalidData),
+        (Error::Encoding, io::ErrorKind::InvalidData),
+        (Error::UnexpectedLastBlock, io::ErrorKind::InvalidData),
+        (
+            Error::UnsupportedAlgorithm { id: 7 },
+            io::ErrorKind::InvalidData,
+        ),
+        (
+            Error::UnsupportedVersion { version: 2 },
+            io::ErrorKind::InvalidData,
+        ),
+        (Error::BufferTooSmall, io::ErrorKind::InvalidInput),
+        (
+            Error::InvalidKeySize {
+                expected: 32,
+                got: 16,
+            },
+            io::ErrorKind::InvalidInput,
+        ),
+        (
+            Error::InvalidNonceSize {
+                expected: 8,
+                got: 12,
+            },
+            io::ErrorKind::InvalidInput,
+        ),
+        (
+            Error::InvalidTagSize {
+                expected: 16,
+                got: 4,
+            },
+            io::ErrorKind::InvalidInput,
+        ),
+        (Error::MessageTooLong, io::ErrorKind::InvalidInput),
+        (Error::NonceReused, io::ErrorKind::InvalidInput),
+        (Error::UnknownAlgorithm, io::ErrorKind::InvalidInput),
+        (Error::CounterOverflow, io::ErrorKind::Other),
+    ] {
+        let io_err = io::Error::from(err);
+        assert_eq!(io_err.kind(), kind, "{:?}", err);
+        assert_eq!(io_err.into_inner().unwrap().downcast_ref(), Some(&err));
+    }
+
+    // `?` converts errors in functions returning `io::Result`
+    fn decrypt(ciphertext: &[u8]) -> io::Result<Vec<u8>> {
+        let mut decryptor = Aes128SivDecryptor::new(&KEY, &NONCE);
+        Ok(decryptor.decrypt_next(b"", ciphertext)?)
+    }
+
+    let err = decrypt(&[0u8; 16]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
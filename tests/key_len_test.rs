@@ -0,0 +1,41 @@
+#![cfg(feature = "const-generics")]
+
+use miscreant::{Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead, AesSivAead, KeyLen, SivKeyLen};
+
+/// Generic over the key length rather than the AEAD type
+fn encrypt<const KEY_LEN: usize>(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Vec<u8>
+where
+    KeyLen<KEY_LEN>: SivKeyLen,
+{
+    AesSivAead::<KEY_LEN>::new(key).encrypt(b"nonce", b"ad", plaintext)
+}
+
+#[test]
+fn key_len_aliases() {
+    // The selected types are the existing aliases
+    let _: Aes128SivAead = AesSivAead::<32>::new(&[0x42; 32]);
+    let _: Aes192SivAead = AesSivAead::<48>::new(&[0x42; 48]);
+    let _: Aes256SivAead = AesSivAead::<64>::new(&[0x42; 64]);
+    assert_eq!(AesSivAead::<48>::KEY_SIZE, 48);
+}
+
+#[test]
+fn key_len_generic() {
+    assert_eq!(
+        encrypt(&[0x42; 32], b"plaintext"),
+        Aes128SivAead::new(&[0x42; 32]).encrypt(b"nonce", b"ad", b"plaintext")
+    );
+    assert_eq!(
+        encrypt(&[0x42; 64], b"plaintext"),
+        Aes256SivAead::new(&[0x42; 64]).encrypt(b"nonce", b"ad", b"plaintext")
+    );
+}
+
+#[cfg(feature = "pmac")]
+#[test]
+fn key_len_pmac_aliases() {
+    use miscreant::{Aes128PmacSivAead, Aes256PmacSivAead, AesPmacSivAead};
+
+    let _: Aes128PmacSivAead = AesPmacSivAead::<32>::new(&[0x42; 32]);
+    let _: Aes256PmacSivAead = AesPmacSivAead::<64>::new(&[0x42; 64]);
+}
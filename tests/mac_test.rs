@@ -0,0 +1,96 @@
+mod mac_vectors;
+
+use self::mac_vectors::MacExample;
+use miscreant::{
+    mac::{Aes128Cmac, Aes128Pmac, Aes256Cmac, Aes256Pmac},
+    Error,
+};
+use std::path::Path;
+
+/// NIST SP 800-38B (and RFC 4493) examples
+#[test]
+fn aes_cmac_examples() {
+    let examples = MacExample::load_from_file(Path::new("tests/vectors/aes_cmac.tjson"));
+
+    for example in examples {
+        let tag = match example.key.len() {
+            16 => {
+                let mut mac = Aes128Cmac::new(&example.key);
+                mac.update(&example.message);
+                mac.finalize()
+            }
+            32 => {
+                let mut mac = Aes256Cmac::new(&example.key);
+                mac.update(&example.message);
+                mac.finalize()
+            }
+            _ => panic!("unexpected key size: {}", example.key.len()),
+        };
+
+        assert_eq!(&tag[..], &example.tag[..]);
+    }
+}
+
+#[test]
+fn aes_cmac_verify() {
+    let mut mac = Aes256Cmac::new(&[0x42; 32]);
+    mac.update(b"message");
+    let tag = mac.clone().finalize();
+
+    assert!(mac.clone().verify(&tag).is_ok());
+    assert_eq!(mac.verify(&tag[..15]), Err(Error::AuthenticationFailed));
+}
+
+#[test]
+fn aes_pmac_examples() {
+    let examples = MacExample::load_from_file(Path::new("tests/vectors/aes_pmac.tjson"));
+
+    for example in examples {
+        let tag = match example.key.len() {
+            16 => {
+                let mut mac = Aes128Pmac::new(&example.key);
+                mac.update(&example.message);
+                mac.finalize()
+            }
+            32 => {
+                let mut mac = Aes256Pmac::new(&example.key);
+                mac.update(&example.message);
+                mac.finalize()
+            }
+            _ => panic!("unexpected key size: {}", example.key.len()),
+        };
+
+        assert_eq!(&tag[..], &example.tag[..]);
+    }
+}
+
+#[test]
+fn aes_pmac_incremental() {
+    let key = [0x42; 16];
+    let message: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+
+    let mut mac = Aes128Pmac::new(&key);
+    mac.update(&message);
+    let expected = mac.finalize();
+
+    // Splitting the message at any point gives the same tag
+    for split in &[0, 1, 15, 16, 17, 127, 128, 129, 999, 1000] {
+        let mut mac = Aes128Pmac::new(&key);
+        mac.update(&message[..*split]);
+        mac.update(&message[*split..]);
+        assert_eq!(mac.finalize(), expected);
+    }
+
+    let mut mac = Aes128Pmac::new(&key);
+    mac.update(&message);
+    assert!(mac.clone().verify(&expected).is_ok());
+    assert_eq!(mac.verify(&[0; 16]), Err(Error::AuthenticationFailed));
+
+    assert_eq!(
+        Aes128Pmac::try_new(&[0; 32]).unwrap_err(),
+        Error::InvalidKeySize {
+            expected: 16,
+            got: 32
+        }
+    );
+}
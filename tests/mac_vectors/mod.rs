@@ -0,0 +1,40 @@
+use std::{fs::File, io::Read, path::Path};
+use subtle_encoding::hex;
+
+/// MAC test vectors (AES-CMAC and AES-PMAC)
+// TODO: switch to the tjson crate (based on serde)
+#[derive(Debug)]
+pub struct MacExample {
+    pub key: Vec<u8>,
+    pub message: Vec<u8>,
+    pub tag: Vec<u8>,
+}
+
+impl MacExample {
+    /// Load examples from a file at the given path
+    pub fn load_from_file(path: &Path) -> Vec<Self> {
+        let mut file = File::open(path).expect("valid MAC vectors file");
+        let mut tjson_string = String::new();
+        file.read_to_string(&mut tjson_string)
+            .expect("MAC vectors read successfully");
+
+        let tjson: serde_json::Value =
+            serde_json::from_str(&tjson_string).expect("MAC vectors parse successfully");
+        let examples = &tjson["examples:A<O>"]
+            .as_array()
+            .expect("MAC vectors examples array");
+
+        examples
+            .iter()
+            .map(|ex| Self {
+                key: decode_field(ex, "key:d16"),
+                message: decode_field(ex, "message:d16"),
+                tag: decode_field(ex, "tag:d16"),
+            })
+            .collect()
+    }
+}
+
+fn decode_field(example: &serde_json::Value, field: &str) -> Vec<u8> {
+    hex::decode(example[field].as_str().expect("encoded example").as_bytes()).expect("hex encoded")
+}
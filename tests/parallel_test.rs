@@ -0,0 +1,170 @@
+#![cfg(all(feature = "pmac", feature = "rayon"))]
+
+mod aead_vectors;
+
+use self::aead_vectors::AesSivAeadExample;
+use miscreant::{
+    stream::{Aes128PmacSivDecryptor, Aes128PmacSivEncryptor},
+    Aead, Aes128PmacSivAead, Aes192PmacSivAead, Aes256PmacSivAead, Error,
+};
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha8Rng,
+};
+
+fn encrypt_parallel<A: Aead>(
+    encrypt: fn(&mut A, &[u8], &[u8], &mut [u8]),
+    key: &[u8],
+    nonce: &[u8],
+    ad: &[u8],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let mut buffer = vec![0u8; plaintext.len() + 16];
+    buffer[16..].copy_from_slice(plaintext);
+    encrypt(&mut A::new(key), nonce, ad, &mut buffer);
+    buffer
+}
+
+#[test]
+fn aes_pmac_siv_examples_encrypt_parallel() {
+    for example in AesSivAeadExample::load_all() {
+        if example.alg != "AES-PMAC-SIV" {
+            continue;
+        }
+
+        let ciphertext = match example.key.len() {
+            32 => encrypt_parallel(
+                Aes128PmacSivAead::encrypt_in_place_parallel,
+                &example.key,
+                &example.nonce,
+                &example.ad,
+                &example.plaintext,
+            ),
+            48 => encrypt_parallel(
+                Aes192PmacSivAead::encrypt_in_place_parallel,
+                &example.key,
+                &example.nonce,
+                &example.ad,
+                &example.plaintext,
+            ),
+            64 => encrypt_parallel(
+                Aes256PmacSivAead::encrypt_in_place_parallel,
+                &example.key,
+                &example.nonce,
+                &example.ad,
+                &example.plaintext,
+            ),
+            _ => panic!("unexpected key size: {}", example.key.len()),
+        };
+
+        assert_eq!(ciphertext, example.ciphertext);
+    }
+}
+
+#[test]
+fn parallel_matches_serial() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let mut key = [0u8; 32];
+    rng.fill_bytes(&mut key);
+
+    // Lengths on either side of the threshold for splitting inputs across
+    // threads (64 KiB), and of the size of each task (16 KiB)
+    for &len in &[
+        16383,
+        65535,
+        65536,
+        65537,
+        65536 + 15,
+        65536 + 16,
+        65536 + 17,
+        16384 * 5 + 1,
+        (1 << 20) + 31,
+    ] {
+        let mut plaintext = vec![0u8; len];
+        rng.fill_bytes(&mut plaintext);
+
+        for ad in &[&b""[..], &plaintext[..70000.min(len)]] {
+            let expected = Aes128PmacSivAead::new(&key).encrypt(b"nonce", ad, &plaintext);
+            let ciphertext = encrypt_parallel(
+                Aes128PmacSivAead::encrypt_in_place_parallel,
+                &key,
+                b"nonce",
+                ad,
+                &plaintext,
+            );
+            assert!(ciphertext == expected, "len: {}, ad: {}", len, ad.len());
+
+            let mut buffer = ciphertext.clone();
+            let decrypted = Aes128PmacSivAead::new(&key)
+                .decrypt_in_place_parallel(b"nonce", ad, &mut buffer)
+                .unwrap();
+            assert!(decrypted == &plaintext[..], "len: {}", len);
+        }
+    }
+}
+
+#[test]
+fn decrypt_parallel_tampered() {
+    let key = [1u8; 32];
+    let plaintext = vec![2u8; 100000];
+
+    let mut ciphertext = Aes128PmacSivAead::new(&key).encrypt(b"nonce", b"", &plaintext);
+    ciphertext[50000] ^= 1;
+
+    let mut buffer = ciphertext.clone();
+    assert_eq!(
+        Aes128PmacSivAead::new(&key).decrypt_in_place_parallel(b"nonce", b"", &mut buffer),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // The unauthenticated plaintext must not be left in the buffer
+    assert!(buffer.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn stream_encrypt_parallel() {
+    let key = [0x42u8; 32];
+    let nonce = [7u8; 8];
+    let plaintext = vec![3u8; 10000];
+
+    for &len in &[0, 1, 4095, 4096, 4097, 10000] {
+        let expected: Vec<_> = Aes128PmacSivEncryptor::new(&key, &nonce)
+            .encrypt_chunks(b"ad", &plaintext[..len], 4096)
+            .collect();
+
+        assert_eq!(
+            Aes128PmacSivEncryptor::encrypt_parallel(&key, &nonce, b"ad", &plaintext[..len], 4096),
+            expected
+        );
+    }
+}
+
+#[test]
+fn stream_decrypt_parallel() {
+    let key = [0x42u8; 32];
+    let nonce = [7u8; 8];
+    let plaintext: Vec<u8> = (0..10000).map(|i| i as u8).collect();
+
+    let messages = Aes128PmacSivEncryptor::encrypt_parallel(&key, &nonce, b"ad", &plaintext, 4096);
+    let mut refs: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+    assert_eq!(
+        Aes128PmacSivDecryptor::decrypt_parallel(&key, &nonce, b"ad", &refs).unwrap(),
+        plaintext
+    );
+
+    // Truncated, reordered, and empty STREAMs are all rejected
+    for bad in &[&refs[..2], &[refs[1], refs[0], refs[2]][..], &[]] {
+        assert_eq!(
+            Aes128PmacSivDecryptor::decrypt_parallel(&key, &nonce, b"ad", bad),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    let mut tampered = messages[1].clone();
+    tampered[100] ^= 1;
+    refs[1] = &tampered;
+    assert_eq!(
+        Aes128PmacSivDecryptor::decrypt_parallel(&key, &nonce, b"ad", &refs),
+        Err(Error::AuthenticationFailed)
+    );
+}
@@ -0,0 +1,108 @@
+//! Property-based tests: round trips and tamper detection on arbitrary inputs
+
+use miscreant::{
+    stream::{Decryptor, Encryptor},
+    Aead, Aes128PmacSivAead, Aes128SivAead, Aes192PmacSivAead, Aes192SivAead, Aes256PmacSivAead,
+    Aes256SivAead, Error,
+};
+use proptest::{collection::vec, prelude::*};
+
+/// Encrypt and decrypt the given plaintext, and check that flipping the bit
+/// at `flip` (modulo the ciphertext length in bits) fails to authenticate
+fn check_round_trip<A: Aead>(
+    key: &[u8],
+    nonce: &[u8],
+    ad: &[u8],
+    plaintext: &[u8],
+    flip: usize,
+) -> Result<(), TestCaseError> {
+    let mut aead = A::new(&key[..A::KEY_SIZE]);
+    let mut ciphertext = aead.encrypt(nonce, ad, plaintext);
+    prop_assert_eq!(ciphertext.len(), A::TAG_SIZE + plaintext.len());
+    prop_assert_eq!(aead.decrypt(nonce, ad, &ciphertext), Ok(plaintext.to_vec()));
+
+    let bit = flip % (ciphertext.len() * 8);
+    ciphertext[bit / 8] ^= 1 << (bit % 8);
+    prop_assert_eq!(
+        aead.decrypt(nonce, ad, &ciphertext),
+        Err(Error::AuthenticationFailed)
+    );
+
+    Ok(())
+}
+
+/// Encrypt the given messages as a STREAM, decrypt them again, and check
+/// that the last message can't be decrypted as if the stream continued
+fn check_stream_round_trip<A: Aead>(
+    key: &[u8],
+    nonce: &[u8],
+    messages: &[(Vec<u8>, Vec<u8>)],
+) -> Result<(), TestCaseError> {
+    let key = &key[..A::KEY_SIZE];
+    let (last, init) = messages.split_last().unwrap();
+
+    let mut encryptor = Encryptor::<A>::new(key, nonce);
+    let mut ciphertexts: Vec<Vec<u8>> = init
+        .iter()
+        .map(|(ad, plaintext)| encryptor.encrypt_next(ad, plaintext))
+        .collect();
+    ciphertexts.push(encryptor.encrypt_last(&last.0, &last.1));
+
+    let mut decryptor = Decryptor::<A>::new(key, nonce);
+    for ((ad, plaintext), ciphertext) in init.iter().zip(&ciphertexts) {
+        prop_assert_eq!(
+            decryptor.decrypt_next(ad, ciphertext),
+            Ok(plaintext.clone())
+        );
+    }
+    prop_assert_eq!(
+        decryptor.decrypt_last(&last.0, ciphertexts.last().unwrap()),
+        Ok(last.1.clone())
+    );
+
+    // The last message decrypted as if it weren't
+    let mut decryptor = Decryptor::<A>::new(key, nonce);
+    for (ad, ciphertext) in init.iter().map(|(ad, _)| ad).zip(&ciphertexts) {
+        prop_assert!(decryptor.decrypt_next(ad, ciphertext).is_ok());
+    }
+    prop_assert_eq!(
+        decryptor.decrypt_next(&last.0, ciphertexts.last().unwrap()),
+        Err(Error::UnexpectedLastBlock)
+    );
+
+    Ok(())
+}
+
+fn bytes(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..max_len)
+}
+
+proptest! {
+    #[test]
+    fn aes_siv_aead_round_trip(
+        key in vec(any::<u8>(), 64),
+        nonce in bytes(32),
+        ad in bytes(64),
+        plaintext in bytes(256),
+        flip in any::<usize>(),
+    ) {
+        check_round_trip::<Aes128SivAead>(&key, &nonce, &ad, &plaintext, flip)?;
+        check_round_trip::<Aes192SivAead>(&key, &nonce, &ad, &plaintext, flip)?;
+        check_round_trip::<Aes256SivAead>(&key, &nonce, &ad, &plaintext, flip)?;
+        check_round_trip::<Aes128PmacSivAead>(&key, &nonce, &ad, &plaintext, flip)?;
+        check_round_trip::<Aes192PmacSivAead>(&key, &nonce, &ad, &plaintext, flip)?;
+        check_round_trip::<Aes256PmacSivAead>(&key, &nonce, &ad, &plaintext, flip)?;
+    }
+
+    #[test]
+    fn aes_siv_stream_round_trip(
+        key in vec(any::<u8>(), 64),
+        nonce in vec(any::<u8>(), 8),
+        messages in vec((bytes(16), bytes(64)), 1..8),
+    ) {
+        check_stream_round_trip::<Aes128SivAead>(&key, &nonce, &messages)?;
+        check_stream_round_trip::<Aes256SivAead>(&key, &nonce, &messages)?;
+        check_stream_round_trip::<Aes128PmacSivAead>(&key, &nonce, &messages)?;
+        check_stream_round_trip::<Aes256PmacSivAead>(&key, &nonce, &messages)?;
+    }
+}
@@ -0,0 +1,242 @@
+//! AES-SIV test vectors from RFC 5297 Appendix A, including the intermediate
+//! S2V values, so a failure points at the step of the computation which
+//! diverged from the RFC.
+
+use aes::{Aes128, Aes256};
+use cmac::Cmac;
+use crypto_mac::Mac;
+use miscreant::{
+    generic_array::GenericArray, Aead, Aes128PmacSivAead, Aes128Siv, Aes128SivAead,
+    Aes256PmacSivAead, Aes256SivAead,
+};
+use pmac_crate::Pmac;
+use subtle_encoding::hex;
+
+/// An RFC 5297 example
+struct Example {
+    name: &'static str,
+    key: &'static str,
+    headers: &'static [&'static str],
+    plaintext: &'static str,
+    s2v: &'static [(&'static str, &'static str)],
+    output: &'static str,
+}
+
+/// RFC 5297 Appendix A.1
+const DETERMINISTIC: Example = Example {
+    name: "A.1 Deterministic Authenticated Encryption Example",
+    key: "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+    headers: &["101112131415161718191a1b1c1d1e1f2021222324252627"],
+    plaintext: "112233445566778899aabbccddee",
+    s2v: &[
+        ("CMAC(zero)", "0e04dfafc1efbf040140582859bf073a"),
+        ("double()", "1c09bf5f83df7e080280b050b37e0e74"),
+        ("CMAC(ad)", "f1f922b7f5193ce64ff80cb47d93f23b"),
+        ("xor", "edf09de876c642ee4d78bce4ceedfc4f"),
+        ("double()", "dbe13bd0ed8c85dc9af179c99ddbf819"),
+        ("pad", "112233445566778899aabbccddee8000"),
+        ("xor", "cac30894b8eaf254035bc20540357819"),
+        ("CMAC(final)", "85632d07c6e8f37f950acd320a2ecc93"),
+    ],
+    output: "85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c",
+};
+
+/// RFC 5297 Appendix A.2 (the nonce is the last header)
+const NONCE_BASED: Example = Example {
+    name: "A.2 Nonce-Based Authenticated Encryption Example",
+    key: "7f7e7d7c7b7a79787776757473727170404142434445464748494a4b4c4d4e4f",
+    headers: &[
+        "00112233445566778899aabbccddeeffdeaddadadeaddadaffeeddccbbaa99887766554433221100",
+        "102030405060708090a0",
+        "09f911029d74e35bd84156c5635688c0",
+    ],
+    plaintext: "7468697320697320736f6d6520706c61696e7465787420746f20656e6372797074207573696e67205349562d414553",
+    s2v: &[
+        ("CMAC(zero)", "c8b43b5974960e7ce6a5dd85231e591a"),
+        ("double()", "916876b2e92c1cf9cd4bbb0a463cb2b3"),
+        ("CMAC(ad1)", "3c9b689ab41102e4809547141dd0d15a"),
+        ("xor", "adf31e285d3d1e1d4ddefc1e5bec63e9"),
+        ("double()", "5be63c50ba7a3c3a9bbdf83cb7d8c755"),
+        ("CMAC(ad2)", "d98c9b0be42cb2d7aa98478ed11eda1b"),
+        ("xor", "826aa75b5e568eed3125bfb266c61d4e"),
+        ("double()", "04d54eb6bcad1dda624b7f64cd8c3a1b"),
+        ("CMAC(nonce)", "128c62a1ce3747a8372c1c05a538b96d"),
+        ("xor", "16592c17729a5a725567636168b48376"),
+        (
+            "xorend",
+            "7468697320697320736f6d6520706c61696e7465787420746f20656e637279662d0c6201f3341575342a3745f5c625",
+        ),
+        ("CMAC(final)", "7bdb6e3b432667eb06f4d14bff2fbd0f"),
+    ],
+    output: "7bdb6e3b432667eb06f4d14bff2fbd0fcb900f2fddbe404326601965c889bf17dba77ceb094fa663b7a3f748ba8af829ea64ad544a272e9c485b62a3fd5c0d",
+};
+
+fn decode(s: &str) -> Vec<u8> {
+    hex::decode(s).expect("hex encoded")
+}
+
+/// Compute S2V step-by-step (as in RFC 5297 Section 2.4) using the same
+/// CMAC (or PMAC) primitive as the crate, returning each intermediate value
+fn s2v_steps<M: Mac>(mac_key: &[u8], headers: &[Vec<u8>], plaintext: &[u8]) -> Vec<Vec<u8>> {
+    let mut mac = M::new_varkey(mac_key).unwrap();
+    let mut prf = |input: &[u8]| {
+        mac.input(input);
+        mac.result_reset().code().to_vec()
+    };
+
+    let mut steps = vec![];
+    let mut d = prf(&[0u8; 16]);
+    steps.push(d.clone());
+
+    for header in headers {
+        d = dbl(&d);
+        steps.push(d.clone());
+        let code = prf(header);
+        steps.push(code.clone());
+        d = xor(&d, &code);
+        steps.push(d.clone());
+    }
+
+    let t = if plaintext.len() >= 16 {
+        let n = plaintext.len() - 16;
+        let mut t = plaintext[..n].to_vec();
+        t.extend(xor(&plaintext[n..], &d));
+        t
+    } else {
+        d = dbl(&d);
+        steps.push(d.clone());
+        let mut padded = plaintext.to_vec();
+        padded.push(0x80);
+        padded.resize(16, 0);
+        steps.push(padded.clone());
+        xor(&d, &padded)
+    };
+
+    steps.push(t.clone());
+    steps.push(prf(&t));
+    steps
+}
+
+/// Multiply by x in GF(2^128)
+fn dbl(block: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; 16];
+
+    for i in 0..16 {
+        result[i] = block[i] << 1 | block.get(i + 1).map(|b| b >> 7).unwrap_or(0);
+    }
+
+    if block[0] & 0x80 != 0 {
+        result[15] ^= 0x87;
+    }
+
+    result
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+fn check_example(example: &Example) {
+    let key = decode(example.key);
+    let headers: Vec<_> = example.headers.iter().map(|h| decode(h)).collect();
+    let plaintext = decode(example.plaintext);
+
+    // The first half of the key is the MAC (S2V) key
+    let steps = s2v_steps::<Cmac<Aes128>>(&key[..16], &headers, &plaintext);
+    assert_eq!(steps.len(), example.s2v.len(), "{}", example.name);
+
+    for (i, (actual, (step, expected))) in steps.iter().zip(example.s2v).enumerate() {
+        assert_eq!(
+            actual,
+            &decode(expected),
+            "{}: S2V step {} ({}) diverged",
+            example.name,
+            i + 1,
+            step
+        );
+    }
+
+    let mut siv = Aes128Siv::new(GenericArray::clone_from_slice(&key));
+    let output = siv.encrypt(&headers, &plaintext).unwrap();
+    assert_eq!(
+        &output[..16],
+        &steps[steps.len() - 1][..],
+        "{}: SIV differs from S2V output",
+        example.name
+    );
+    assert_eq!(output, decode(example.output), "{}", example.name);
+    assert_eq!(siv.decrypt(&headers, &output).unwrap(), plaintext);
+}
+
+#[test]
+fn rfc5297_deterministic_example() {
+    check_example(&DETERMINISTIC);
+}
+
+#[test]
+fn rfc5297_deterministic_example_aead() {
+    let key = decode(DETERMINISTIC.key);
+    let ad = decode(DETERMINISTIC.headers[0]);
+    let plaintext = decode(DETERMINISTIC.plaintext);
+
+    let mut aead = Aes128SivAead::new(&key);
+    let ciphertext = aead.seal_deterministic(&ad, &plaintext);
+    assert_eq!(ciphertext, decode(DETERMINISTIC.output));
+    assert_eq!(
+        aead.open_deterministic(&ad, &ciphertext).unwrap(),
+        plaintext
+    );
+
+    // Deterministic mode has no nonce header, so it differs from using an
+    // empty nonce
+    assert_ne!(ciphertext, aead.encrypt(b"", &ad, &plaintext));
+
+    let mut tampered = ciphertext.clone();
+    tampered[20] ^= 1;
+    assert!(aead.open_deterministic(&ad, &tampered).is_err());
+    assert!(aead.open_deterministic(&ad, &ciphertext[..15]).is_err());
+}
+
+#[test]
+fn rfc5297_nonce_based_example() {
+    check_example(&NONCE_BASED);
+}
+
+/// Check the SIV tags produced by an AEAD type against the step-by-step S2V
+/// for associated data of various lengths (on either side of the block size
+/// and spanning several blocks), which exercise the padding and doubling of
+/// the final block
+fn check_ad_lengths<A: Aead, M: Mac>(name: &str) {
+    let key: Vec<u8> = (0..A::KEY_SIZE as u8).collect();
+    let nonce = b"nonce".to_vec();
+
+    for &ad_len in &[0, 1, 15, 16, 17, 31, 32, 33, 48, 100] {
+        let ad: Vec<u8> = (0..ad_len as u8).map(|b| b.wrapping_mul(7)).collect();
+
+        for &plaintext_len in &[0, 15, 16, 17, 40] {
+            let plaintext = vec![0xa5; plaintext_len];
+            let headers = [ad.clone(), nonce.clone()];
+            let steps = s2v_steps::<M>(&key[..key.len() / 2], &headers, &plaintext);
+
+            let mut aead = A::new(&key);
+            let ciphertext = aead.encrypt(&nonce, &ad, &plaintext);
+            assert_eq!(
+                &ciphertext[..16],
+                &steps[steps.len() - 1][..],
+                "{}: ad {} bytes, plaintext {} bytes",
+                name,
+                ad_len,
+                plaintext_len
+            );
+            assert_eq!(aead.decrypt(&nonce, &ad, &ciphertext).unwrap(), plaintext);
+        }
+    }
+}
+
+#[test]
+fn associated_data_lengths() {
+    check_ad_lengths::<Aes128SivAead, Cmac<Aes128>>("AES-128-SIV");
+    check_ad_lengths::<Aes256SivAead, Cmac<Aes256>>("AES-256-SIV");
+    check_ad_lengths::<Aes128PmacSivAead, Pmac<Aes128>>("AES-128-PMAC-SIV");
+    check_ad_lengths::<Aes256PmacSivAead, Pmac<Aes256>>("AES-256-PMAC-SIV");
+}
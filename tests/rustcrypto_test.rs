@@ -0,0 +1,93 @@
+#![cfg(feature = "rustcrypto-aead")]
+
+use aead_crate::{generic_array::GenericArray, AeadInPlace, Key, KeySizeUser, Payload};
+use miscreant::{Aead, Aes128PmacSivAead, Aes128SivAead, Aes256PmacSivAead, Aes256SivAead};
+
+/// Encrypt and decrypt with an AEAD through the RustCrypto traits alone
+fn seal_and_open<A: aead_crate::Aead>(aead: &A, nonce: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let nonce = GenericArray::from_slice(nonce);
+    let payload = Payload {
+        msg: plaintext,
+        aad: b"associated data",
+    };
+
+    let ciphertext = aead.encrypt(nonce, payload).unwrap();
+    let payload = Payload {
+        msg: &ciphertext,
+        aad: b"associated data",
+    };
+    assert_eq!(aead.decrypt(nonce, payload).unwrap(), plaintext);
+
+    let payload = Payload {
+        msg: &ciphertext,
+        aad: b"other associated data",
+    };
+    assert!(aead.decrypt(nonce, payload).is_err());
+
+    ciphertext
+}
+
+#[test]
+fn rustcrypto_aead_matches_encrypt() {
+    let nonce = [7u8; 16];
+
+    let mut aead = Aes128SivAead::new(&[0x42; 32]);
+    let expected = aead.encrypt(&nonce, b"associated data", b"plaintext");
+    assert_eq!(seal_and_open(&aead, &nonce, b"plaintext"), expected);
+
+    let mut aead = Aes256SivAead::new(&[0x42; 64]);
+    let expected = aead.encrypt(&nonce, b"associated data", b"");
+    assert_eq!(seal_and_open(&aead, &nonce, b""), expected);
+
+    let mut aead = Aes128PmacSivAead::new(&[0x42; 32]);
+    let expected = aead.encrypt(&nonce, b"associated data", &[0xa5; 100]);
+    assert_eq!(seal_and_open(&aead, &nonce, &[0xa5; 100]), expected);
+}
+
+#[test]
+fn rustcrypto_aead_in_place_detached() {
+    let aead = Aes128SivAead::new(&[0x42; 32]);
+    let nonce = GenericArray::from_slice(&[7u8; 16]);
+
+    let mut buffer = *b"plaintext";
+    let tag = aead
+        .encrypt_in_place_detached(nonce, b"ad", &mut buffer)
+        .unwrap();
+
+    let expected = Aes128SivAead::new(&[0x42; 32]).encrypt(&[7u8; 16], b"ad", b"plaintext");
+    assert_eq!(&tag[..], &expected[..16]);
+    assert_eq!(&buffer[..], &expected[16..]);
+
+    aead.decrypt_in_place_detached(nonce, b"ad", &mut buffer, &tag)
+        .unwrap();
+    assert_eq!(&buffer, b"plaintext");
+
+    let mut buffer = Vec::from(&expected[..15]);
+    assert!(aead.decrypt_in_place(nonce, b"ad", &mut buffer).is_err());
+}
+
+/// Construct an AEAD generically from a `GenericArray` key
+fn new_generic<A: aead_crate::KeyInit>(key: &Key<A>) -> A {
+    A::new(key)
+}
+
+#[test]
+fn rustcrypto_key_init() {
+    use aead_crate::KeyInit;
+
+    assert_eq!(Aes128SivAead::key_size(), 32);
+    assert_eq!(Aes256PmacSivAead::key_size(), 64);
+
+    let key = [0x42; 32];
+    let nonce = [7u8; 16];
+    let expected =
+        <Aes128SivAead as Aead>::new(&key).encrypt(&nonce, b"associated data", b"plaintext");
+
+    let aead: Aes128SivAead = new_generic(GenericArray::from_slice(&key));
+    assert_eq!(seal_and_open(&aead, &nonce, b"plaintext"), expected);
+
+    let aead = Aes128SivAead::new_from_slice(&key).unwrap();
+    assert_eq!(seal_and_open(&aead, &nonce, b"plaintext"), expected);
+
+    assert!(Aes128SivAead::new_from_slice(&[0; 16]).is_err());
+}
@@ -1,7 +1,10 @@
 mod siv_vectors;
 
 use self::siv_vectors::{AesPmacSivExample, AesSivExample};
-use miscreant::{generic_array::GenericArray, Aes128PmacSiv, Aes128Siv, Aes256PmacSiv, Aes256Siv};
+use miscreant::{
+    generic_array::GenericArray, Aead, Aes128PmacSiv, Aes128Siv, Aes128SivAead, Aes256PmacSiv,
+    Aes256Siv,
+};
 
 #[test]
 fn aes_siv_examples_encrypt() {
@@ -74,3 +77,18 @@ fn aes_pmac_siv_examples_decrypt() {
         assert_eq!(plaintext, example.plaintext);
     }
 }
+
+#[test]
+fn aes_siv_nonce_is_last_header() {
+    let key = [0x42u8; 32];
+    let nonce = [0x24u8; 16];
+
+    let ciphertext = Aes128Siv::new(GenericArray::clone_from_slice(&key))
+        .encrypt(&[&b"ad"[..], &nonce], b"plaintext")
+        .unwrap();
+
+    assert_eq!(
+        ciphertext,
+        Aes128SivAead::new(&key).encrypt(&nonce, b"ad", b"plaintext")
+    );
+}
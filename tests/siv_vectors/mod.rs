@@ -1,4 +1,3 @@
-pub use serde_json::Value as JsonValue;
 use std::{fs::File, io::Read, path::Path};
 use subtle_encoding::hex;
 
@@ -15,12 +14,12 @@ pub struct AesSivExample {
 impl AesSivExample {
     /// Load examples from aes_siv.tjson
     pub fn load_all() -> Vec<Self> {
-        Self::load_from_file(Path::new("vectors/aes_siv.tjson"))
+        Self::load_from_file(Path::new("tests/vectors/aes_siv.tjson"))
     }
 
     /// Load examples from a file at the given path
     pub fn load_from_file(path: &Path) -> Vec<Self> {
-        let mut file = File::open(&path).expect("valid aes_siv.tjson");
+        let mut file = File::open(path).expect("valid aes_siv.tjson");
         let mut tjson_string = String::new();
         file.read_to_string(&mut tjson_string)
             .expect("aes_siv.tjson read successfully");
@@ -32,7 +31,7 @@ impl AesSivExample {
             .expect("aes_siv.tjson examples array");
 
         examples
-            .into_iter()
+            .iter()
             .map(|ex| Self {
                 key: hex::decode(ex["key:d16"].as_str().expect("encoded example").as_bytes())
                     .expect("hex encoded"),
@@ -77,12 +76,12 @@ pub struct AesPmacSivExample {
 impl AesPmacSivExample {
     /// Load examples from aes_pmac_siv.tjson
     pub fn load_all() -> Vec<Self> {
-        Self::load_from_file(Path::new("vectors/aes_pmac_siv.tjson"))
+        Self::load_from_file(Path::new("tests/vectors/aes_pmac_siv.tjson"))
     }
 
     /// Load examples from a file at the given path
     pub fn load_from_file(path: &Path) -> Vec<Self> {
-        let mut file = File::open(&path).expect("valid aes_pmac_siv.tjson");
+        let mut file = File::open(path).expect("valid aes_pmac_siv.tjson");
         let mut tjson_string = String::new();
         file.read_to_string(&mut tjson_string)
             .expect("aes_pmac_siv.tjson read successfully");
@@ -94,7 +93,7 @@ impl AesPmacSivExample {
             .expect("aes_pmac_siv.tjson examples array");
 
         examples
-            .into_iter()
+            .iter()
             .map(|ex| Self {
                 key: hex::decode(ex["key:d16"].as_str().expect("encoded example").as_bytes())
                     .expect("hex encoded"),
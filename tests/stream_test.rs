@@ -4,10 +4,13 @@ use self::stream_vectors::{AesSivStreamExample, Block};
 use miscreant::{
     stream::{
         Aes128PmacSivDecryptor, Aes128PmacSivEncryptor, Aes128SivDecryptor, Aes128SivEncryptor,
-        Aes256PmacSivDecryptor, Aes256PmacSivEncryptor, Aes256SivDecryptor, Aes256SivEncryptor,
-        Decryptor, Encryptor,
+        Aes128SivEncryptor64, Aes192PmacSivDecryptor, Aes192PmacSivEncryptor, Aes192SivDecryptor,
+        Aes192SivEncryptor, Aes256PmacSivDecryptor, Aes256PmacSivEncryptor, Aes256SivDecryptor,
+        Aes256SivDecryptor64, Aes256SivEncryptor, Aes256SivEncryptor64, Counter, Decryptor,
+        Decryptor96, Encryptor, Encryptor96, NonceEncoder32, NonceEncoder64, NonceTracker,
+        PrefixedNonceEncoder, StreamState, NONCE_SIZE_64, NONCE_SIZE_96,
     },
-    Aead,
+    Aead, Aes128SivAead, Error, Zeroizing,
 };
 
 #[test]
@@ -79,3 +82,1161 @@ fn test_decryptor<A: Aead>(mut decryptor: Decryptor<A>, blocks: &[Block]) {
         }
     }
 }
+
+#[test]
+fn aes_siv_stream64_nonce_layout() {
+    let key = [0x42u8; 32];
+    let prefix = [0x01, 0x02, 0x03, 0x04];
+    let mut encryptor = Aes128SivEncryptor64::new(&key, &prefix);
+    let mut aead = Aes128SivAead::new(&key);
+
+    // Reference layout: 4-byte prefix || 64-bit big endian counter || last block flag
+    let mut nonce = [0u8; 13];
+    nonce[..4].copy_from_slice(&prefix);
+
+    for counter in 0..3u64 {
+        nonce[4..12].copy_from_slice(&counter.to_be_bytes());
+        let expected = aead.encrypt(&nonce, b"ad", b"chunk");
+        assert_eq!(encryptor.encrypt_next(b"ad", b"chunk"), expected);
+    }
+
+    nonce[4..12].copy_from_slice(&3u64.to_be_bytes());
+    nonce[12] = 1;
+    let expected = aead.encrypt(&nonce, b"ad", b"last");
+    assert_eq!(encryptor.encrypt_last(b"ad", b"last"), expected);
+}
+
+#[test]
+fn aes_siv_stream64_round_trip() {
+    let key = [0x42u8; 64];
+    let nonce = [0u8; NONCE_SIZE_64];
+    let mut encryptor = Aes256SivEncryptor64::new(&key, &nonce);
+    let mut decryptor = Aes256SivDecryptor64::new(&key, &nonce);
+
+    let ciphertext = encryptor.encrypt_next(b"", b"first");
+    assert_eq!(decryptor.decrypt_next(b"", &ciphertext).unwrap(), b"first");
+
+    let ciphertext = encryptor.encrypt_last(b"", b"last");
+    assert!(Aes256SivDecryptor64::new(&key, &nonce)
+        .decrypt_last(b"", &ciphertext)
+        .is_err());
+    assert_eq!(decryptor.decrypt_last(b"", &ciphertext).unwrap(), b"last");
+}
+
+/// Example of a downstream counter: 7-byte prefix and 40-bit counter
+struct NonceEncoder40 {
+    value: [u8; 13],
+    counter: u64,
+}
+
+impl Counter for NonceEncoder40 {
+    type Nonce = [u8; 13];
+    type Position = u64;
+    type Prefix = [u8; 7];
+    const MAX_POSITION: u64 = (1 << 40) - 1;
+
+    fn new(prefix: &[u8]) -> Self {
+        Self::with_position(prefix, 0)
+    }
+
+    fn with_position(prefix: &[u8], position: u64) -> Self {
+        let mut value = [0u8; 13];
+        value[..7].copy_from_slice(prefix);
+        value[7..12].copy_from_slice(&position.to_be_bytes()[3..]);
+        Self {
+            value,
+            counter: position,
+        }
+    }
+
+    fn prefix(&self) -> [u8; 7] {
+        let mut prefix = [0u8; 7];
+        prefix.copy_from_slice(&self.value[..7]);
+        prefix
+    }
+
+    fn increment(&mut self) {
+        self.counter += 1;
+        assert!(self.counter < 1 << 40, "counter overflowed");
+        self.value[7..12].copy_from_slice(&self.counter.to_be_bytes()[3..]);
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.value
+    }
+
+    fn position(&self) -> u64 {
+        self.counter
+    }
+
+    fn final_nonce(&self) -> [u8; 13] {
+        let mut value = self.value;
+        value[12] = 1;
+        value
+    }
+}
+
+#[test]
+fn aes_siv_stream_custom_counter() {
+    let key = [0x42u8; 32];
+    let prefix = [0x07u8; 7];
+    let mut encryptor = Encryptor::<Aes128SivAead, NonceEncoder40>::new(&key, &prefix);
+    let mut decryptor = Decryptor::<Aes128SivAead, NonceEncoder40>::new(&key, &prefix);
+
+    let mut nonce = [0u8; 13];
+    nonce[..7].copy_from_slice(&prefix);
+    nonce[11] = 1;
+
+    let first = encryptor.encrypt_next(b"", b"first");
+    let second = encryptor.encrypt_next(b"", b"second");
+    assert_eq!(
+        second,
+        Aes128SivAead::new(&key).encrypt(&nonce, b"", b"second")
+    );
+
+    let last = encryptor.encrypt_last(b"", b"last");
+    assert_eq!(decryptor.decrypt_next(b"", &first).unwrap(), b"first");
+    assert_eq!(decryptor.decrypt_next(b"", &second).unwrap(), b"second");
+    assert_eq!(decryptor.decrypt_last(b"", &last).unwrap(), b"last");
+}
+
+#[test]
+fn aes_siv_stream_position() {
+    let key = [0x42u8; 32];
+    let nonce = [0u8; 8];
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(encryptor.position(), 0);
+
+    for i in 0..3 {
+        let ciphertext = encryptor.encrypt_next(b"", b"chunk");
+        assert_eq!(encryptor.position(), i + 1);
+
+        assert_eq!(decryptor.position(), i);
+        decryptor.decrypt_next(b"", &ciphertext).unwrap();
+    }
+
+    assert_eq!(encryptor.position(), decryptor.position());
+}
+
+#[test]
+fn aes_siv_stream_save_and_restore() {
+    let key = [0x42u8; 32];
+    let nonce = [0x24u8; 8];
+    let mut uninterrupted = Aes128SivEncryptor::new(&key, &nonce);
+    let expected: Vec<_> = (0..4)
+        .map(|_| uninterrupted.encrypt_next(b"", b"chunk"))
+        .collect();
+    let expected_last = uninterrupted.encrypt_last(b"", b"last");
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+
+    for ciphertext in &expected[..2] {
+        assert_eq!(&encryptor.encrypt_next(b"", b"chunk"), ciphertext);
+        decryptor.decrypt_next(b"", ciphertext).unwrap();
+    }
+
+    let state = encryptor.save_state();
+    assert_eq!(state.nonce, nonce);
+    assert_eq!(state.position, 2);
+    drop(encryptor);
+
+    let mut encryptor = Aes128SivEncryptor::restore(&key, state);
+    let mut decryptor = Aes128SivDecryptor::restore(&key, decryptor.save_state());
+
+    for ciphertext in &expected[2..] {
+        assert_eq!(&encryptor.encrypt_next(b"", b"chunk"), ciphertext);
+        assert_eq!(decryptor.decrypt_next(b"", ciphertext).unwrap(), b"chunk");
+    }
+
+    assert_eq!(encryptor.encrypt_last(b"", b"last"), expected_last);
+    assert_eq!(
+        decryptor.decrypt_last(b"", &expected_last).unwrap(),
+        b"last"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn aes_siv_stream_state_serde() {
+    let key = [0x42u8; 64];
+    let mut encryptor = Aes256SivEncryptor64::new(&key, &[1, 2, 3, 4]);
+    encryptor.encrypt_next(b"", b"chunk");
+
+    let json = serde_json::to_string(&encryptor.save_state()).unwrap();
+    let state = serde_json::from_str(&json).unwrap();
+    assert_eq!(encryptor.save_state(), state);
+
+    let mut restored = Aes256SivEncryptor64::restore(&key, state);
+    assert_eq!(
+        restored.encrypt_next(b"", b"chunk"),
+        encryptor.encrypt_next(b"", b"chunk")
+    );
+}
+
+#[test]
+fn aes_siv_stream_counter_overflow() {
+    let key = [0x42u8; 32];
+    let state = StreamState {
+        nonce: [0u8; 8],
+        position: !0 - 1,
+    };
+
+    let mut encryptor = Aes128SivEncryptor::restore(&key, state);
+    let ciphertext = encryptor.try_encrypt_next(b"", b"chunk").unwrap();
+    assert_eq!(
+        encryptor.try_encrypt_next(b"", b"chunk"),
+        Err(Error::CounterOverflow)
+    );
+
+    let mut buffer = [0u8; 21];
+    assert_eq!(
+        encryptor.try_encrypt_next_in_place(b"", &mut buffer),
+        Err(Error::CounterOverflow)
+    );
+    assert_eq!(buffer, [0u8; 21]);
+
+    // The final message can still be encrypted at the maximum position
+    let last = encryptor.encrypt_last(b"", b"last");
+
+    let mut decryptor = Aes128SivDecryptor::restore(&key, state);
+    assert_eq!(decryptor.decrypt_next(b"", &ciphertext).unwrap(), b"chunk");
+    assert_eq!(
+        decryptor.decrypt_next(b"", &ciphertext),
+        Err(Error::CounterOverflow)
+    );
+    assert_eq!(decryptor.decrypt_last(b"", &last).unwrap(), b"last");
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn aes_siv_stream_generate() {
+    use rand_chacha::{
+        rand_core::{RngCore, SeedableRng},
+        ChaCha8Rng,
+    };
+
+    let key = [0x42u8; 32];
+    let (mut encryptor, nonce) =
+        Aes128SivEncryptor::generate(&key, &mut ChaCha8Rng::seed_from_u64(1));
+
+    let mut expected_nonce = [0u8; 8];
+    ChaCha8Rng::seed_from_u64(1).fill_bytes(&mut expected_nonce);
+    assert_eq!(nonce, expected_nonce);
+
+    let ciphertext = encryptor.encrypt_next(b"", b"chunk");
+    let mut decryptor = Aes128SivDecryptor::new(&key, &expected_nonce);
+    assert_eq!(decryptor.decrypt_next(b"", &ciphertext).unwrap(), b"chunk");
+}
+
+#[test]
+fn aes_siv_stream_unexpected_last_block() {
+    let key = [0x42u8; 32];
+    let nonce = [0u8; 8];
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let first = encryptor.encrypt_next(b"", b"first");
+    let last = encryptor.encrypt_last(b"", b"last");
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    decryptor.decrypt_next(b"", &first).unwrap();
+    assert_eq!(
+        decryptor.decrypt_next(b"", &last),
+        Err(Error::UnexpectedLastBlock)
+    );
+
+    let mut buffer = last.clone();
+    assert_eq!(
+        decryptor.decrypt_next_in_place(b"", &mut buffer),
+        Err(Error::UnexpectedLastBlock)
+    );
+    assert_eq!(buffer, last);
+
+    // A tampered message is still an authentication failure
+    let mut tampered = last.clone();
+    tampered[0] ^= 1;
+    assert_eq!(
+        decryptor.decrypt_next_in_place(b"", &mut tampered),
+        Err(Error::AuthenticationFailed)
+    );
+    assert!(tampered.iter().all(|&b| b == 0));
+
+    assert_eq!(decryptor.position(), 1);
+    assert_eq!(
+        decryptor.decrypt_last_in_place(b"", &mut buffer).unwrap(),
+        b"last"
+    );
+}
+
+#[test]
+fn aes_siv_stream_too_many_headers() {
+    use miscreant::siv::MAX_HEADERS;
+
+    let key = [0x42u8; 32];
+    let nonce = [0u8; 8];
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let ciphertext = encryptor.encrypt_next(b"", b"first");
+    let headers = vec![&b""[..]; MAX_HEADERS];
+
+    // Rejected up front, so neither attempt touches the buffer
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    let mut buffer = ciphertext.clone();
+    assert_eq!(
+        decryptor.decrypt_next_headers_in_place(&headers, &mut buffer),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(buffer, ciphertext);
+    assert_eq!(decryptor.position(), 0);
+}
+
+#[test]
+fn aes_siv_stream_verify() {
+    let key = [0x42u8; 32];
+    let nonce = [0u8; 8];
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let first = encryptor.encrypt_next(b"", b"first");
+    let last = encryptor.encrypt_last(b"", b"last");
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    decryptor.verify_next(b"", &first).unwrap();
+    assert_eq!(
+        decryptor.verify_next(b"", &first),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        decryptor.verify_next(b"", &last),
+        Err(Error::UnexpectedLastBlock)
+    );
+
+    assert_eq!(decryptor.position(), 1);
+    decryptor.verify_last(b"", &last).unwrap();
+}
+
+/// (associated data, ciphertext) pairs for the given messages
+fn pairs(messages: &[Vec<u8>]) -> Vec<(&[u8], &[u8])> {
+    messages
+        .iter()
+        .map(|m| (&b"ad"[..], m.as_slice()))
+        .collect()
+}
+
+#[test]
+fn aes_siv_stream_verify_stream() {
+    let key = [0x42u8; 32];
+    let nonce = [0u8; 8];
+    let messages: Vec<_> = Aes128SivEncryptor::new(&key, &nonce)
+        .encrypt_chunks(b"ad", b"a message split into several chunks", 8)
+        .collect();
+    let decryptor = Aes128SivDecryptor::new(&key, &nonce);
+
+    decryptor.clone().verify_stream(pairs(&messages)).unwrap();
+
+    // Truncated
+    assert_eq!(
+        decryptor
+            .clone()
+            .verify_stream(pairs(&messages[..messages.len() - 1])),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        decryptor.clone().verify_stream(pairs(&[])),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // Reordered
+    let mut reordered = messages.clone();
+    reordered.swap(0, 1);
+    assert_eq!(
+        decryptor.clone().verify_stream(pairs(&reordered)),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // Extended past the last message
+    let mut extended = messages.clone();
+    extended.push(messages[0].clone());
+    assert_eq!(
+        decryptor.clone().verify_stream(pairs(&extended)),
+        Err(Error::UnexpectedLastBlock)
+    );
+
+    // Wrong associated data
+    let mut pairs = pairs(&messages);
+    pairs[1].0 = b"other ad";
+    assert_eq!(
+        decryptor.verify_stream(pairs),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[test]
+fn aes_siv_stream_decrypt_auto() {
+    let key = [0x42u8; 32];
+    let nonce = [0u8; 8];
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let mut messages = vec![
+        encryptor.encrypt_next(b"", b"first"),
+        encryptor.encrypt_next(b"", b"second"),
+    ];
+    messages.push(encryptor.encrypt_last(b"", b"last"));
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    let mut plaintexts = vec![];
+
+    for (i, message) in messages.iter().enumerate() {
+        let mut buffer = message.clone();
+        let (last, plaintext) = decryptor.decrypt_auto_in_place(b"", &mut buffer).unwrap();
+        assert_eq!(last, i == messages.len() - 1);
+        plaintexts.push(plaintext.to_vec());
+    }
+
+    assert_eq!(plaintexts, [&b"first"[..], b"second", b"last"]);
+
+    // The STREAM is finished once the last message has been returned, so a
+    // repeated last message (or anything else) is rejected
+    let last = messages.last().unwrap();
+    assert_eq!(
+        decryptor.decrypt_auto(b"", last),
+        Err(Error::UnexpectedLastBlock)
+    );
+    let mut buffer = last.clone();
+    assert_eq!(
+        decryptor.decrypt_auto_in_place(b"", &mut buffer),
+        Err(Error::UnexpectedLastBlock)
+    );
+    assert_eq!(&buffer, last);
+    assert_eq!(
+        decryptor.decrypt_next(b"", &messages[0]),
+        Err(Error::UnexpectedLastBlock)
+    );
+    assert_eq!(
+        decryptor.decrypt_last(b"", last),
+        Err(Error::UnexpectedLastBlock)
+    );
+
+    // Authentication failures aren't masked by the last block check
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    let mut tampered = messages[0].clone();
+    tampered[20] ^= 1;
+    assert_eq!(
+        decryptor.decrypt_auto(b"", &tampered),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        decryptor.decrypt_auto(b"", &messages[0]),
+        Ok((false, b"first".to_vec()))
+    );
+}
+
+#[test]
+fn aes_siv_stream_encrypt_chunks() {
+    let key = [0x42u8; 32];
+    let nonce = [0u8; 8];
+    let plaintext: Vec<u8> = (0..100).collect();
+
+    let messages: Vec<_> = Aes128SivEncryptor::new(&key, &nonce)
+        .encrypt_chunks(b"ad", &plaintext, 32)
+        .collect();
+
+    assert_eq!(
+        messages.iter().map(Vec::len).collect::<Vec<_>>(),
+        [48, 48, 48, 20]
+    );
+    assert_eq!(Aes128SivEncryptor::ciphertext_len(100, 32), 164);
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    let mut decrypted = vec![];
+
+    for message in &messages[..3] {
+        decrypted.extend(decryptor.decrypt_next(b"ad", message).unwrap());
+    }
+
+    decrypted.extend(decryptor.decrypt_last(b"ad", &messages[3]).unwrap());
+    assert_eq!(decrypted, plaintext);
+
+    // An exact multiple of the chunk size doesn't add an empty final message
+    let messages: Vec<_> = Aes128SivEncryptor::new(&key, &nonce)
+        .encrypt_chunks(b"", &plaintext[..64], 32)
+        .collect();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(Aes128SivEncryptor::ciphertext_len(64, 32), 96);
+
+    // An empty plaintext still produces a final message
+    let messages: Vec<_> = Aes128SivEncryptor::new(&key, &nonce)
+        .encrypt_chunks(b"", b"", 32)
+        .collect();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(Aes128SivEncryptor::ciphertext_len(0, 32), 16);
+    assert_eq!(
+        Aes128SivDecryptor::new(&key, &nonce).decrypt_last(b"", &messages[0]),
+        Ok(vec![])
+    );
+}
+
+#[test]
+fn aes_siv_stream_clone() {
+    let key = [0x42u8; 32];
+    let nonce = [0u8; 8];
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let first = encryptor.encrypt_next(b"", b"first");
+
+    let mut fork = encryptor.clone();
+    assert_eq!(fork.position(), 1);
+    let second = encryptor.encrypt_next(b"", b"second");
+    assert_eq!(fork.encrypt_next(b"", b"second"), second);
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(decryptor.decrypt_next(b"", &first).unwrap(), b"first");
+
+    let snapshot = decryptor.clone();
+    assert_eq!(decryptor.decrypt_next(b"", &second).unwrap(), b"second");
+
+    // Rolling back to the snapshot replays the same position
+    let mut decryptor = snapshot;
+    assert_eq!(decryptor.position(), 1);
+    assert_eq!(decryptor.decrypt_next(b"", &second).unwrap(), b"second");
+
+    let last = encryptor.encrypt_last(b"", b"last");
+    assert_eq!(decryptor.decrypt_last(b"", &last).unwrap(), b"last");
+}
+
+#[test]
+fn aes_siv_stream_debug_redacts_key() {
+    let mut encryptor = Aes128SivEncryptor::new(&[0x42; 32], &[0x24; 8]);
+    encryptor.encrypt_next(b"", b"");
+    assert_eq!(format!("{:?}", encryptor), "Encryptor { position: 1 }");
+
+    let decryptor = Aes128SivDecryptor::new(&[0x42; 32], &[0x24; 8]);
+    assert_eq!(format!("{:?}", decryptor), "Decryptor { position: 0 }");
+}
+
+#[test]
+fn aes_siv_stream_rekey() {
+    let old_key = [0x42u8; 32];
+    let new_key = [0x43u8; 32];
+    let nonce = [0u8; 8];
+
+    let mut encryptor = Aes128SivEncryptor::new(&old_key, &nonce);
+    let first = encryptor.encrypt_next(b"", b"first");
+    encryptor.rekey(&new_key);
+    assert_eq!(encryptor.position(), 1);
+    let second = encryptor.encrypt_next(b"", b"second");
+    let last = encryptor.encrypt_last(b"", b"last");
+
+    let mut decryptor = Aes128SivDecryptor::new(&old_key, &nonce);
+    assert_eq!(decryptor.decrypt_next(b"", &first).unwrap(), b"first");
+    assert_eq!(
+        decryptor.decrypt_next(b"", &second),
+        Err(Error::AuthenticationFailed)
+    );
+    decryptor.rekey(&new_key);
+    assert_eq!(decryptor.decrypt_next(b"", &second).unwrap(), b"second");
+    assert_eq!(decryptor.decrypt_last(b"", &last).unwrap(), b"last");
+}
+
+#[test]
+fn aes_siv_stream_peek_nonces() {
+    let key = [0x42u8; 32];
+    let nonce = [0x24u8; 8];
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    encryptor.encrypt_next(b"", b"first");
+
+    let mut expected = [0x24u8; 13];
+    expected[8..12].copy_from_slice(&1u32.to_be_bytes());
+    expected[12] = 0;
+    assert_eq!(encryptor.next_nonce(), &expected[..]);
+
+    expected[12] = 1;
+    assert_eq!(encryptor.final_nonce(), expected);
+    assert_eq!(encryptor.final_nonce(), expected);
+    assert_eq!(encryptor.position(), 1);
+
+    // Messages are encrypted under exactly these nonces
+    let mut aead = Aes128SivAead::new(&key);
+    let next = aead.encrypt(encryptor.next_nonce(), b"", b"second");
+    assert_eq!(encryptor.encrypt_next(b"", b"second"), next);
+
+    let last = aead.encrypt(&encryptor.final_nonce(), b"", b"last");
+    assert_eq!(encryptor.encrypt_last(b"", b"last"), last);
+}
+
+#[test]
+fn aes_siv_stream_reset_nonce() {
+    let key = [0x42u8; 32];
+    let mut encryptor = Aes128SivEncryptor::new(&key, &[0u8; 8]);
+    encryptor.encrypt_next(b"", b"first");
+
+    encryptor.reset_nonce(&[1u8; 8]);
+    assert_eq!(encryptor.position(), 0);
+
+    let mut fresh = Aes128SivEncryptor::new(&key, &[1u8; 8]);
+    assert_eq!(
+        encryptor.encrypt_next(b"", b"first"),
+        fresh.encrypt_next(b"", b"first")
+    );
+    assert_eq!(
+        encryptor.encrypt_last(b"", b"last"),
+        fresh.encrypt_last(b"", b"last")
+    );
+}
+
+#[test]
+#[should_panic(expected = "incorrect nonce size")]
+fn aes_siv_stream_reset_nonce_wrong_size() {
+    let mut encryptor = Aes128SivEncryptor::new(&[0x42u8; 32], &[0u8; 8]);
+    encryptor.reset_nonce(&[1u8; 7]);
+}
+
+#[test]
+fn aes_siv_stream_try_new() {
+    assert!(Aes128SivEncryptor::try_new(&[0x42; 32], &[0u8; 8]).is_ok());
+    assert_eq!(
+        Aes128SivEncryptor::try_new(&[0x42; 31], &[0u8; 8]).unwrap_err(),
+        Error::InvalidKeySize {
+            expected: 32,
+            got: 31
+        }
+    );
+    assert_eq!(
+        Aes128SivDecryptor::try_new(&[0x42; 32], &[0u8; 9]).unwrap_err(),
+        Error::InvalidNonceSize {
+            expected: 8,
+            got: 9
+        }
+    );
+    assert_eq!(
+        Aes256SivDecryptor64::try_new(&[0x42; 64], &[0u8; 8]).unwrap_err(),
+        Error::InvalidNonceSize {
+            expected: 4,
+            got: 8
+        }
+    );
+}
+
+#[test]
+fn aes_192_siv_stream_round_trip() {
+    let key = [0x42u8; 48];
+    let nonce = [0u8; 8];
+
+    let mut encryptor = Aes192SivEncryptor::new(&key, &nonce);
+    let first = encryptor.encrypt_next(b"", b"first");
+    let last = encryptor.encrypt_last(b"", b"last");
+
+    let mut decryptor = Aes192SivDecryptor::new(&key, &nonce);
+    assert_eq!(decryptor.decrypt_next(b"", &first).unwrap(), b"first");
+    assert_eq!(decryptor.decrypt_last(b"", &last).unwrap(), b"last");
+
+    let mut encryptor = Aes192PmacSivEncryptor::new(&key, &nonce);
+    let first = encryptor.encrypt_next(b"", b"first");
+    let last = encryptor.encrypt_last(b"", b"last");
+
+    let mut decryptor = Aes192PmacSivDecryptor::new(&key, &nonce);
+    assert_eq!(decryptor.decrypt_next(b"", &first).unwrap(), b"first");
+    assert_eq!(decryptor.decrypt_last(b"", &last).unwrap(), b"last");
+}
+
+#[test]
+fn aes_siv_stream_nonce_encoders_zeroize_on_drop() {
+    assert!(std::mem::needs_drop::<NonceEncoder32>());
+    assert!(std::mem::needs_drop::<NonceEncoder64>());
+}
+
+#[test]
+fn nonce_tracker() {
+    let key = [0x42; 32];
+    let mut tracker = NonceTracker::new(2);
+
+    let encryptor: Aes128SivEncryptor = tracker.new_encryptor(&key, b"nonce 1!").unwrap();
+    drop(encryptor);
+    assert!(tracker.contains(b"nonce 1!"));
+
+    assert_eq!(
+        tracker
+            .new_encryptor::<Aes128SivAead, NonceEncoder32>(&key, b"nonce 1!")
+            .unwrap_err(),
+        Error::NonceReused
+    );
+
+    // Invalid nonces are rejected without being remembered
+    assert_eq!(
+        tracker
+            .new_encryptor::<Aes128SivAead, NonceEncoder32>(&key, b"short")
+            .unwrap_err(),
+        Error::InvalidNonceSize {
+            expected: 8,
+            got: 5
+        }
+    );
+    assert_eq!(tracker.len(), 1);
+
+    // Only the most recent nonces are remembered
+    for nonce in &[b"nonce 2!", b"nonce 3!"] {
+        tracker
+            .new_encryptor::<Aes128SivAead, NonceEncoder32>(&key, *nonce)
+            .unwrap();
+    }
+
+    assert_eq!(tracker.len(), 2);
+    assert!(!tracker.contains(b"nonce 1!"));
+    assert!(tracker.contains(b"nonce 3!"));
+}
+
+#[test]
+fn stream_no_ad() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+
+    let mut expected = Aes128SivEncryptor::new(&key, &nonce);
+    let first = expected.encrypt_next(b"", b"first");
+    let last = expected.encrypt_last(b"", b"last");
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    assert_eq!(encryptor.encrypt_next_no_ad(b"first"), first);
+    assert_eq!(encryptor.encrypt_last_no_ad(b"last"), last);
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let mut buffer = [0u8; 21];
+    buffer[16..].copy_from_slice(b"first");
+    encryptor.encrypt_next_in_place_no_ad(&mut buffer);
+    assert_eq!(&buffer[..], &first[..]);
+    let mut last_buffer = [0u8; 20];
+    last_buffer[16..].copy_from_slice(b"last");
+    encryptor.encrypt_last_in_place_no_ad(&mut last_buffer);
+    assert_eq!(&last_buffer[..], &last[..]);
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(decryptor.decrypt_next_no_ad(&first).unwrap(), b"first");
+    assert_eq!(decryptor.decrypt_last_no_ad(&last).unwrap(), b"last");
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(
+        decryptor.decrypt_next_in_place_no_ad(&mut buffer).unwrap(),
+        b"first"
+    );
+    assert_eq!(
+        decryptor
+            .decrypt_last_in_place_no_ad(&mut last_buffer)
+            .unwrap(),
+        b"last"
+    );
+}
+
+#[test]
+fn stream_counter_binding() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce).with_counter_binding();
+    let first = encryptor.encrypt_next(b"ad", b"first");
+    let second = encryptor.encrypt_next(b"ad", b"second");
+    let last = encryptor.encrypt_last(b"ad", b"last");
+
+    // The counter is appended to the associated data as a big-endian u32
+    let mut unbound = Aes128SivAead::new(&key);
+    let mut expected_nonce = [0u8; 13];
+    expected_nonce[..8].copy_from_slice(&nonce);
+    assert_eq!(
+        unbound.encrypt(&expected_nonce, b"ad\0\0\0\0", b"first"),
+        first
+    );
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce).with_counter_binding();
+    assert_eq!(decryptor.decrypt_next(b"ad", &first).unwrap(), b"first");
+    assert_eq!(decryptor.decrypt_next(b"ad", &second).unwrap(), b"second");
+    assert_eq!(
+        decryptor.decrypt_next(b"ad", &last),
+        Err(Error::UnexpectedLastBlock)
+    );
+    assert_eq!(decryptor.decrypt_last(b"ad", &last).unwrap(), b"last");
+
+    // A decryptor without counter binding rejects every message
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(
+        decryptor.decrypt_next(b"ad", &first),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // Replayed messages fail to authenticate
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce).with_counter_binding();
+    assert!(decryptor.decrypt_next(b"ad", &first).is_ok());
+    assert_eq!(
+        decryptor.decrypt_next(b"ad", &first),
+        Err(Error::AuthenticationFailed)
+    );
+
+    // In-place decryption enforces the same binding
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce).with_counter_binding();
+    let mut buffer = first.clone();
+    assert_eq!(
+        decryptor.decrypt_next_in_place(b"ad", &mut buffer).unwrap(),
+        b"first"
+    );
+}
+
+#[test]
+fn stream_with_counter() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let messages: Vec<_> = (0..3)
+        .map(|_| encryptor.encrypt_next(b"", b"chunk"))
+        .collect();
+    let last = encryptor.encrypt_last(b"", b"last");
+
+    let mut encryptor = Aes128SivEncryptor::with_counter(&key, &nonce, 2);
+    assert_eq!(encryptor.position(), 2);
+    assert_eq!(encryptor.encrypt_next(b"", b"chunk"), messages[2]);
+    assert_eq!(encryptor.encrypt_last(b"", b"last"), last);
+
+    // The last block flag is set at any starting position
+    assert_eq!(
+        Aes128SivEncryptor::with_counter(&key, &nonce, 3).encrypt_last(b"", b"last"),
+        last
+    );
+
+    let mut decryptor = Aes128SivDecryptor::with_counter(&key, &nonce, 1);
+    assert_eq!(decryptor.decrypt_next(b"", &messages[1]).unwrap(), b"chunk");
+    assert_eq!(
+        decryptor.decrypt_next(b"", &messages[1]),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(decryptor.decrypt_next(b"", &messages[2]).unwrap(), b"chunk");
+    assert_eq!(decryptor.decrypt_last(b"", &last).unwrap(), b"last");
+}
+
+#[test]
+fn stream_skip() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let messages: Vec<_> = (0..4)
+        .map(|_| encryptor.encrypt_next(b"", b"chunk"))
+        .collect();
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    decryptor.skip(0).unwrap();
+    decryptor.skip(3).unwrap();
+    assert_eq!(decryptor.position(), 3);
+    assert_eq!(decryptor.decrypt_next(b"", &messages[3]).unwrap(), b"chunk");
+
+    // Skipping may reach the maximum position, but not go past it
+    let state = StreamState {
+        nonce,
+        position: u32::max_value() - 2,
+    };
+    let mut decryptor = Aes128SivDecryptor::restore(&key, state);
+    assert_eq!(decryptor.skip(3), Err(Error::CounterOverflow));
+    assert_eq!(decryptor.position(), u32::max_value() - 2);
+    decryptor.skip(2).unwrap();
+    assert_eq!(decryptor.position(), u32::max_value());
+
+    // The provided implementation for other counters behaves the same
+    let prefix = [0x07u8; 7];
+    let mut encryptor = Encryptor::<Aes128SivAead, NonceEncoder40>::new(&key, &prefix);
+    encryptor.encrypt_next(b"", b"first");
+    let second = encryptor.encrypt_next(b"", b"second");
+
+    let mut decryptor = Decryptor::<Aes128SivAead, NonceEncoder40>::new(&key, &prefix);
+    decryptor.skip(1).unwrap();
+    assert_eq!(decryptor.decrypt_next(b"", &second).unwrap(), b"second");
+
+    let state = StreamState {
+        nonce: prefix,
+        position: NonceEncoder40::MAX_POSITION - 1,
+    };
+    let mut decryptor = Decryptor::<Aes128SivAead, NonceEncoder40>::restore(&key, state);
+    assert_eq!(decryptor.skip(2), Err(Error::CounterOverflow));
+    assert_eq!(decryptor.position(), NonceEncoder40::MAX_POSITION - 1);
+}
+
+#[test]
+fn stream_finish() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+
+    let mut expected = Aes128SivEncryptor::new(&key, &nonce);
+    let first = expected.encrypt_next(b"", b"first");
+    let last = expected.encrypt_last(b"", b"last");
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    assert_eq!(encryptor.encrypt_next(b"", b"first"), first);
+    let (ciphertext, finished) = encryptor.finish(b"", b"last");
+    assert_eq!(ciphertext, last);
+    assert_eq!(finished.position(), 1);
+    finished.close();
+
+    let mut buffer = [0u8; 20];
+    buffer[16..].copy_from_slice(b"last");
+    let finished = Aes128SivEncryptor::new(&key, &nonce).finish_in_place(b"", &mut buffer);
+    assert_eq!(finished.position(), 0);
+    assert_eq!(
+        Aes128SivDecryptor::new(&key, &nonce)
+            .decrypt_last_in_place(b"", &mut buffer)
+            .unwrap(),
+        b"last"
+    );
+}
+
+#[test]
+fn stream_remaining() {
+    let key = [0x42; 32];
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &[0u8; 8]);
+    assert_eq!(encryptor.remaining(), u32::max_value());
+    encryptor.encrypt_next(b"", b"chunk");
+    assert_eq!(encryptor.remaining(), u32::max_value() - 1);
+
+    let state = StreamState {
+        nonce: [0u8; 8],
+        position: u32::max_value() - 1,
+    };
+    let mut encryptor = Aes128SivEncryptor::restore(&key, state);
+    assert_eq!(encryptor.remaining(), 1);
+    encryptor.encrypt_next(b"", b"chunk");
+    assert_eq!(encryptor.remaining(), 0);
+    assert_eq!(
+        encryptor.try_encrypt_next(b"", b"chunk"),
+        Err(Error::CounterOverflow)
+    );
+    encryptor.encrypt_last(b"", b"last");
+
+    let encryptor = Aes256SivEncryptor64::new(&[0x42; 64], &[0u8; NONCE_SIZE_64]);
+    assert_eq!(encryptor.remaining(), u64::max_value());
+}
+
+#[test]
+fn stream_prefixed_nonce() {
+    let key = [0x42; 32];
+    let prefix = [7u8; NONCE_SIZE_96];
+
+    let mut encryptor = Encryptor96::<Aes128SivAead>::new(&key, &prefix);
+    let first = encryptor.encrypt_next(b"ad", b"first");
+    let last = encryptor.encrypt_last(b"ad", b"last");
+
+    // 12-byte prefix, big-endian counter, and last block flag
+    let mut nonce = [0u8; 17];
+    nonce[..12].copy_from_slice(&prefix);
+    let mut aead = Aes128SivAead::new(&key);
+    assert_eq!(aead.encrypt(&nonce, b"ad", b"first"), first);
+    nonce[15] = 1;
+    nonce[16] = 1;
+    assert_eq!(aead.encrypt(&nonce, b"ad", b"last"), last);
+
+    let mut decryptor = Decryptor96::<Aes128SivAead>::new(&key, &prefix);
+    assert_eq!(decryptor.decrypt_next(b"ad", &first).unwrap(), b"first");
+    assert_eq!(decryptor.save_state().nonce, prefix);
+    assert_eq!(decryptor.decrypt_last(b"ad", &last).unwrap(), b"last");
+
+    // Other prefix sizes, up to the full 128-bit SIV nonce width
+    let prefix = [9u8; 16];
+    let mut encryptor =
+        Encryptor::<Aes128SivAead, PrefixedNonceEncoder<[u8; 16]>>::new(&key, &prefix);
+    let first = encryptor.encrypt_next(b"", b"first");
+    assert_eq!(encryptor.next_nonce().len(), 21);
+    let mut decryptor =
+        Decryptor::<Aes128SivAead, PrefixedNonceEncoder<[u8; 16]>>::new(&key, &prefix);
+    assert_eq!(decryptor.decrypt_next(b"", &first).unwrap(), b"first");
+
+    assert_eq!(
+        Encryptor96::<Aes128SivAead>::try_new(&key, &[0u8; 8]).unwrap_err(),
+        Error::InvalidNonceSize {
+            expected: 12,
+            got: 8
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "nonce prefix too large")]
+fn stream_prefixed_nonce_too_large() {
+    Encryptor::<Aes128SivAead, PrefixedNonceEncoder<[u8; 17]>>::new(&[0x42; 32], &[0u8; 17]);
+}
+
+#[test]
+fn stream_decrypt_next_zeroizing() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let first = encryptor.encrypt_next(b"ad", b"secret");
+    let second = encryptor.encrypt_next(b"ad", b"more secret");
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    let plaintext: Zeroizing<Vec<u8>> = decryptor.decrypt_next_zeroizing(b"ad", &first).unwrap();
+    assert_eq!(&plaintext[..], b"secret");
+    assert_eq!(
+        decryptor.decrypt_next_zeroizing(b"", &second).unwrap_err(),
+        Error::AuthenticationFailed
+    );
+    assert_eq!(
+        &decryptor.decrypt_next_zeroizing(b"ad", &second).unwrap()[..],
+        b"more secret"
+    );
+}
+
+#[test]
+fn stream_empty_last_block() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+    let chunks: &[&[u8]] = &[b"0123456789abcdef", b"fedcba9876543210"];
+
+    // Several full messages followed by an empty last message, which is
+    // just a tag
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let mut messages: Vec<_> = chunks
+        .iter()
+        .map(|chunk| encryptor.encrypt_next(b"ad", chunk))
+        .collect();
+
+    let mut last = [0u8; 16];
+    encryptor.clone().encrypt_last_in_place(b"ad", &mut last);
+    let last_message = encryptor.encrypt_last(b"ad", b"");
+    assert_eq!(last_message, last);
+    messages.push(last_message);
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    for (chunk, message) in chunks.iter().zip(&messages) {
+        assert_eq!(&decryptor.decrypt_next(b"ad", message).unwrap(), chunk);
+    }
+
+    // Decrypted as a message in the middle of the STREAM, it's detected as
+    // the last one
+    assert_eq!(
+        decryptor.decrypt_next(b"ad", &messages[2]),
+        Err(Error::UnexpectedLastBlock)
+    );
+
+    let mut buffer = last;
+    assert_eq!(
+        decryptor
+            .clone()
+            .decrypt_last_in_place(b"ad", &mut buffer)
+            .unwrap(),
+        b""
+    );
+    assert_eq!(
+        decryptor.clone().decrypt_auto(b"ad", &messages[2]).unwrap(),
+        (true, vec![])
+    );
+    assert!(decryptor
+        .decrypt_last(b"ad", &messages[2])
+        .unwrap()
+        .is_empty());
+
+    // A STREAM consisting solely of one empty last message
+    let message = Aes128SivEncryptor::new(&key, &nonce).encrypt_last(b"ad", b"");
+    assert_eq!(message.len(), 16);
+    assert_eq!(
+        Aes128SivEncryptor::new(&key, &nonce)
+            .encrypt_chunks(b"ad", b"", 16)
+            .collect::<Vec<_>>(),
+        vec![message.clone()]
+    );
+
+    let decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(
+        decryptor.clone().decrypt_next(b"ad", &message),
+        Err(Error::UnexpectedLastBlock)
+    );
+    assert_eq!(
+        decryptor.clone().decrypt_last(b"other ad", &message),
+        Err(Error::AuthenticationFailed)
+    );
+    assert!(decryptor.decrypt_last(b"ad", &message).unwrap().is_empty());
+
+    // A truncated (shorter than a tag) last message is rejected
+    assert_eq!(
+        Aes128SivDecryptor::new(&key, &nonce).decrypt_last(b"ad", &message[..15]),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[test]
+fn aes_siv_stream_headers() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+    let index = 0u32.to_be_bytes();
+    let headers: &[&[u8]] = &[&index, b"type", b"length"];
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce);
+    let first = encryptor.encrypt_next_headers(headers, b"first");
+    let last = encryptor.encrypt_last_headers(headers, b"last");
+
+    // Each header is a separate S2V input, unlike concatenated AD
+    let mut siv = Aes128SivAead::new(&key);
+    let mut expected_nonce = [0u8; 13];
+    expected_nonce[..8].copy_from_slice(&nonce);
+    assert_eq!(
+        siv.encrypt_headers(&expected_nonce, headers, b"first"),
+        first
+    );
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(
+        decryptor.decrypt_next_headers(headers, &first).unwrap(),
+        b"first"
+    );
+
+    let mut buffer = last.clone();
+    assert_eq!(
+        decryptor.decrypt_next_headers_in_place(headers, &mut buffer),
+        Err(Error::UnexpectedLastBlock)
+    );
+    assert_eq!(buffer, last);
+    assert_eq!(
+        decryptor
+            .decrypt_last_headers_in_place(headers, &mut buffer)
+            .unwrap(),
+        b"last"
+    );
+
+    // Decryption must use the identical set of headers
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(
+        decryptor.decrypt_next_headers(&headers[..2], &first),
+        Err(Error::AuthenticationFailed)
+    );
+    assert_eq!(
+        decryptor.decrypt_next(b"type", &first),
+        Err(Error::AuthenticationFailed)
+    );
+}
+
+#[test]
+fn aes_siv_stream_headers_counter_binding() {
+    let key = [0x42; 32];
+    let nonce = [7u8; 8];
+    let headers: &[&[u8]] = &[b"type", b"length"];
+
+    let mut encryptor = Aes128SivEncryptor::new(&key, &nonce).with_counter_binding();
+    let first = encryptor.encrypt_next_headers(headers, b"first");
+    let single = encryptor.encrypt_next_headers(&[b"ad"], b"second");
+    let last = encryptor.encrypt_last_headers(&[], b"last");
+
+    // The counter is appended to the last header (or is the only header)
+    let mut siv = Aes128SivAead::new(&key);
+    let mut expected_nonce = [0u8; 13];
+    expected_nonce[..8].copy_from_slice(&nonce);
+    assert_eq!(
+        siv.encrypt_headers(&expected_nonce, &[b"type", b"length\0\0\0\0"], b"first"),
+        first
+    );
+
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce).with_counter_binding();
+    assert_eq!(
+        decryptor.decrypt_next_headers(headers, &first).unwrap(),
+        b"first"
+    );
+
+    // A single header is equivalent to the `ad` of `decrypt_next`
+    assert_eq!(decryptor.decrypt_next(b"ad", &single).unwrap(), b"second");
+    assert_eq!(decryptor.decrypt_last_headers(&[], &last).unwrap(), b"last");
+
+    // Without counter binding, the messages fail to authenticate
+    let mut decryptor = Aes128SivDecryptor::new(&key, &nonce);
+    assert_eq!(
+        decryptor.decrypt_next_headers(headers, &first),
+        Err(Error::AuthenticationFailed)
+    );
+}
@@ -0,0 +1,105 @@
+//! Tests for the WebAssembly bindings. Run them under Node.js with:
+//!
+//! `cargo test --target wasm32-unknown-unknown --features wasm --test wasm_test`
+//!
+//! (which requires `wasm-bindgen-test-runner` from `wasm-bindgen-cli` to be
+//! configured as the runner for the `wasm32-unknown-unknown` target)
+
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use js_sys::{Array, Uint8Array};
+use miscreant::{wasm::WasmAead, Aead, Aes128PmacSivAead, Aes256SivAead};
+use subtle_encoding::hex;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const KEY: &str = "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff";
+const NONCE: &str = "101112131415161718191a1b1c1d1e1f2021222324252627";
+const PLAINTEXT: &str = "112233445566778899aabbccddee";
+
+fn decode(s: &str) -> Vec<u8> {
+    hex::decode(s).unwrap()
+}
+
+fn array(items: &[&[u8]]) -> JsValue {
+    items
+        .iter()
+        .map(|item| JsValue::from(Uint8Array::from(*item)))
+        .collect::<Array>()
+        .into()
+}
+
+#[wasm_bindgen_test]
+fn seal_examples() {
+    for &(alg, ciphertext) in &[
+        (
+            "AES-SIV",
+            "4b3d0f15ae9ffa9e65b949421582ef70e410910d6446c7759ebff9b5385a",
+        ),
+        (
+            "AES-PMAC-SIV",
+            "3e6acab1cc2f4a847f8fa605e7e1ce55d9200b444571f8b8956eb3df5498",
+        ),
+    ] {
+        let mut aead = WasmAead::import_key(&decode(KEY), alg).unwrap();
+        let nonce = decode(NONCE);
+        let plaintext = decode(PLAINTEXT);
+
+        // Omitted, a single empty Uint8Array, and an array containing one are
+        // all the same associated data
+        for ad in &[
+            JsValue::UNDEFINED,
+            Uint8Array::new_with_length(0).into(),
+            array(&[b""]),
+        ] {
+            let sealed = aead.seal(&plaintext, &nonce, ad.clone()).unwrap();
+            assert_eq!(sealed, decode(ciphertext));
+            assert_eq!(aead.open(&sealed, &nonce, ad.clone()).unwrap(), plaintext);
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn seal_multiple_headers() {
+    let key = [7u8; 64];
+    let headers: &[&[u8]] = &[b"header 1", b"", b"header 3"];
+
+    let mut aead = WasmAead::import_key(&key, "AES-SIV").unwrap();
+    let sealed = aead.seal(b"plaintext", b"nonce", array(headers)).unwrap();
+
+    assert_eq!(
+        sealed,
+        Aes256SivAead::new(&key).encrypt_headers(b"nonce", headers, b"plaintext")
+    );
+
+    assert_eq!(
+        aead.open(&sealed, b"nonce", array(headers)).unwrap(),
+        b"plaintext"
+    );
+
+    // Splitting the associated data differently must not authenticate
+    assert!(aead
+        .open(&sealed, b"nonce", array(&[b"header 1header 3"]))
+        .is_err());
+}
+
+#[wasm_bindgen_test]
+fn open_tampered() {
+    let key = [1u8; 32];
+    let mut sealed = Aes128PmacSivAead::new(&key).encrypt(b"nonce", b"ad", b"plaintext");
+    sealed[20] ^= 1;
+
+    let mut aead = WasmAead::import_key(&key, "AES-PMAC-SIV").unwrap();
+    let ad = Uint8Array::from(&b"ad"[..]);
+    assert!(aead.open(&sealed, b"nonce", ad.into()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn invalid_arguments() {
+    assert!(WasmAead::import_key(&[0u8; 32], "AES-GCM").is_err());
+    assert!(WasmAead::import_key(&[0u8; 16], "AES-SIV").is_err());
+
+    let mut aead = WasmAead::import_key(&[0u8; 32], "AES-SIV").unwrap();
+    assert!(aead.seal(b"", b"", JsValue::from(42)).is_err());
+    assert!(aead.seal(b"", b"", array(&[&b""[..]; 126])).is_err());
+}
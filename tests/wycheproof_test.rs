@@ -0,0 +1,94 @@
+//! Wycheproof AEAD-AES-SIV-CMAC test vectors
+//!
+//! Vendored from <https://github.com/C2SP/wycheproof> (`testvectors`) into
+//! `tests/vectors/wycheproof`. "valid" vectors must encrypt to the given tag and
+//! ciphertext and decrypt successfully, "invalid" vectors must be rejected.
+
+use miscreant::{Aead, Aes128SivAead, Aes192SivAead, Aes256SivAead, Error, Tag};
+use std::{fs::File, io::Read};
+use subtle_encoding::hex;
+
+fn decode(value: &serde_json::Value) -> Vec<u8> {
+    hex::decode(value.as_str().expect("hex string")).expect("hex encoded")
+}
+
+/// Decrypt (and for "valid" vectors, encrypt) the given test case
+macro_rules! check {
+    ($aead:ty, $key:expr, $test:expr) => {{
+        let mut aead = <$aead>::new($key);
+        let test = $test;
+        let id = &test["tcId"];
+        let nonce = decode(&test["iv"]);
+        let ad = decode(&test["aad"]);
+        let msg = decode(&test["msg"]);
+        let ct = decode(&test["ct"]);
+        let tag_bytes = decode(&test["tag"]);
+        let valid = match test["result"].as_str().expect("result") {
+            "valid" => true,
+            "invalid" => false,
+            other => panic!("tcId {}: unexpected result: {}", id, other),
+        };
+
+        let mut tag = [0u8; 16];
+        tag.copy_from_slice(&tag_bytes);
+        let tag = Tag::new(tag);
+
+        let mut buffer = ct.clone();
+        let result = aead.decrypt_in_place_detached(&nonce, &ad, &mut buffer, &tag);
+
+        if valid {
+            assert_eq!(result, Ok(()), "tcId {}: valid vector rejected", id);
+            assert_eq!(buffer, msg, "tcId {}: wrong plaintext", id);
+
+            let mut buffer = msg.clone();
+            let computed = aead.encrypt_in_place_detached(&nonce, &ad, &mut buffer);
+            assert_eq!(computed.as_ref(), &tag_bytes[..], "tcId {}: wrong tag", id);
+            assert_eq!(buffer, ct, "tcId {}: wrong ciphertext", id);
+        } else {
+            assert_eq!(
+                result,
+                Err(Error::AuthenticationFailed),
+                "tcId {}: invalid vector accepted",
+                id
+            );
+            assert!(
+                buffer.iter().all(|&b| b == 0),
+                "tcId {}: buffer not zeroized",
+                id
+            );
+        }
+    }};
+}
+
+#[test]
+fn wycheproof_aead_aes_siv_cmac() {
+    let mut json = String::new();
+    File::open("tests/vectors/wycheproof/aead_aes_siv_cmac_test.json")
+        .expect("valid aead_aes_siv_cmac_test.json")
+        .read_to_string(&mut json)
+        .expect("aead_aes_siv_cmac_test.json read successfully");
+
+    let vectors: serde_json::Value =
+        serde_json::from_str(&json).expect("aead_aes_siv_cmac_test.json parses successfully");
+    let mut count = 0;
+
+    for group in vectors["testGroups"].as_array().expect("test groups") {
+        for test in group["tests"].as_array().expect("tests") {
+            let key = decode(&test["key"]);
+
+            match group["keySize"].as_u64().expect("key size") {
+                256 => check!(Aes128SivAead, &key, test),
+                384 => check!(Aes192SivAead, &key, test),
+                512 => check!(Aes256SivAead, &key, test),
+                other => panic!("unexpected key size: {}", other),
+            }
+
+            count += 1;
+        }
+    }
+
+    assert_eq!(
+        count,
+        vectors["numberOfTests"].as_u64().expect("number of tests")
+    );
+}